@@ -282,7 +282,6 @@ pub use crate::types::MatrixStageData as StageMatrixData;
 
 // CLUT
 pub use crate::types::CLutStageData as StageCLutData;
-pub use crate::types::CLutStageDataTab as StageCLutDataTab;
 
 //----------------------------------------------------------------------------------------------------------
 // Optimization. Using this plug-in, additional optimization strategies may be implemented.
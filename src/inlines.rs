@@ -58,9 +58,16 @@ pub const fn quick_floor(val: f64) -> i32 {
         pub halves: [i32; 2],
     }
 
-    let i = Split { val };
-
-    unsafe { i.halves[0] }
+    let i = Split {
+        val: val + DOUBLE_2_FIX_MAGIC,
+    };
+
+    // Uses limited precision (52-16=36 bits) to floor
+    if cfg!(target_endian = "big") {
+        unsafe { i.halves[1] >> 16 }
+    } else {
+        unsafe { i.halves[0] >> 16 }
+    }
 }
 
 #[inline]
@@ -81,6 +88,13 @@ pub fn quick_saturate_word(d: f64) -> u16 {
     quick_floor_word(d)
 }
 
+// Quantize a value 0 <= i < max_samples to 0..0xffff
+#[inline]
+pub fn quantize_val(i: f64, max_samples: usize) -> u16 {
+    let x = (i * 65535.0) / (max_samples - 1) as f64;
+    quick_saturate_word(x)
+}
+
 #[inline]
 pub fn lock_primitive<'a>(m: &'a dyn IMutex<'a>) -> Box<dyn MutexGuard + 'a> {
     m.lock()
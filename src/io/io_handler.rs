@@ -1,6 +1,14 @@
-use crate::Context;
+use std::{
+    fs::File,
+    io::{Cursor, Read, Seek, SeekFrom, Write},
+    path::Path,
+};
 
-use super::Stream;
+use log::Level;
+
+use crate::{signal_error, state::ErrorCode, Context, Result};
+
+use super::{NullStream, ReadOnlyStream, Stream};
 
 pub struct IoHandler {
     pub stream: Box<dyn Stream>,
@@ -14,3 +22,201 @@ pub struct IoHandler {
     pub tell: fn(iohandler: &mut Self) -> usize,
     pub write: fn(iohandler: &mut Self, size: usize, buffer: &[u8]) -> bool,
 }
+
+impl IoHandler {
+    /// Creates an IO handler around any seekable stream.
+    pub fn from_stream(
+        context_id: &'static Context,
+        stream: Box<dyn Stream>,
+        reported_size: usize,
+    ) -> IoHandler {
+        IoHandler {
+            stream,
+            context_id,
+            used_space: 0,
+            reported_size,
+            physical_file: String::new(),
+            read: stream_read,
+            seek: stream_seek,
+            close: stream_close,
+            tell: stream_tell,
+            write: stream_write,
+        }
+    }
+
+    /// Creates a read-only IO handler over a stream that cannot be written to.
+    pub fn from_reader(
+        context_id: &'static Context,
+        mut reader: impl Read + Seek + 'static,
+    ) -> Result<IoHandler> {
+        let reported_size = match reader
+            .seek(SeekFrom::End(0))
+            .and_then(|len| reader.seek(SeekFrom::Start(0)).map(|_| len))
+        {
+            Ok(len) => len as usize,
+            Err(_) => {
+                signal_error(context_id, Level::Error, ErrorCode::Seek, "Cannot get size of stream");
+                return Err("Seek error in IoHandler::from_reader".into());
+            }
+        };
+
+        Ok(Self::from_stream(
+            context_id,
+            Box::new(ReadOnlyStream(reader)),
+            reported_size,
+        ))
+    }
+
+    /// Creates an IO handler reading from a copy of a memory block.
+    pub fn from_mem(context_id: &'static Context, data: &[u8]) -> IoHandler {
+        Self::from_stream(context_id, Box::new(Cursor::new(data.to_vec())), data.len())
+    }
+
+    /// Creates an IO handler that writes to a growing memory block.
+    pub fn mem_writer(context_id: &'static Context) -> IoHandler {
+        Self::from_stream(context_id, Box::new(Cursor::new(Vec::<u8>::new())), 0)
+    }
+
+    /// Consumes a memory based IO handler, returning the contents of its memory block.
+    pub fn into_mem(self) -> Option<Vec<u8>> {
+        self.stream
+            .into_any()
+            .downcast::<Cursor<Vec<u8>>>()
+            .ok()
+            .map(|cursor| cursor.into_inner())
+    }
+
+    /// Opens a file for reading (`'r'`) or writing (`'w'`).
+    pub fn from_file(
+        context_id: &'static Context,
+        file_name: impl AsRef<Path>,
+        access_mode: char,
+    ) -> Result<IoHandler> {
+        let path = file_name.as_ref();
+
+        let mut result = match access_mode {
+            'r' => {
+                let file = match File::open(path) {
+                    Ok(file) => file,
+                    Err(_) => {
+                        let msg = format!("File '{}' not found", path.display());
+                        signal_error(context_id, Level::Error, ErrorCode::File, &msg);
+                        return Err(msg);
+                    }
+                };
+                let reported_size = match file.metadata() {
+                    Ok(meta) => meta.len() as usize,
+                    Err(_) => {
+                        let msg = format!("Cannot get size of file '{}'", path.display());
+                        signal_error(context_id, Level::Error, ErrorCode::File, &msg);
+                        return Err(msg);
+                    }
+                };
+
+                Self::from_stream(context_id, Box::new(ReadOnlyStream(file)), reported_size)
+            }
+            'w' => {
+                let file = match File::create(path) {
+                    Ok(file) => file,
+                    Err(_) => {
+                        let msg = format!("Couldn't create '{}'", path.display());
+                        signal_error(context_id, Level::Error, ErrorCode::File, &msg);
+                        return Err(msg);
+                    }
+                };
+
+                Self::from_stream(context_id, Box::new(file), 0)
+            }
+            _ => {
+                let msg = format!("Unknown access mode '{}'", access_mode);
+                signal_error(context_id, Level::Error, ErrorCode::File, &msg);
+                return Err(msg);
+            }
+        };
+
+        result.physical_file = path.display().to_string();
+        Ok(result)
+    }
+
+    /// Creates an IO handler that discards all data, but keeps track of the space used.
+    pub fn null(context_id: &'static Context) -> IoHandler {
+        Self::from_stream(context_id, Box::<NullStream>::default(), 0)
+    }
+}
+
+fn stream_read(iohandler: &mut IoHandler, buffer: &mut [u8], size: usize, count: usize) -> usize {
+    let len = size * count;
+
+    if len > buffer.len() {
+        signal_error(
+            iohandler.context_id,
+            Level::Error,
+            ErrorCode::Read,
+            "Read buffer is too small",
+        );
+        return 0;
+    }
+
+    match iohandler.stream.read_exact(&mut buffer[..len]) {
+        Ok(_) => len,
+        Err(_) => {
+            let msg = format!("Read error. Got less than {} bytes", len);
+            signal_error(iohandler.context_id, Level::Error, ErrorCode::Read, &msg);
+            0
+        }
+    }
+}
+
+fn stream_seek(iohandler: &mut IoHandler, offset: usize) -> bool {
+    if iohandler.reported_size != 0 && offset > iohandler.reported_size {
+        signal_error(
+            iohandler.context_id,
+            Level::Error,
+            ErrorCode::Seek,
+            "Too few data; probably corrupted profile",
+        );
+        return false;
+    }
+
+    match iohandler.stream.seek(SeekFrom::Start(offset as u64)) {
+        Ok(_) => true,
+        Err(_) => {
+            signal_error(
+                iohandler.context_id,
+                Level::Error,
+                ErrorCode::Seek,
+                "Seek error; probably corrupted file",
+            );
+            false
+        }
+    }
+}
+
+fn stream_tell(iohandler: &mut IoHandler) -> usize {
+    iohandler.stream.stream_position().unwrap_or(0) as usize
+}
+
+fn stream_write(iohandler: &mut IoHandler, size: usize, buffer: &[u8]) -> bool {
+    // Housekeeping
+    if size == 0 {
+        return true;
+    }
+    if size > buffer.len() {
+        return false;
+    }
+
+    if iohandler.stream.write_all(&buffer[..size]).is_err() {
+        return false;
+    }
+
+    let at = stream_tell(iohandler);
+    if iohandler.used_space < at {
+        iohandler.used_space = at;
+    }
+
+    true
+}
+
+fn stream_close(iohandler: &mut IoHandler) -> bool {
+    iohandler.stream.flush().is_ok()
+}
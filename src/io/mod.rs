@@ -1,8 +1,81 @@
-use std::io::{Read, Seek, Write};
+use std::{
+    any::Any,
+    io::{self, ErrorKind, Read, Seek, SeekFrom, Write},
+};
 
 mod io_handler;
 
 pub use io_handler::IoHandler;
 
-pub trait Stream: Read + Write + Seek {}
-impl<T> Stream for T where T: Read + Write + Seek {}
+pub trait Stream: Read + Write + Seek {
+    fn into_any(self: Box<Self>) -> Box<dyn Any>;
+}
+impl<T> Stream for T
+where
+    T: Read + Write + Seek + 'static,
+{
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+}
+
+/// Adapter for sources that can only be read from.
+pub(crate) struct ReadOnlyStream<T: Read + Seek>(pub T);
+
+impl<T: Read + Seek> Read for ReadOnlyStream<T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.read(buf)
+    }
+}
+
+impl<T: Read + Seek> Seek for ReadOnlyStream<T> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.0.seek(pos)
+    }
+}
+
+impl<T: Read + Seek> Write for ReadOnlyStream<T> {
+    fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+        Err(io::Error::new(ErrorKind::PermissionDenied, "Stream is read only"))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// A stream that stores nothing. Reads return zeros and writes only move the pointer.
+#[derive(Default)]
+pub(crate) struct NullStream {
+    pointer: u64,
+}
+
+impl Read for NullStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        buf.fill(0);
+        self.pointer += buf.len() as u64;
+        Ok(buf.len())
+    }
+}
+
+impl Seek for NullStream {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.pointer = match pos {
+            SeekFrom::Start(offset) => offset,
+            SeekFrom::Current(offset) => self.pointer.saturating_add_signed(offset),
+            SeekFrom::End(offset) => offset.max(0) as u64,
+        };
+        Ok(self.pointer)
+    }
+}
+
+impl Write for NullStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pointer += buf.len() as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -3,8 +3,12 @@
 #![cfg_attr(debug_assertions, allow(dead_code))]
 
 use log::Level;
-use state::{default_error_handler_log_function, ContextStruct, ErrorCode};
+use once_cell::sync::Lazy;
+use state::{
+    default_error_handler_log_function, ContextStruct, ErrorCode, Formatters, MutexFunctions,
+};
 use std::{any::Any, sync::Arc};
+use types::default_interp_factory;
 
 /// Maximum number of channels in ICC profiles
 pub const MAX_CHANNELS: usize = 16;
@@ -19,25 +23,40 @@ pub const NO_COUNTRY: [u8; 2] = [0, 0];
 
 pub const MAX_TYPES_IN_PLUGIN: usize = 20;
 
+const DEFAULT_ALARM_CODES: [u16; MAX_CHANNELS] = [
+    0x7F00, 0x7F00, 0x7F00, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+];
+const DEFAULT_OBSERVER_ADAPTATION_STATE: f64 = 1.0;
+
 const PI: f64 = 3.14159265358979323846;
 const LOG10E: f64 = 0.434294481903251827651;
 
-pub const DEFAULT_CONTEXT: Context = todo!() /* Arc::new(ContextStruct {
-    alarm_codes: todo!(),
-    adaptation_state: todo!(),
-    interpolator: todo!(),
-    curves: todo!(),
-    formatters: todo!(),
-    tag_types: todo!(),
-    mpe_types: todo!(),
-    tags: todo!(),
-    intents: todo!(),
-    optimizations: todo!(),
-    transforms: todo!(),
-    mutex: todo!(),
-    user_data: todo!(),
-    error_logger: default_error_handler_log_function,
-}) */;
+pub static DEFAULT_CONTEXT: Lazy<Context> = Lazy::new(|| {
+    Arc::new(ContextStruct {
+        alarm_codes: DEFAULT_ALARM_CODES,
+        adaptation_state: DEFAULT_OBSERVER_ADAPTATION_STATE,
+        interpolator: default_interp_factory,
+        curves: Vec::new(),
+        formatters: Formatters {
+            r#in: Vec::new(),
+            out: Vec::new(),
+        },
+        tag_types: Vec::new(),
+        mpe_types: Vec::new(),
+        tags: Vec::new(),
+        intents: Vec::new(),
+        optimizations: Vec::new(),
+        transforms: Vec::new(),
+        mutex: MutexFunctions {
+            create: None,
+            destroy: None,
+            lock: None,
+            unlock: None,
+        },
+        user_data: Box::new(()),
+        error_logger: default_error_handler_log_function,
+    })
+});
 
 #[allow(non_camel_case_types)]
 pub type s15f16 = i32;
@@ -66,6 +85,8 @@ pub type Context = Arc<state::ContextStruct>;
 
 mod consts;
 mod inlines;
+mod md5;
+mod white_point;
 pub(crate) use consts::*;
 pub(crate) use inlines::*;
 
@@ -81,10 +102,14 @@ pub mod types;
 pub use state::context::signal_error;
 
 pub mod device_attribute {
-    pub const REFLECTIVE: u32 = 0;
-    pub const TRANSPARENCY: u32 = 1;
-    pub const GLOSSY: u32 = 0;
-    pub const MATTE: u32 = 2;
+    pub const REFLECTIVE: u64 = 0;
+    pub const TRANSPARENCY: u64 = 1;
+    pub const GLOSSY: u64 = 0;
+    pub const MATTE: u64 = 2;
+    pub const POSITIVE: u64 = 0;
+    pub const NEGATIVE: u64 = 4;
+    pub const COLOR: u64 = 0;
+    pub const BLACK_AND_WHITE: u64 = 8;
 }
 
 pub mod illuminant_type {
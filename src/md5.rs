@@ -0,0 +1,153 @@
+// MD5 message digest as described in RFC 1321. Used to compute profile IDs.
+
+const S: [u32; 64] = [
+    7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22, 5, 9, 14, 20, 5, 9, 14, 20, 5, 9,
+    14, 20, 5, 9, 14, 20, 4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23, 6, 10, 15,
+    21, 6, 10, 15, 21, 6, 10, 15, 21, 6, 10, 15, 21,
+];
+
+const K: [u32; 64] = [
+    0xd76aa478, 0xe8c7b756, 0x242070db, 0xc1bdceee, 0xf57c0faf, 0x4787c62a, 0xa8304613, 0xfd469501,
+    0x698098d8, 0x8b44f7af, 0xffff5bb1, 0x895cd7be, 0x6b901122, 0xfd987193, 0xa679438e, 0x49b40821,
+    0xf61e2562, 0xc040b340, 0x265e5a51, 0xe9b6c7aa, 0xd62f105d, 0x02441453, 0xd8a1e681, 0xe7d3fbc8,
+    0x21e1cde6, 0xc33707d6, 0xf4d50d87, 0x455a14ed, 0xa9e3e905, 0xfcefa3f8, 0x676f02d9, 0x8d2a4c8a,
+    0xfffa3942, 0x8771f681, 0x6d9d6122, 0xfde5380c, 0xa4beea44, 0x4bdecfa9, 0xf6bb4b60, 0xbebfbc70,
+    0x289b7ec6, 0xeaa127fa, 0xd4ef3085, 0x04881d05, 0xd9d4d039, 0xe6db99e5, 0x1fa27cf8, 0xc4ac5665,
+    0xf4292244, 0x432aff97, 0xab9423a7, 0xfc93a039, 0x655b59c3, 0x8f0ccc92, 0xffeff47d, 0x85845dd1,
+    0x6fa87e4f, 0xfe2ce6e0, 0xa3014314, 0x4e0811a1, 0xf7537e82, 0xbd3af235, 0x2ad7d2bb, 0xeb86d391,
+];
+
+pub(crate) struct Md5 {
+    buf: [u32; 4],
+    bits: u64,
+    block: [u8; 64],
+    block_len: usize,
+}
+
+impl Md5 {
+    pub fn new() -> Self {
+        Md5 {
+            buf: [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476],
+            bits: 0,
+            block: [0; 64],
+            block_len: 0,
+        }
+    }
+
+    /// Adds bytes to the digest.
+    pub fn add(&mut self, data: &[u8]) {
+        self.bits = self.bits.wrapping_add((data.len() as u64) << 3);
+
+        let mut data = data;
+        while !data.is_empty() {
+            let n = (64 - self.block_len).min(data.len());
+            self.block[self.block_len..self.block_len + n].copy_from_slice(&data[..n]);
+            self.block_len += n;
+            data = &data[n..];
+
+            if self.block_len == 64 {
+                self.transform();
+                self.block_len = 0;
+            }
+        }
+    }
+
+    /// Pads the message and returns the 16 bytes of the digest.
+    pub fn finish(mut self) -> [u8; 16] {
+        let bits = self.bits;
+
+        // Padding is a single 1 bit followed by zeros, up to 56 bytes in the last block
+        let pad_len = if self.block_len < 56 {
+            56 - self.block_len
+        } else {
+            120 - self.block_len
+        };
+        let mut padding = [0u8; 64];
+        padding[0] = 0x80;
+        self.add(&padding[..pad_len]);
+
+        // Append length in bits
+        self.add(&bits.to_le_bytes());
+
+        let mut result = [0u8; 16];
+        for (chunk, word) in result.chunks_exact_mut(4).zip(self.buf) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+        result
+    }
+
+    fn transform(&mut self) {
+        let mut m = [0u32; 16];
+        for (word, chunk) in m.iter_mut().zip(self.block.chunks_exact(4)) {
+            *word = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        }
+
+        let [mut a, mut b, mut c, mut d] = self.buf;
+
+        for i in 0..64 {
+            let (f, g) = match i / 16 {
+                0 => ((b & c) | (!b & d), i),
+                1 => ((d & b) | (!d & c), (5 * i + 1) % 16),
+                2 => (b ^ c ^ d, (3 * i + 5) % 16),
+                _ => (c ^ (b | !d), (7 * i) % 16),
+            };
+
+            let f = f.wrapping_add(a).wrapping_add(K[i]).wrapping_add(m[g]);
+            a = d;
+            d = c;
+            c = b;
+            b = b.wrapping_add(f.rotate_left(S[i]));
+        }
+
+        self.buf[0] = self.buf[0].wrapping_add(a);
+        self.buf[1] = self.buf[1].wrapping_add(b);
+        self.buf[2] = self.buf[2].wrapping_add(c);
+        self.buf[3] = self.buf[3].wrapping_add(d);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Md5;
+
+    fn hex(digest: [u8; 16]) -> String {
+        digest.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    // Test suite of RFC 1321, appendix A.5
+    #[test]
+    fn rfc1321_vectors() {
+        let vectors = [
+            ("", "d41d8cd98f00b204e9800998ecf8427e"),
+            ("a", "0cc175b9c0f1b6a831c399e269772661"),
+            ("abc", "900150983cd24fb0d6963f7d28e17f72"),
+            ("message digest", "f96b697d7cb7938d525a2f31aaf161d0"),
+            ("abcdefghijklmnopqrstuvwxyz", "c3fcd3d76192e4007dfb496cca67e13b"),
+            (
+                "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789",
+                "d174ab98d277d9f5a5611c2c9f419d9f",
+            ),
+            (
+                "12345678901234567890123456789012345678901234567890123456789012345678901234567890",
+                "57edf4a22be3c955ac49da2e2107b67a",
+            ),
+        ];
+
+        for (message, digest) in vectors {
+            let mut md5 = Md5::new();
+            md5.add(message.as_bytes());
+            assert_eq!(hex(md5.finish()), digest, "{:?}", message);
+        }
+    }
+
+    #[test]
+    fn split_input() {
+        let message = b"12345678901234567890123456789012345678901234567890123456789012345678901234567890";
+
+        let mut md5 = Md5::new();
+        for chunk in message.chunks(7) {
+            md5.add(chunk);
+        }
+        assert_eq!(hex(md5.finish()), "57edf4a22be3c955ac49da2e2107b67a");
+    }
+}
@@ -223,15 +223,10 @@ pub fn write_xyz(io: &mut IoHandler, xyz: XYZ) -> Result<()> {
 }
 
 pub fn read_type_base(io: &mut IoHandler) -> Result<Signature> {
-    let mut tmp = [0u8; size_of::<u32>()];
-    if (io.read)(io, &mut tmp, size_of::<u32>(), 1) != size_of::<u32>() {
-        return Err("Write error in read_type_base".into());
-    }
-    let result = Signature(u32::from_ne_bytes(tmp));
+    let (Ok(result), Ok(_reserved)) = (read_signature(io), read_u32(io)) else {
+        return Err("Read error in read_type_base".into());
+    };
 
-    if (io.read)(io, &mut tmp, size_of::<u32>(), 1) != size_of::<u32>() {
-        return Err("Write error in read_type_base".into());
-    }
     Ok(result)
 }
 
@@ -288,7 +283,7 @@ pub fn u8f8_to_f64(fixed8: u8f8) -> f64 {
     let lsb = fixed8 & 0xFF;
     let msb = fixed8 >> 8;
 
-    msb as f64 + (lsb as f64 / 255.0)
+    msb as f64 + (lsb as f64 / 256.0)
 }
 
 pub fn f64_to_u8f8(val: f64) -> u8f8 {
@@ -323,16 +318,17 @@ pub fn f64_to_u16_fixed16(v: f64) -> u16f16 {
 
 pub fn encode_date_time(source: dt<Utc>) -> DateTime {
     DateTime {
-        seconds: adjust_endianess16(source.second() as u16),
-        minutes: adjust_endianess16(source.minute() as u16),
-        hours: adjust_endianess16(source.hour() as u16),
-        day: adjust_endianess16(source.day() as u16),
-        month: adjust_endianess16(source.month() as u16),
-        year: adjust_endianess16(source.year() as u16),
+        seconds: source.second() as u16,
+        minutes: source.minute() as u16,
+        hours: source.hour() as u16,
+        day: source.day() as u16,
+        month: source.month() as u16,
+        year: source.year() as u16,
     }
 }
 
-pub fn decode_date_time(source: DateTime) -> dt<Utc> {
+/// Returns None if the fields do not form a valid date, as happens with all zero dates.
+pub fn decode_date_time(source: DateTime) -> Option<dt<Utc>> {
     let utc = Utc;
     utc.with_ymd_and_hms(
         source.year as i32,
@@ -342,5 +338,5 @@ pub fn decode_date_time(source: DateTime) -> dt<Utc> {
         source.minutes as u32,
         source.seconds as u32,
     )
-    .unwrap()
+    .single()
 }
@@ -36,7 +36,7 @@ pub use optimization::{Optimization, OptimizationFn};
 pub use parallel::Parallelization;
 pub use parametric_curve::{ParametricCurve, ParametricCurveEvaluator};
 pub use rendering_intent::{IntentFn, RenderingIntent};
-pub use tag::{Tag, TagDescriptor};
-pub use tag_type::{TagType, TagTypeHandler};
+pub use tag::{get_tag_descriptor, DecideTypeFn, Tag, TagDescriptor, SUPPORTED_TAGS};
+pub use tag_type::{get_tag_type_handler, TagType, TagTypeHandler, SUPPORTED_TAG_TYPES};
 pub use transform::{Transform, TransformFactories};
 pub(crate) use functions::*;
@@ -1,14 +1,25 @@
 use std::any::Any;
 
-use crate::{types::Signature, MAX_TYPES_IN_PLUGIN};
+use once_cell::sync::Lazy;
 
-use super::Base;
+use crate::{sig, state, types::Signature, Context, MAX_TYPES_IN_PLUGIN};
+
+use super::{
+    tag_type::{
+        curve::decide_curve_type,
+        lut16::{decide_lut_type_a2b, decide_lut_type_b2a},
+        text::decide_text_type, text_description::decide_text_desc_type, xyz::decide_xyz_type,
+    },
+    Base,
+};
+
+pub type DecideTypeFn = fn(icc_version: f64, data: &dyn Any) -> Signature;
 
 pub struct TagDescriptor {
     pub elem_count: u32,
     pub n_supported_types: u32,
     pub supported_types: [Signature; MAX_TYPES_IN_PLUGIN as usize],
-    pub decide_type: Option<fn(icc_version: f64, data: &Box<dyn Any>) -> Signature>,
+    pub decide_type: Option<DecideTypeFn>,
 }
 
 pub struct Tag {
@@ -16,3 +27,162 @@ pub struct Tag {
     pub signature: Signature,
     pub descriptor: TagDescriptor,
 }
+
+impl TagDescriptor {
+    pub fn new(elem_count: u32, types: &[Signature], decide_type: Option<DecideTypeFn>) -> Self {
+        let mut supported_types = [Signature(0); MAX_TYPES_IN_PLUGIN];
+        supported_types[..types.len()].copy_from_slice(types);
+
+        TagDescriptor {
+            elem_count,
+            n_supported_types: types.len() as u32,
+            supported_types,
+            decide_type,
+        }
+    }
+
+    pub fn supported_types(&self) -> &[Signature] {
+        &self.supported_types[..self.n_supported_types as usize]
+    }
+
+    /// Checks whether a type can be used to store this tag.
+    pub fn is_type_supported(&self, r#type: Signature) -> bool {
+        self.supported_types().contains(&r#type)
+    }
+
+    /// Picks the type to use when writing the data, depending on the profile version.
+    pub fn decide_type(&self, icc_version: f64, data: &dyn Any) -> Signature {
+        match self.decide_type {
+            Some(decide) => decide(icc_version, data),
+            None => self.supported_types[0],
+        }
+    }
+}
+
+macro_rules! TagDescriptor {
+    ($sig:path, $count:literal, [$($t:path),+ $(,)?], $decide:expr) => {
+        state::Tag {
+            signature: $sig,
+            descriptor: TagDescriptor::new($count, &[$($t),+], $decide),
+        }
+    };
+}
+
+pub static SUPPORTED_TAGS: Lazy<Vec<state::Tag>> = Lazy::new(|| {
+    use sig::{tags, types};
+
+    let text_desc: Option<DecideTypeFn> = Some(decide_text_desc_type);
+    let xyz: Option<DecideTypeFn> = Some(decide_xyz_type);
+    let curve: Option<DecideTypeFn> = Some(decide_curve_type);
+    let a2b: Option<DecideTypeFn> = Some(decide_lut_type_a2b);
+    let b2a: Option<DecideTypeFn> = Some(decide_lut_type_b2a);
+
+    vec![
+        TagDescriptor!(tags::A_TO_B0, 1, [types::LUT16, types::LUT_A_TO_B, types::LUT8], a2b),
+        TagDescriptor!(tags::A_TO_B1, 1, [types::LUT16, types::LUT_A_TO_B, types::LUT8], a2b),
+        TagDescriptor!(tags::A_TO_B2, 1, [types::LUT16, types::LUT_A_TO_B, types::LUT8], a2b),
+        TagDescriptor!(tags::B_TO_A0, 1, [types::LUT16, types::LUT_B_TO_A, types::LUT8], b2a),
+        TagDescriptor!(tags::B_TO_A1, 1, [types::LUT16, types::LUT_B_TO_A, types::LUT8], b2a),
+        TagDescriptor!(tags::B_TO_A2, 1, [types::LUT16, types::LUT_B_TO_A, types::LUT8], b2a),
+        TagDescriptor!(tags::RED_COLORANT, 1, [types::XYZ], xyz),
+        TagDescriptor!(tags::GREEN_COLORANT, 1, [types::XYZ], xyz),
+        TagDescriptor!(tags::BLUE_COLORANT, 1, [types::XYZ], xyz),
+        TagDescriptor!(tags::RED_TRC, 1, [types::CURVE, types::PARAMETRIC_CURVE], curve),
+        TagDescriptor!(tags::GREEN_TRC, 1, [types::CURVE, types::PARAMETRIC_CURVE], curve),
+        TagDescriptor!(tags::BLUE_TRC, 1, [types::CURVE, types::PARAMETRIC_CURVE], curve),
+        TagDescriptor!(tags::CALIBRATION_DATE_TIME, 1, [types::DATE_TIME], None),
+        TagDescriptor!(tags::CHAR_TARGET, 1, [types::TEXT], None),
+        TagDescriptor!(tags::CHROMATIC_ADAPTATION, 9, [types::S15_FIXED16_ARRAY], None),
+        TagDescriptor!(tags::CHROMATICITY, 1, [types::CHROMATICITY], None),
+        TagDescriptor!(tags::COLORANT_ORDER, 1, [types::COLORANT_ORDER], None),
+        TagDescriptor!(tags::COLORANT_TABLE, 1, [types::COLORANT_TABLE], None),
+        TagDescriptor!(tags::COLORANT_TABLE_OUT, 1, [types::COLORANT_TABLE], None),
+        TagDescriptor!(
+            tags::COPYRIGHT,
+            1,
+            [types::TEXT, types::MULTI_LOCALIZED_UNICODE, types::TEXT_DESCRIPTION],
+            Some(decide_text_type)
+        ),
+        TagDescriptor!(tags::DATE_TIME, 1, [types::DATE_TIME], None),
+        TagDescriptor!(
+            tags::DEVICE_MFG_DESC,
+            1,
+            [types::TEXT_DESCRIPTION, types::MULTI_LOCALIZED_UNICODE, types::TEXT],
+            text_desc
+        ),
+        TagDescriptor!(
+            tags::DEVICE_MODEL_DESC,
+            1,
+            [types::TEXT_DESCRIPTION, types::MULTI_LOCALIZED_UNICODE, types::TEXT],
+            text_desc
+        ),
+        TagDescriptor!(tags::GAMUT, 1, [types::LUT16, types::LUT_B_TO_A, types::LUT8], b2a),
+        TagDescriptor!(tags::GRAY_TRC, 1, [types::CURVE, types::PARAMETRIC_CURVE], curve),
+        TagDescriptor!(tags::LUMINANCE, 1, [types::XYZ], None),
+        TagDescriptor!(tags::MEDIA_BLACK_POINT, 1, [types::XYZ], None),
+        TagDescriptor!(tags::MEDIA_WHITE_POINT, 1, [types::XYZ], None),
+        TagDescriptor!(tags::NAMED_COLOR2, 1, [types::NAMED_COLOR2], None),
+        TagDescriptor!(tags::PREVIEW0, 1, [types::LUT16, types::LUT_B_TO_A, types::LUT8], b2a),
+        TagDescriptor!(tags::PREVIEW1, 1, [types::LUT16, types::LUT_B_TO_A, types::LUT8], b2a),
+        TagDescriptor!(tags::PREVIEW2, 1, [types::LUT16, types::LUT_B_TO_A, types::LUT8], b2a),
+        TagDescriptor!(
+            tags::PROFILE_DESCRIPTION,
+            1,
+            [types::TEXT_DESCRIPTION, types::MULTI_LOCALIZED_UNICODE, types::TEXT],
+            text_desc
+        ),
+        TagDescriptor!(tags::PROFILE_SEQUENCE_DESC, 1, [types::PROFILE_SEQUENCE_DESC], None),
+        TagDescriptor!(tags::TECHNOLOGY, 1, [types::SIGNATURE], None),
+        TagDescriptor!(tags::COLORIMETRIC_INTENT_IMAGE_STATE, 1, [types::SIGNATURE], None),
+        TagDescriptor!(tags::PERCEPTUAL_RENDERING_INTENT_GAMUT, 1, [types::SIGNATURE], None),
+        TagDescriptor!(tags::SATURATION_RENDERING_INTENT_GAMUT, 1, [types::SIGNATURE], None),
+        TagDescriptor!(tags::MEASUREMENT, 1, [types::MEASUREMENT], None),
+        TagDescriptor!(tags::PS2_CRD0, 1, [types::DATA], None),
+        TagDescriptor!(tags::PS2_CRD1, 1, [types::DATA], None),
+        TagDescriptor!(tags::PS2_CRD2, 1, [types::DATA], None),
+        TagDescriptor!(tags::PS2_CRD3, 1, [types::DATA], None),
+        TagDescriptor!(tags::PS2_CSA, 1, [types::DATA], None),
+        TagDescriptor!(tags::PS2_RENDERING_INTENT, 1, [types::DATA], None),
+        TagDescriptor!(
+            tags::VIEWING_COND_DESC,
+            1,
+            [types::TEXT_DESCRIPTION, types::MULTI_LOCALIZED_UNICODE, types::TEXT],
+            text_desc
+        ),
+        TagDescriptor!(tags::UCR_BG, 1, [types::UCR_BG], None),
+        TagDescriptor!(tags::CRD_INFO, 1, [types::CRD_INFO], None),
+        TagDescriptor!(tags::D_TO_B0, 1, [types::MULTI_PROCESS_ELEMENT], None),
+        TagDescriptor!(tags::D_TO_B1, 1, [types::MULTI_PROCESS_ELEMENT], None),
+        TagDescriptor!(tags::D_TO_B2, 1, [types::MULTI_PROCESS_ELEMENT], None),
+        TagDescriptor!(tags::D_TO_B3, 1, [types::MULTI_PROCESS_ELEMENT], None),
+        TagDescriptor!(tags::B_TO_D0, 1, [types::MULTI_PROCESS_ELEMENT], None),
+        TagDescriptor!(tags::B_TO_D1, 1, [types::MULTI_PROCESS_ELEMENT], None),
+        TagDescriptor!(tags::B_TO_D2, 1, [types::MULTI_PROCESS_ELEMENT], None),
+        TagDescriptor!(tags::B_TO_D3, 1, [types::MULTI_PROCESS_ELEMENT], None),
+        TagDescriptor!(tags::SCREENING_DESC, 1, [types::TEXT_DESCRIPTION], None),
+        TagDescriptor!(tags::VIEWING_CONDITIONS, 1, [types::VIEWING_CONDITIONS], None),
+        TagDescriptor!(tags::SCREENING, 1, [types::SCREENING], None),
+        TagDescriptor!(tags::VCGT, 1, [types::VCGT], None),
+        TagDescriptor!(tags::META, 1, [types::DICT], None),
+        TagDescriptor!(tags::PROFILE_SEQUENCE_ID, 1, [types::PROFILE_SEQUENCE_ID], None),
+        TagDescriptor!(tags::PROFILE_DESCRIPTION_ML, 1, [types::MULTI_LOCALIZED_UNICODE], None),
+        TagDescriptor!(tags::CICP, 1, [types::CICP], None),
+        TagDescriptor!(tags::OUTPUT_RESPONSE, 1, [types::RESPONSE_CURVE_SET16], None),
+        TagDescriptor!(tags::ARGYLL_ARTS, 9, [types::S15_FIXED16_ARRAY], None),
+    ]
+});
+
+/// Returns the descriptor of a tag. Plug-in tags take precedence over the built-in ones.
+pub fn get_tag_descriptor(context_id: &'static Context, sig: Signature) -> Option<&'static TagDescriptor> {
+    context_id
+        .tags
+        .iter()
+        .find(|tag| tag.signature == sig)
+        .map(|tag| &tag.descriptor)
+        .or_else(|| {
+            SUPPORTED_TAGS
+                .iter()
+                .find(|tag| tag.signature == sig)
+                .map(|tag| &tag.descriptor)
+        })
+}
@@ -19,6 +19,8 @@ pub fn type_chromaticity_read(_handler: &TagTypeHandler, io: &mut IoHandler, n_i
         return Err("Invalid number of channels in type_chromaticity_read".into());
     }
 
+    read_u16(io)?; // Colorant type
+
     let chrm = XYYTriple {
         red: XYY { x: read_s15f16(io)?, y: read_s15f16(io)?, y_lum: 1.0 },
         green: XYY { x: read_s15f16(io)?, y: read_s15f16(io)?, y_lum: 1.0 },
@@ -53,3 +55,33 @@ pub fn type_chromaticity_write(_handler: &TagTypeHandler, io: &mut IoHandler, pt
 }
 
 type_dup_and_free!(chromaticity, XYYTriple);
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        sig,
+        types::{Profile, XYYTriple, XYY},
+    };
+
+    #[test]
+    fn chromaticity_round_trip() {
+        let xyy = |x, y| XYY { x, y, y_lum: 1.0 };
+        let chrm = XYYTriple {
+            red: xyy(0.64, 0.33),
+            green: xyy(0.30, 0.60),
+            blue: xyy(0.15, 0.06),
+        };
+
+        let mut profile = Profile::new();
+        profile.write_tag(sig::tags::CHROMATICITY, &chrm).unwrap();
+        let mem = profile.save_to_mem().unwrap();
+
+        let mut profile = Profile::open_mem(&mem).unwrap();
+        let read = profile.read_tag::<XYYTriple>(sig::tags::CHROMATICITY).unwrap();
+
+        for (read, expected) in [(read.red, chrm.red), (read.green, chrm.green), (read.blue, chrm.blue)] {
+            assert!((read.x - expected.x).abs() < 1e-4);
+            assert!((read.y - expected.y).abs() < 1e-4);
+        }
+    }
+}
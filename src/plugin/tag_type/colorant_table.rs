@@ -0,0 +1,117 @@
+use std::any::Any;
+
+use log::Level;
+
+use crate::{
+    io::IoHandler,
+    plugin::{read_u16_slice, read_u32, write_u16_slice, write_u32},
+    signal_error,
+    state::ErrorCode,
+    types::NamedColor,
+    Result, MAX_CHANNELS,
+};
+
+use super::{read_name, write_name, TagTypeHandler};
+
+// The colorant table holds the name and PCS value of each device colorant
+pub fn type_colorant_table_read(
+    handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    n_items: &mut usize,
+    _size_of_tag: usize,
+) -> Result<Box<dyn Any>> {
+    *n_items = 0;
+
+    // Get the number of colorants
+    let count = read_u32(io)? as usize;
+
+    if count > MAX_CHANNELS {
+        let msg = format!("Too many colorants '{}'", count);
+        signal_error(&handler.context_id, Level::Error, ErrorCode::Range, &msg);
+        return Err(msg);
+    }
+
+    let mut list = NamedColor::new(io.context_id, 0, "", "")?;
+
+    for _ in 0..count {
+        let name = read_name(io)?;
+
+        let mut pcs = [0u16; 3];
+        read_u16_slice(io, &mut pcs)?;
+
+        list.append(&name, pcs, None)?;
+    }
+
+    *n_items = 1;
+    Ok(Box::new(list))
+}
+
+pub fn type_colorant_table_write(
+    _handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    ptr: &dyn Any,
+    _n_items: usize,
+) -> Result<()> {
+    let Some(named_color_list) = ptr.downcast_ref::<NamedColor>() else {
+        return Err("Invalid object to write with type_colorant_table_write".into());
+    };
+
+    write_u32(io, named_color_list.count() as u32)?;
+
+    for entry in named_color_list.iter() {
+        write_name(io, &entry.name)?;
+        write_u16_slice(io, &entry.pcs)?;
+    }
+
+    Ok(())
+}
+
+type_dup_and_free!(colorant_table, NamedColor);
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        sig,
+        types::{NamedColor, Profile},
+        DEFAULT_CONTEXT,
+    };
+
+    #[test]
+    fn colorant_table_round_trip() {
+        let mut list = NamedColor::new(&DEFAULT_CONTEXT, 0, "", "").unwrap();
+        list.append("Cyan", [0x8e00, 0x5500, 0x3a00], None).unwrap();
+        list.append("Magenta", [0x7800, 0xc000, 0x7c00], None).unwrap();
+        list.append("Yellow", [0xe000, 0x7e00, 0xd300], None).unwrap();
+        list.append("Black", [0x1000, 0x8000, 0x8000], None).unwrap();
+
+        let mut profile = Profile::new();
+        profile.write_tag(sig::tags::COLORANT_TABLE, &list).unwrap();
+        let mem = profile.save_to_mem().unwrap();
+
+        let mut profile = Profile::open_mem(&mem).unwrap();
+        assert_eq!(&profile.read_raw_tag(sig::tags::COLORANT_TABLE).unwrap()[..4], b"clrt");
+
+        let read = profile.read_tag::<NamedColor>(sig::tags::COLORANT_TABLE).unwrap();
+        assert_eq!(read.count(), 4);
+        assert_eq!(read.get(1).unwrap().pcs, [0x7800, 0xc000, 0x7c00]);
+
+        assert_eq!(profile.colorant_names().unwrap(), ["Cyan", "Magenta", "Yellow", "Black"]);
+    }
+
+    #[test]
+    fn too_many_colorants_are_rejected() {
+        let mut list = NamedColor::new(&DEFAULT_CONTEXT, 0, "", "").unwrap();
+        list.append("Cyan", [0x8e00, 0x5500, 0x3a00], None).unwrap();
+
+        let mut profile = Profile::new();
+        profile.write_tag(sig::tags::COLORANT_TABLE, &list).unwrap();
+        let mut mem = profile.save_to_mem().unwrap();
+
+        // The count follows the type signature and the reserved field
+        let pos = mem.windows(4).rposition(|w| w == b"clrt").unwrap();
+        mem[pos + 8..pos + 12].copy_from_slice(&1000u32.to_be_bytes());
+
+        let mut profile = Profile::open_mem(&mem).unwrap();
+        assert!(profile.colorant_names().is_err());
+    }
+}
@@ -0,0 +1,135 @@
+use std::{any::Any, mem::size_of};
+
+use crate::{
+    io::IoHandler,
+    plugin::{read_u32, write_u32},
+    types::MLU,
+    Result,
+};
+
+use super::TagTypeHandler;
+
+//  This type contains the PostScript product name to which this profile corresponds
+//  and the names of the companion CRDs. Recall that a single profile can generate
+//  multiple CRDs. It is implemented as a MLU being the language code "PS" and then
+//  country varies for each element:
+//
+//                  nm: PostScript product name
+//                  #0: Rendering intent 0 CRD name
+//                  #1: Rendering intent 1 CRD name
+//                  #2: Rendering intent 2 CRD name
+//                  #3: Rendering intent 3 CRD name
+
+const SECTIONS: [[u8; 2]; 5] = [*b"nm", *b"#0", *b"#1", *b"#2", *b"#3"];
+
+// Auxiliary, read an string specified as count + string
+fn read_count_and_string(io: &mut IoHandler, mlu: &mut MLU, size_of_tag: &mut usize, section: [u8; 2]) -> Result<()> {
+    if *size_of_tag < size_of::<u32>() {
+        return Err("Tag is too small in type_crd_info_read".into());
+    }
+
+    let count = read_u32(io)? as usize;
+
+    if *size_of_tag - size_of::<u32>() < count {
+        return Err("Tag is too small in type_crd_info_read".into());
+    }
+
+    let mut text = vec![0u8; count];
+    if (io.read)(io, &mut text, size_of::<u8>(), count) != count {
+        return Err("Read error in type_crd_info_read".into());
+    }
+
+    mlu.set_ascii(*b"PS", section, &text)?;
+    *size_of_tag -= count + size_of::<u32>();
+
+    Ok(())
+}
+
+fn write_count_and_string(io: &mut IoHandler, mlu: &MLU, section: [u8; 2]) -> Result<()> {
+    let text_size = mlu.get_ascii(*b"PS", section, &mut []).unwrap_or(0);
+    let mut text = vec![0u8; text_size];
+    mlu.get_ascii(*b"PS", section, &mut text);
+
+    // Up to and including the terminator
+    let len = text.iter().position(|&c| c == 0).map_or(text.len(), |pos| pos + 1);
+
+    write_u32(io, len as u32)?;
+    if !(io.write)(io, len, &text[..len]) {
+        return Err("Write error in type_crd_info_write".into());
+    }
+
+    Ok(())
+}
+
+pub fn type_crd_info_read(
+    handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    n_items: &mut usize,
+    size_of_tag: usize,
+) -> Result<Box<dyn Any>> {
+    *n_items = 0;
+
+    let mut size_of_tag = size_of_tag;
+    let mut mlu = MLU::new(&handler.context_id, 5);
+
+    for section in SECTIONS {
+        read_count_and_string(io, &mut mlu, &mut size_of_tag, section)?;
+    }
+
+    *n_items = 1;
+    Ok(Box::new(mlu))
+}
+
+pub fn type_crd_info_write(
+    _handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    ptr: &dyn Any,
+    _n_items: usize,
+) -> Result<()> {
+    let Some(mlu) = ptr.downcast_ref::<MLU>() else {
+        return Err("Invalid object to write with type_crd_info_write".into());
+    };
+
+    for section in SECTIONS {
+        write_count_and_string(io, mlu, section)?;
+    }
+
+    Ok(())
+}
+
+type_dup_and_free!(crd_info, MLU);
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        sig,
+        types::{Profile, MLU},
+        DEFAULT_CONTEXT,
+    };
+
+    use super::SECTIONS;
+
+    #[test]
+    fn crd_info_round_trip() {
+        let names = ["Printer", "Perceptual", "Colorimetric", "Saturation", "Absolute"];
+
+        let mut mlu = MLU::new(&DEFAULT_CONTEXT, 5);
+        for (section, name) in SECTIONS.iter().zip(names) {
+            mlu.set_ascii(*b"PS", *section, name.as_bytes()).unwrap();
+        }
+
+        let mut profile = Profile::new();
+        profile.write_tag(sig::tags::CRD_INFO, &mlu).unwrap();
+        let mem = profile.save_to_mem().unwrap();
+
+        let mut profile = Profile::open_mem(&mem).unwrap();
+        assert_eq!(&profile.read_raw_tag(sig::tags::CRD_INFO).unwrap()[..4], b"crdi");
+
+        let read = profile.read_tag::<MLU>(sig::tags::CRD_INFO).unwrap();
+        for (section, name) in SECTIONS.iter().zip(names) {
+            let mut text = [0u8; 16];
+            let len = read.get_ascii(*b"PS", *section, &mut text).unwrap();
+            assert_eq!(&text[..len - 1], name.as_bytes());
+        }
+    }
+}
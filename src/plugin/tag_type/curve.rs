@@ -0,0 +1,130 @@
+use std::any::Any;
+
+use crate::{
+    io::IoHandler,
+    plugin::{f64_to_u8f8, read_u16, read_u16_slice, read_u32, u8f8_to_f64, write_u16, write_u16_slice, write_u32},
+    sig,
+    types::{Signature, ToneCurve},
+    Result,
+};
+
+use super::TagTypeHandler;
+
+pub fn type_curve_read(
+    handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    n_items: &mut usize,
+    _size_of_tag: usize,
+) -> Result<Box<dyn Any>> {
+    *n_items = 0;
+
+    let count = read_u32(io)?;
+
+    let curve = match count {
+        // Linear.
+        0 => ToneCurve::build_parametric(&handler.context_id, 1, &[1.0])?,
+
+        // Specified as the exponent of gamma function
+        1 => {
+            let single_gamma = u8f8_to_f64(read_u16(io)?);
+
+            ToneCurve::build_parametric(&handler.context_id, 1, &[single_gamma])?
+        }
+
+        // Curve
+        _ => {
+            // This is to prevent bad guys for doing bad things
+            if count > 0x7FFF {
+                return Err("Too many entries in type_curve_read".into());
+            }
+
+            let mut table = vec![0u16; count as usize];
+            read_u16_slice(io, &mut table)?;
+
+            ToneCurve::build_tabulated_16(&handler.context_id, &table)?
+        }
+    };
+
+    *n_items = 1;
+    Ok(Box::new(curve))
+}
+
+pub fn type_curve_write(
+    _handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    ptr: &dyn Any,
+    _n_items: usize,
+) -> Result<()> {
+    let Some(curve) = ptr.downcast_ref::<ToneCurve>() else {
+        return Err("Invalid object to write with type_curve_write".into());
+    };
+
+    if curve.parametric_type() == 1 {
+        // Single gamma, preserve number
+        let single_gamma_fixed = f64_to_u8f8(curve.segments[0].params[0]);
+
+        write_u32(io, 1)?;
+        return write_u16(io, single_gamma_fixed);
+    }
+
+    write_u32(io, curve.table_16.len() as u32)?;
+    write_u16_slice(io, &curve.table_16)
+}
+
+type_dup_and_free!(curve, ToneCurve);
+
+/// Decide which curve type to use on writing
+pub fn decide_curve_type(icc_version: f64, data: &dyn Any) -> Signature {
+    let Some(curve) = data.downcast_ref::<ToneCurve>() else {
+        return sig::types::CURVE;
+    };
+
+    // Only 1-segment, non-inverted ICC parametric curves can be saved as parametric
+    match curve.parametric_type() {
+        1..=5 if icc_version >= 4.0 => sig::types::PARAMETRIC_CURVE,
+        _ => sig::types::CURVE,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        sig,
+        types::{Profile, ToneCurve},
+        DEFAULT_CONTEXT,
+    };
+
+    fn round_trip(version: f64, curve: &ToneCurve) -> (Vec<u8>, ToneCurve) {
+        let mut profile = Profile::new();
+        profile.set_version(version).unwrap();
+        profile.write_tag(sig::tags::GRAY_TRC, curve).unwrap();
+        let mem = profile.save_to_mem().unwrap();
+
+        let mut profile = Profile::open_mem(&mem).unwrap();
+        let raw = profile.read_raw_tag(sig::tags::GRAY_TRC).unwrap();
+        (raw, profile.read_tag::<ToneCurve>(sig::tags::GRAY_TRC).unwrap().clone())
+    }
+
+    #[test]
+    fn tabulated_curve_round_trip() {
+        let table: Vec<u16> = (0..256u32).map(|i| (i * i * 65535 / (255 * 255)) as u16).collect();
+        let curve = ToneCurve::build_tabulated_16(&DEFAULT_CONTEXT, &table).unwrap();
+
+        let (raw, read) = round_trip(4.3, &curve);
+        assert_eq!(&raw[..4], b"curv");
+        assert_eq!(u32::from_be_bytes(raw[8..12].try_into().unwrap()), 256);
+        assert_eq!(read.table_16(), &table[..]);
+    }
+
+    #[test]
+    fn single_gamma_round_trip() {
+        let curve = ToneCurve::build_parametric(&DEFAULT_CONTEXT, 1, &[2.2]).unwrap();
+
+        // Parametric curves are only available in v4
+        let (raw, read) = round_trip(2.1, &curve);
+        assert_eq!(&raw[..4], b"curv");
+        assert_eq!(raw.len(), 14);
+        assert_eq!(read.parametric_type(), 1);
+        assert!((read.params().unwrap()[0] - 2.2).abs() < 1.0 / 256.0);
+    }
+}
@@ -51,7 +51,7 @@ pub fn type_data_write(
     match ptr.downcast_ref::<Data>() {
         None => Err("Invalid object to write with type_data_write".into()),
         Some(data) => {
-            write_u32(io, data.len as u32)?;
+            write_u32(io, data.flag)?;
 
             match (io.write)(io, data.len, &data.data) {
                 false => Err("Write error in type_data_write".into()),
@@ -0,0 +1,112 @@
+use std::any::Any;
+
+use chrono::{DateTime as dt, Utc};
+use log::Level;
+
+use crate::{
+    io::IoHandler,
+    plugin::{decode_date_time, encode_date_time, read_u16, write_u16},
+    signal_error,
+    state::ErrorCode,
+    types::DateTime,
+    Result,
+};
+
+use super::TagTypeHandler;
+
+pub fn type_date_time_read(
+    handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    n_items: &mut usize,
+    _size_of_tag: usize,
+) -> Result<Box<dyn Any>> {
+    *n_items = 0;
+
+    let timestamp = DateTime {
+        year: read_u16(io)?,
+        month: read_u16(io)?,
+        day: read_u16(io)?,
+        hours: read_u16(io)?,
+        minutes: read_u16(io)?,
+        seconds: read_u16(io)?,
+    };
+
+    // Unset dates are all zeros. They are kept as is when saving, but there is no date to return
+    let Some(date_time) = decode_date_time(timestamp) else {
+        let msg = "Invalid date in type_date_time_read";
+        signal_error(&handler.context_id, Level::Error, ErrorCode::CorruptionDetected, msg);
+        return Err(msg.into());
+    };
+
+    *n_items = 1;
+    Ok(Box::new(date_time))
+}
+
+pub fn type_date_time_write(
+    _handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    ptr: &dyn Any,
+    _n_items: usize,
+) -> Result<()> {
+    let Some(date_time) = ptr.downcast_ref::<dt<Utc>>() else {
+        return Err("Invalid object to write with type_date_time_write".into());
+    };
+
+    let timestamp = encode_date_time(*date_time);
+
+    for value in [
+        timestamp.year,
+        timestamp.month,
+        timestamp.day,
+        timestamp.hours,
+        timestamp.minutes,
+        timestamp.seconds,
+    ] {
+        write_u16(io, value)?;
+    }
+
+    Ok(())
+}
+
+type_dup_and_free!(date_time, dt<Utc>);
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime as dt, TimeZone, Utc};
+
+    use crate::{sig, types::Profile};
+
+    fn date_time_profile() -> Vec<u8> {
+        let date = Utc.with_ymd_and_hms(2023, 11, 5, 14, 30, 15).unwrap();
+
+        let mut profile = Profile::new();
+        profile.write_tag(sig::tags::CALIBRATION_DATE_TIME, &date).unwrap();
+        profile.save_to_mem().unwrap()
+    }
+
+    #[test]
+    fn date_time_round_trip() {
+        let mem = date_time_profile();
+        let mut profile = Profile::open_mem(&mem).unwrap();
+
+        let date = profile.read_tag::<dt<Utc>>(sig::tags::CALIBRATION_DATE_TIME).unwrap();
+        assert_eq!(*date, Utc.with_ymd_and_hms(2023, 11, 5, 14, 30, 15).unwrap());
+    }
+
+    #[test]
+    fn zero_date_is_kept() {
+        let mut mem = date_time_profile();
+
+        // The date fields follow the type signature and the reserved field
+        let pos = mem.windows(4).rposition(|w| w == b"dtim").unwrap();
+        mem[pos + 8..pos + 20].fill(0);
+
+        let mut profile = Profile::open_mem(&mem).unwrap();
+        assert!(profile.read_tag::<dt<Utc>>(sig::tags::CALIBRATION_DATE_TIME).is_err());
+
+        let mut profile = Profile::open_mem(&mem).unwrap();
+        let saved = profile.save_to_mem().unwrap();
+        let raw = Profile::open_mem(&saved).unwrap().read_raw_tag(sig::tags::CALIBRATION_DATE_TIME).unwrap();
+        assert_eq!(raw[8..20], [0; 12]);
+    }
+}
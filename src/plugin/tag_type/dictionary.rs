@@ -0,0 +1,352 @@
+use std::{any::Any, mem::size_of};
+
+use log::Level;
+
+use crate::{
+    io::IoHandler,
+    plugin::{read_u16_slice, read_u32, write_u16_slice, write_u32},
+    signal_error,
+    state::ErrorCode,
+    types::{tag::Base as TagBase, Dictionary, DictionaryEntry, MLU},
+    Result,
+};
+
+use super::{type_mlu_read, type_mlu_write, TagTypeHandler};
+
+// Each record holds the offset and size of name and value, plus those of display name (24 bytes)
+// and display value (32 bytes). Offsets are counted from the beginning of the tag, 0 means absent.
+#[derive(Clone, Copy, Default)]
+struct DicElem {
+    offset: usize,
+    size: usize,
+}
+
+#[derive(Clone, Copy, Default)]
+struct DicRecord {
+    name: DicElem,
+    value: DicElem,
+    display_name: DicElem,
+    display_value: DicElem,
+}
+
+fn read_elem(io: &mut IoHandler, base_offset: usize) -> Result<DicElem> {
+    let offset = read_u32(io)? as usize;
+    let size = read_u32(io)? as usize;
+
+    // An offset of zero has special meaning and shall be preserved
+    Ok(DicElem {
+        offset: if offset > 0 { offset + base_offset } else { 0 },
+        size,
+    })
+}
+
+fn write_elem(io: &mut IoHandler, elem: DicElem) -> Result<()> {
+    write_u32(io, elem.offset as u32)?;
+    write_u32(io, elem.size as u32)
+}
+
+fn write_offset_array(io: &mut IoHandler, records: &[DicRecord], length: usize) -> Result<()> {
+    for record in records {
+        write_elem(io, record.name)?;
+        write_elem(io, record.value)?;
+
+        if length > 16 {
+            write_elem(io, record.display_name)?;
+        }
+        if length > 24 {
+            write_elem(io, record.display_value)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn read_one_wchar(io: &mut IoHandler, elem: DicElem, end_of_tag: usize) -> Result<Option<String>> {
+    // Special case for undefined strings (see ICC Votable
+    // Proposal Submission, Dictionary Type and Metadata TAG Definition)
+    if elem.offset == 0 {
+        return Ok(None);
+    }
+
+    if elem.offset.saturating_add(elem.size) > end_of_tag {
+        return Err("String out of the tag in read_one_wchar".into());
+    }
+
+    if !(io.seek)(io, elem.offset) {
+        return Err("Seek error in read_one_wchar".into());
+    }
+
+    let mut wide = vec![0u16; elem.size / size_of::<u16>()];
+    read_u16_slice(io, &mut wide)?;
+
+    Ok(Some(String::from_utf16_lossy(&wide)))
+}
+
+fn read_one_mluc(
+    handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    elem: DicElem,
+    end_of_tag: usize,
+) -> Result<Option<MLU>> {
+    // A way to get null MLUCs
+    if elem.offset == 0 || elem.size == 0 {
+        return Ok(None);
+    }
+
+    if elem.offset.saturating_add(elem.size) > end_of_tag {
+        return Err("Display string out of the tag in read_one_mluc".into());
+    }
+
+    if !(io.seek)(io, elem.offset) {
+        return Err("Seek error in read_one_mluc".into());
+    }
+
+    let mut n_items = 0;
+    match type_mlu_read(handler, io, &mut n_items, elem.size)?.downcast::<MLU>() {
+        Ok(mlu) => Ok(Some(*mlu)),
+        Err(_) => Err("Read error in read_one_mluc".into()),
+    }
+}
+
+pub fn type_dictionary_read(
+    handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    n_items: &mut usize,
+    size_of_tag: usize,
+) -> Result<Box<dyn Any>> {
+    *n_items = 0;
+
+    let base_offset = (io.tell)(io) - size_of::<TagBase>();
+    let end_of_tag = base_offset + size_of::<TagBase>() + size_of_tag;
+
+    // Get name-value record count
+    let count = read_u32(io)? as usize;
+
+    // Get rec length
+    let length = read_u32(io)? as usize;
+
+    // Check for valid lengths
+    if length != 16 && length != 24 && length != 32 {
+        let msg = format!("Unknown record length in dictionary '{}'", length);
+        signal_error(&handler.context_id, Level::Error, ErrorCode::UnknownExtension, &msg);
+        return Err(msg);
+    }
+
+    // Check there is enough room for the directory
+    if count > io.reported_size.saturating_sub((io.tell)(io)) / length {
+        return Err("Too many records in type_dictionary_read".into());
+    }
+
+    // Read column arrays
+    let mut records = vec![DicRecord::default(); count];
+    for record in records.iter_mut() {
+        record.name = read_elem(io, base_offset)?;
+        record.value = read_elem(io, base_offset)?;
+
+        if length > 16 {
+            record.display_name = read_elem(io, base_offset)?;
+        }
+        if length > 24 {
+            record.display_value = read_elem(io, base_offset)?;
+        }
+    }
+
+    let mut dict = Dictionary::new(io.context_id);
+
+    for record in records {
+        let name = read_one_wchar(io, record.name, end_of_tag)?;
+        let value = read_one_wchar(io, record.value, end_of_tag)?;
+        let display_name = read_one_mluc(handler, io, record.display_name, end_of_tag)?;
+        let display_value = read_one_mluc(handler, io, record.display_value, end_of_tag)?;
+
+        let (Some(name), Some(value)) = (name, value) else {
+            let msg = "Bad dictionary Name/Value";
+            signal_error(&handler.context_id, Level::Error, ErrorCode::CorruptionDetected, msg);
+            return Err(msg.into());
+        };
+
+        // Names should be unique, but files with duplicates are accepted
+        dict.entries.push(DictionaryEntry {
+            display_name,
+            display_value,
+            name,
+            value,
+        });
+    }
+
+    *n_items = 1;
+    Ok(Box::new(dict))
+}
+
+pub fn type_dictionary_write(
+    handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    ptr: &dyn Any,
+    _n_items: usize,
+) -> Result<()> {
+    let Some(dict) = ptr.downcast_ref::<Dictionary>() else {
+        return Err("Invalid object to write with type_dictionary_write".into());
+    };
+
+    let base_offset = (io.tell)(io) - size_of::<TagBase>();
+
+    // Let's inspect the dictionary
+    let any_name = dict.iter().any(|entry| entry.display_name.is_some());
+    let any_value = dict.iter().any(|entry| entry.display_value.is_some());
+
+    let length = match (any_name, any_value) {
+        (_, true) => 32,
+        (true, false) => 24,
+        (false, false) => 16,
+    };
+
+    write_u32(io, dict.len() as u32)?;
+    write_u32(io, length as u32)?;
+
+    // Keep starting position of offsets table
+    let directory_pos = (io.tell)(io);
+
+    // Write a fake directory to be filled latter on
+    let mut records = vec![DicRecord::default(); dict.len()];
+    write_offset_array(io, &records, length)?;
+
+    // Repeated strings are written only once, and their offsets shared
+    let mut strings = Vec::<(&str, DicElem)>::new();
+    let mut mlus = Vec::<(&MLU, DicElem)>::new();
+
+    // Write each element. Keep track of the size as well.
+    for (record, entry) in records.iter_mut().zip(dict.iter()) {
+        for (elem, string) in [(&mut record.name, &entry.name), (&mut record.value, &entry.value)] {
+            if let Some((_, shared)) = strings.iter().find(|(s, _)| *s == string) {
+                *elem = *shared;
+                continue;
+            }
+
+            let before = (io.tell)(io);
+            write_u16_slice(io, &string.encode_utf16().collect::<Vec<_>>())?;
+
+            *elem = DicElem {
+                offset: before - base_offset,
+                size: (io.tell)(io) - before,
+            };
+            strings.push((string, *elem));
+        }
+
+        for (elem, mlu) in [
+            (&mut record.display_name, &entry.display_name),
+            (&mut record.display_value, &entry.display_value),
+        ] {
+            let Some(mlu) = mlu else {
+                continue;
+            };
+
+            if let Some((_, shared)) = mlus.iter().find(|(m, _)| *m == mlu) {
+                *elem = *shared;
+                continue;
+            }
+
+            let before = (io.tell)(io);
+            type_mlu_write(handler, io, mlu, 1)?;
+
+            *elem = DicElem {
+                offset: before - base_offset,
+                size: (io.tell)(io) - before,
+            };
+            mlus.push((mlu, *elem));
+        }
+    }
+
+    // Write the directory
+    let current_pos = (io.tell)(io);
+    if !(io.seek)(io, directory_pos) {
+        return Err("Seek error in type_dictionary_write".into());
+    }
+
+    write_offset_array(io, &records, length)?;
+
+    if !(io.seek)(io, current_pos) {
+        return Err("Seek error in type_dictionary_write".into());
+    }
+
+    Ok(())
+}
+
+type_dup_and_free!(dictionary, Dictionary);
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        sig,
+        types::{Dictionary, Profile, MLU},
+        DEFAULT_CONTEXT,
+    };
+
+    fn utf16(s: &str) -> Vec<u8> {
+        s.encode_utf16().flat_map(u16::to_be_bytes).collect()
+    }
+
+    fn dictionary_profile() -> Vec<u8> {
+        let mut display_name = MLU::new(&DEFAULT_CONTEXT, 1);
+        display_name.set_ascii(*b"en", *b"US", b"Model").unwrap();
+
+        let mut dict = Dictionary::new(&DEFAULT_CONTEXT);
+        dict.insert("ab", "first", Some(&display_name), None).unwrap();
+        dict.insert("ac", "second", None, None).unwrap();
+
+        // Another tag follows the dictionary, so that reading past its end does not hit the end of file
+        let mut description = MLU::new(&DEFAULT_CONTEXT, 1);
+        description.set_ascii(*b"en", *b"US", &[b'x'; 200]).unwrap();
+
+        let mut profile = Profile::new();
+        profile.write_tag(sig::tags::META, &dict).unwrap();
+        profile.write_tag(sig::tags::PROFILE_DESCRIPTION, &description).unwrap();
+        profile.save_to_mem().unwrap()
+    }
+
+    #[test]
+    fn dictionary_round_trip() {
+        let mem = dictionary_profile();
+        let mut profile = Profile::open_mem(&mem).unwrap();
+        let dict = profile.read_tag::<Dictionary>(sig::tags::META).unwrap();
+
+        assert_eq!(dict.len(), 2);
+        assert_eq!(dict.get("ab").unwrap().value, "first");
+        assert_eq!(dict.get("ac").unwrap().value, "second");
+        assert!(dict.get("ac").unwrap().display_name.is_none());
+
+        let mut buffer = [0u8; 16];
+        let display_name = dict.get("ab").unwrap().display_name.as_ref().unwrap();
+        let len = display_name.get_ascii(*b"en", *b"US", &mut buffer).unwrap();
+        assert_eq!(&buffer[..len - 1], b"Model");
+    }
+
+    #[test]
+    fn duplicate_names_are_read() {
+        let mut mem = dictionary_profile();
+
+        let (ab, ac) = (utf16("ab"), utf16("ac"));
+        let pos = mem.windows(ac.len()).position(|w| w == ac).unwrap();
+        mem[pos..pos + ab.len()].copy_from_slice(&ab);
+
+        let mut profile = Profile::open_mem(&mem).unwrap();
+        let dict = profile.read_tag::<Dictionary>(sig::tags::META).unwrap();
+        assert_eq!(dict.len(), 2);
+        assert_eq!(dict.iter().filter(|entry| entry.name == "ab").count(), 2);
+
+        // Only values given by the user are checked
+        let mut dict = dict.clone();
+        assert!(dict.insert("ab", "third", None, None).is_err());
+    }
+
+    #[test]
+    fn string_out_of_the_tag_is_rejected() {
+        let mut mem = dictionary_profile();
+
+        // The size of the first name follows its offset, after count and record length
+        let pos = mem.windows(4).rposition(|w| w == b"dict").unwrap();
+        mem[pos + 20..pos + 24].copy_from_slice(&0x100u32.to_be_bytes());
+
+        let mut profile = Profile::open_mem(&mem).unwrap();
+        assert!(profile.read_tag::<Dictionary>(sig::tags::META).is_err());
+    }
+}
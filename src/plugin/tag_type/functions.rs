@@ -1,12 +1,27 @@
 use std::{any::Any, mem::size_of};
 
+use log::Level;
+
 use crate::{
+    from_16_to_8, from_8_to_16,
     io::IoHandler,
-    plugin::{read_u16, write_u16, read_u32, write_u32},
-    PositionTableEntryFn, Result,
+    plugin::{
+        read_alignment, read_s15f16, read_type_base, read_u16, read_u16_slice, read_u32, read_u8,
+        write_alignment, write_s15f16, write_type_base, write_u16, write_u16_slice, write_u32, write_u8,
+    },
+    sig, signal_error,
+    state::ErrorCode,
+    types::{
+        cube_size, tag::Base as TagBase, CLutStageData, MatrixStageData, Pipeline, Stage, ToneCurve,
+        ToneCurvesStageData, MLU,
+    },
+    PositionTableEntryFn, Result, MAX_CHANNELS, MAX_INPUT_DIMENSIONS,
 };
 
-use super::TagTypeHandler;
+use super::{
+    type_curve_read, type_curve_write, type_mlu_read, type_mlu_write, type_parametric_curve_read,
+    type_parametric_curve_write, type_text_description_read, type_text_read, write_text_description, TagTypeHandler,
+};
 
 pub fn write_utf16_slice(io: &mut IoHandler, slice: &[u16]) -> Result<()> {
     for n in slice {
@@ -38,7 +53,7 @@ pub fn read_position_table(
     let current_pos = (io.tell)(io);
 
     // Verify there is enough space left to read at least two uint items for count items
-    if ((io.reported_size - current_pos) / (2 * size_of::<u32>())) < count {
+    if (io.reported_size.saturating_sub(current_pos) / (2 * size_of::<u32>())) < count {
         return Err("File too short for read in read_position_table".into());
     }
 
@@ -129,3 +144,399 @@ pub fn write_position_table(
 
     Ok(())
 }
+
+/// Number of entries of a CLUT with `a` grid points on `b` dimensions and `n` outputs, that is
+/// n * a^b. Returns None on overflow.
+pub fn uipow(n: u32, a: u32, b: u32) -> Option<usize> {
+    if a == 0 || n == 0 {
+        return Some(0);
+    }
+
+    let mut rv = 1u32;
+    for _ in 0..b {
+        rv = rv.checked_mul(a)?;
+    }
+
+    rv.checked_mul(n).map(|rc| rc as usize)
+}
+
+pub fn read_matrix_3x3(io: &mut IoHandler) -> Result<[f64; 9]> {
+    let mut matrix = [0f64; 9];
+    for value in matrix.iter_mut() {
+        *value = read_s15f16(io)?;
+    }
+
+    Ok(matrix)
+}
+
+pub fn write_matrix_3x3(io: &mut IoHandler, matrix: &[f64]) -> Result<()> {
+    for value in &matrix[..9] {
+        write_s15f16(io, *value)?;
+    }
+
+    Ok(())
+}
+
+pub fn is_identity_3x3(matrix: &[f64]) -> bool {
+    matrix[..9].iter().enumerate().all(|(i, value)| {
+        let expected = if i % 4 == 0 { 1.0 } else { 0.0 };
+        (value - expected).abs() < (1.0 / 65535.0)
+    })
+}
+
+/// The stages a Lut8 or Lut16 is made of: matrix, input curves, CLUT and output curves.
+pub struct LegacyLutParts<'a> {
+    pub matrix: Option<&'a MatrixStageData>,
+    pub pre: Option<&'a ToneCurvesStageData>,
+    pub clut: Option<&'a CLutStageData<u16>>,
+    pub clut_points: u32,
+    pub post: Option<&'a ToneCurvesStageData>,
+}
+
+/// Disassembles a pipeline into the fixed layout of Lut8 and Lut16. Fails if there are other
+/// stages, or if the stages are in the wrong order.
+pub fn split_legacy_lut<'a>(
+    handler: &TagTypeHandler,
+    lut: &'a Pipeline,
+    type_name: &str,
+) -> Result<LegacyLutParts<'a>> {
+    let not_suitable = |what: &str| -> Result<LegacyLutParts<'a>> {
+        let msg = format!("{} not suitable to be saved as {}", what, type_name);
+        signal_error(&handler.context_id, Level::Error, ErrorCode::UnknownExtension, &msg);
+        Err(msg)
+    };
+
+    let mut parts = LegacyLutParts {
+        matrix: None,
+        pre: None,
+        clut: None,
+        clut_points: 0,
+        post: None,
+    };
+    let mut stages = lut.stages().iter().peekable();
+
+    if let Some(mpe) = stages.next_if(|mpe| mpe.r#type() == sig::mpe_stage::MATRIX) {
+        if mpe.input_channels() != 3 || mpe.output_channels() != 3 {
+            return not_suitable("Matrix other than 3x3");
+        }
+        match mpe.data::<MatrixStageData>() {
+            // The offset cannot be stored
+            Some(matrix) if matrix.offset.iter().flatten().all(|offset| *offset == 0.0) => {
+                parts.matrix = Some(matrix)
+            }
+            _ => return not_suitable("Matrix with offset"),
+        }
+    }
+
+    if let Some(mpe) = stages.next_if(|mpe| mpe.r#type() == sig::mpe_stage::CURVE_SET) {
+        parts.pre = mpe.data::<ToneCurvesStageData>();
+    }
+
+    if let Some(mpe) = stages.next_if(|mpe| mpe.r#type() == sig::mpe_stage::CLUT) {
+        let Some(clut) = mpe.data::<CLutStageData<u16>>() else {
+            return not_suitable("Floating point CLUT");
+        };
+
+        // Only the same CLUT points in all dimensions are allowed
+        let n_samples = &clut.params.n_samples[..mpe.input_channels() as usize];
+        if n_samples.iter().any(|&points| points != n_samples[0]) {
+            return not_suitable("LUT with different samples per dimension");
+        }
+        if n_samples[0] > u8::MAX as usize {
+            return not_suitable("CLUT with more than 255 grid points");
+        }
+
+        parts.clut_points = n_samples[0] as u32;
+        parts.clut = Some(clut);
+    }
+
+    if let Some(mpe) = stages.next_if(|mpe| mpe.r#type() == sig::mpe_stage::CURVE_SET) {
+        parts.post = mpe.data::<ToneCurvesStageData>();
+    }
+
+    // That should be all
+    if stages.next().is_some() {
+        return not_suitable("LUT");
+    }
+
+    Ok(parts)
+}
+
+// Reads a curve of a LutAtoB or LutBtoA, which may be either a curv or a para
+fn read_embedded_curve(handler: &TagTypeHandler, io: &mut IoHandler) -> Result<ToneCurve> {
+    let base_type = read_type_base(io)?;
+    let mut n_items = 0;
+
+    let curve = match base_type {
+        sig::types::CURVE => type_curve_read(handler, io, &mut n_items, 0)?,
+        sig::types::PARAMETRIC_CURVE => type_parametric_curve_read(handler, io, &mut n_items, 0)?,
+        _ => {
+            let msg = format!("Unknown curve type '{:x}'", base_type.0);
+            signal_error(&handler.context_id, Level::Error, ErrorCode::UnknownExtension, &msg);
+            return Err(msg);
+        }
+    };
+
+    match curve.downcast::<ToneCurve>() {
+        Ok(curve) => Ok(*curve),
+        Err(_) => Err("Read error in read_embedded_curve".into()),
+    }
+}
+
+/// Reads a set of curves stored at an offset of a LutAtoB or LutBtoA.
+pub fn read_set_of_curves(
+    handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    offset: usize,
+    n_curves: u32,
+) -> Result<Stage> {
+    if n_curves as usize > MAX_CHANNELS {
+        return Err("Too many curves in read_set_of_curves".into());
+    }
+
+    if !(io.seek)(io, offset) {
+        return Err("Seek error in read_set_of_curves".into());
+    }
+
+    let mut curves = Vec::with_capacity(n_curves as usize);
+    for _ in 0..n_curves {
+        curves.push(read_embedded_curve(handler, io)?);
+        read_alignment(io)?;
+    }
+
+    Stage::new_tone_curves(io.context_id, n_curves, Some(&curves))
+}
+
+/// Reads a CLUT stored at an offset of a LutAtoB or LutBtoA. Entries may be 8 or 16 bits.
+pub fn read_clut(
+    handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    offset: usize,
+    input_channels: u32,
+    output_channels: u32,
+) -> Result<Stage> {
+    if !(io.seek)(io, offset) {
+        return Err("Seek error in read_clut".into());
+    }
+
+    let mut grid_points_8 = [0u8; MAX_INPUT_DIMENSIONS + 1]; // Number of grid points in each dimension.
+    if (io.read)(io, &mut grid_points_8, MAX_INPUT_DIMENSIONS + 1, 1) != MAX_INPUT_DIMENSIONS + 1 {
+        return Err("Read error in read_clut".into());
+    }
+
+    if input_channels as usize > MAX_INPUT_DIMENSIONS {
+        return Err("Too many input channels in read_clut".into());
+    }
+
+    let mut grid_points = [0u32; MAX_INPUT_DIMENSIONS];
+    for (points, points_8) in grid_points.iter_mut().zip(&grid_points_8[..input_channels as usize]) {
+        // Impossible value, 0 for no CLUT and then 2 at least
+        if *points_8 == 1 {
+            return Err("Invalid number of grid points in read_clut".into());
+        }
+        *points = *points_8 as u32;
+    }
+
+    let precision = read_u8(io)?;
+    read_u8(io)?; // Padding
+    read_u8(io)?;
+    read_u8(io)?;
+
+    let Some(n_entries) = cube_size(&grid_points[..input_channels as usize])
+        .and_then(|n| n.checked_mul(output_channels as usize))
+    else {
+        return Err("CLUT too large in read_clut".into());
+    };
+
+    let mut table = vec![0u16; n_entries];
+    match precision {
+        1 => {
+            for value in table.iter_mut() {
+                *value = from_8_to_16(read_u8(io)?);
+            }
+        }
+        2 => {
+            read_u16_slice(io, &mut table)?;
+        }
+        _ => {
+            let msg = format!("Unknown precision of '{}'", precision);
+            signal_error(&handler.context_id, Level::Error, ErrorCode::UnknownExtension, &msg);
+            return Err(msg);
+        }
+    }
+
+    Stage::new_clut_16bit_granular(
+        io.context_id,
+        &grid_points[..input_channels as usize],
+        input_channels,
+        output_channels,
+        Some(&table),
+    )
+}
+
+/// Reads a 3x3 matrix plus offset stored at an offset of a LutAtoB or LutBtoA.
+pub fn read_matrix(io: &mut IoHandler, offset: usize) -> Result<Stage> {
+    if !(io.seek)(io, offset) {
+        return Err("Seek error in read_matrix".into());
+    }
+
+    let matrix = read_matrix_3x3(io)?;
+    let mut offsets = [0f64; 3];
+    for value in offsets.iter_mut() {
+        *value = read_s15f16(io)?;
+    }
+
+    Stage::new_matrix(io.context_id, 3, 3, &matrix, Some(&offsets))
+}
+
+/// Writes a set of curves of a LutAtoB or LutBtoA, each one followed by alignment.
+pub fn write_set_of_curves(handler: &TagTypeHandler, io: &mut IoHandler, mpe: &Stage) -> Result<()> {
+    let Some(curves) = mpe.data::<ToneCurvesStageData>() else {
+        return Err("Invalid stage in write_set_of_curves".into());
+    };
+
+    for curve in curves.the_curves.iter() {
+        // Tables, segmented and inverted curves can only be saved as curv
+        let current_type = match curve.parametric_type() {
+            1..=5 => sig::types::PARAMETRIC_CURVE,
+            _ => sig::types::CURVE,
+        };
+
+        write_type_base(io, current_type)?;
+        match current_type {
+            sig::types::PARAMETRIC_CURVE => type_parametric_curve_write(handler, io, curve, 1)?,
+            _ => type_curve_write(handler, io, curve, 1)?,
+        }
+
+        write_alignment(io)?;
+    }
+
+    Ok(())
+}
+
+/// Writes a 16 bit CLUT of a LutAtoB or LutBtoA with the given precision in bytes.
+pub fn write_clut(handler: &TagTypeHandler, io: &mut IoHandler, precision: u8, mpe: &Stage) -> Result<()> {
+    let Some(clut) = mpe.data::<CLutStageData<u16>>() else {
+        let msg = "Cannot save floating point data, CLUT are 8 or 16 bit only";
+        signal_error(&handler.context_id, Level::Error, ErrorCode::NotSuitable, msg);
+        return Err(msg.into());
+    };
+
+    let mut grid_points = [0u8; MAX_INPUT_DIMENSIONS + 1]; // Number of grid points in each dimension.
+    for (points, n_samples) in grid_points.iter_mut().zip(&clut.params.n_samples[..mpe.input_channels() as usize]) {
+        if *n_samples > u8::MAX as usize {
+            let msg = "CLUT with more than 255 grid points";
+            signal_error(&handler.context_id, Level::Error, ErrorCode::NotSuitable, msg);
+            return Err(msg.into());
+        }
+        *points = *n_samples as u8;
+    }
+
+    if !(io.write)(io, grid_points.len(), &grid_points) {
+        return Err("Write error in write_clut".into());
+    }
+
+    write_u8(io, precision)?;
+    write_u8(io, 0)?;
+    write_u8(io, 0)?;
+    write_u8(io, 0)?;
+
+    // Precision can be 1 or 2 bytes
+    match precision {
+        1 => {
+            for value in clut.table() {
+                write_u8(io, from_16_to_8(*value))?;
+            }
+        }
+        2 => write_u16_slice(io, clut.table())?,
+        _ => {
+            let msg = format!("Unknown precision of '{}'", precision);
+            signal_error(&handler.context_id, Level::Error, ErrorCode::UnknownExtension, &msg);
+            return Err(msg);
+        }
+    }
+
+    write_alignment(io)
+}
+
+/// Writes a 3x3 matrix plus offset of a LutAtoB or LutBtoA.
+pub fn write_matrix(handler: &TagTypeHandler, io: &mut IoHandler, mpe: &Stage) -> Result<()> {
+    let (Some(matrix), 3, 3) = (mpe.data::<MatrixStageData>(), mpe.input_channels(), mpe.output_channels()) else {
+        let msg = "Only 3x3 matrices can be saved in LutAtoB or LutBtoA";
+        signal_error(&handler.context_id, Level::Error, ErrorCode::NotSuitable, msg);
+        return Err(msg.into());
+    };
+
+    write_matrix_3x3(io, &matrix.double)?;
+
+    match &matrix.offset {
+        Some(offset) => {
+            for value in &offset[..3] {
+                write_s15f16(io, *value)?;
+            }
+        }
+        None => {
+            for _ in 0..3 {
+                write_s15f16(io, 0.0)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads a text embedded in other types, which may be a text, a text description or a
+/// multi-localized unicode.
+pub fn read_embedded_text(handler: &TagTypeHandler, io: &mut IoHandler, size_of_tag: usize) -> Result<MLU> {
+    let base_type = read_type_base(io)?;
+    let size_of_tag = size_of_tag.saturating_sub(size_of::<TagBase>());
+    let mut n_items = 0;
+
+    let mlu = match base_type {
+        sig::types::TEXT => type_text_read(handler, io, &mut n_items, size_of_tag)?,
+        sig::types::TEXT_DESCRIPTION => type_text_description_read(handler, io, &mut n_items, size_of_tag)?,
+        sig::types::MULTI_LOCALIZED_UNICODE => type_mlu_read(handler, io, &mut n_items, size_of_tag)?,
+        _ => return Err(format!("Unknown embedded text type '{:x}'", base_type.0)),
+    };
+
+    match mlu.downcast::<MLU>() {
+        Ok(mlu) => Ok(*mlu),
+        Err(_) => Err("Read error in read_embedded_text".into()),
+    }
+}
+
+/// Writes a text embedded in other types: a text description in V2, a multi-localized unicode in V4.
+pub fn save_description(handler: &TagTypeHandler, io: &mut IoHandler, text: &MLU) -> Result<()> {
+    if handler.icc_version < 0x4000000 {
+        write_type_base(io, sig::types::TEXT_DESCRIPTION)?;
+        write_text_description(io, text, false)
+    } else {
+        write_type_base(io, sig::types::MULTI_LOCALIZED_UNICODE)?;
+        type_mlu_write(handler, io, text, 1)
+    }
+}
+
+// Color names, prefix and suffix are stored in fixed 32 byte fields
+const NAME_SIZE: usize = 32;
+
+pub fn read_name(io: &mut IoHandler) -> Result<String> {
+    let mut name = [0u8; NAME_SIZE];
+    if (io.read)(io, &mut name, NAME_SIZE, 1) != NAME_SIZE {
+        return Err("Read error in read_name".into());
+    }
+
+    let len = name.iter().position(|&c| c == 0).unwrap_or(NAME_SIZE);
+    Ok(String::from_utf8_lossy(&name[..len]).into_owned())
+}
+
+pub fn write_name(io: &mut IoHandler, value: &str) -> Result<()> {
+    let mut name = [0u8; NAME_SIZE];
+    let len = value.len().min(NAME_SIZE);
+    name[..len].copy_from_slice(&value.as_bytes()[..len]);
+
+    if !(io.write)(io, NAME_SIZE, &name) {
+        return Err("Write error in write_name".into());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,301 @@
+use std::any::Any;
+
+use log::Level;
+
+use crate::{
+    io::IoHandler,
+    plugin::{read_u16, read_u16_slice, read_u8, write_u16, write_u16_slice, write_u8},
+    sig, signal_error,
+    state::ErrorCode,
+    types::{Pipeline, Signature, Stage, StageLoc, ToneCurve, ToneCurvesStageData},
+    Context, Result, MAX_CHANNELS,
+};
+
+use super::{
+    is_identity_3x3, read_matrix_3x3, split_legacy_lut, uipow, write_matrix_3x3, TagTypeHandler,
+};
+
+// Read 16 bit tables as gamma functions
+fn read_16bit_tables(
+    context_id: &'static Context,
+    io: &mut IoHandler,
+    lut: &mut Pipeline,
+    n_channels: u32,
+    n_entries: u32,
+) -> Result<()> {
+    // Maybe an empty table? (this is a lcms extension)
+    if n_entries == 0 {
+        return Ok(());
+    }
+
+    // Check for malicious profiles
+    if n_entries < 2 || n_channels as usize > MAX_CHANNELS {
+        return Err("Invalid tables in read_16bit_tables".into());
+    }
+
+    let mut tables = Vec::with_capacity(n_channels as usize);
+    let mut values = vec![0u16; n_entries as usize];
+    for _ in 0..n_channels {
+        read_u16_slice(io, &mut values)?;
+        tables.push(ToneCurve::build_tabulated_16(context_id, &values)?);
+    }
+
+    // Add the table (which may certainly be an identity, but this is up to the optimizer, not the reading code)
+    lut.insert_stage(
+        StageLoc::AtEnd,
+        Stage::new_tone_curves(context_id, n_channels, Some(&tables))?,
+    )
+}
+
+fn write_16bit_tables(io: &mut IoHandler, tables: &ToneCurvesStageData) -> Result<()> {
+    for curve in tables.the_curves.iter() {
+        write_u16_slice(io, curve.table_16())?;
+    }
+
+    Ok(())
+}
+
+pub fn type_lut16_read(
+    _handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    n_items: &mut usize,
+    _size_of_tag: usize,
+) -> Result<Box<dyn Any>> {
+    *n_items = 0;
+
+    let context_id = io.context_id;
+
+    let input_channels = read_u8(io)? as u32;
+    let output_channels = read_u8(io)? as u32;
+    let clut_points = read_u8(io)? as u32; // 255 maximum
+    read_u8(io)?; // Padding
+
+    // Do some checking
+    if input_channels == 0 || input_channels as usize > MAX_CHANNELS {
+        return Err("Invalid number of input channels in type_lut16_read".into());
+    }
+    if output_channels == 0 || output_channels as usize > MAX_CHANNELS {
+        return Err("Invalid number of output channels in type_lut16_read".into());
+    }
+
+    // Allocates an empty LUT
+    let mut new_lut = Pipeline::new(context_id, input_channels, output_channels)?;
+
+    // Read the Matrix
+    let matrix = read_matrix_3x3(io)?;
+
+    // Only operates on 3 channels
+    if input_channels == 3 && !is_identity_3x3(&matrix) {
+        new_lut.insert_stage(
+            StageLoc::AtEnd,
+            Stage::new_matrix(context_id, 3, 3, &matrix, None)?,
+        )?;
+    }
+
+    let input_entries = read_u16(io)? as u32;
+    let output_entries = read_u16(io)? as u32;
+
+    if input_entries > 0x7FFF || output_entries > 0x7FFF {
+        return Err("Too many table entries in type_lut16_read".into());
+    }
+    // Impossible value, 0 for no CLUT and then 2 at least
+    if clut_points == 1 {
+        return Err("Invalid number of CLUT points in type_lut16_read".into());
+    }
+
+    // Get input tables
+    read_16bit_tables(context_id, io, &mut new_lut, input_channels, input_entries)?;
+
+    // Get 3D CLUT. Check the overflow....
+    let Some(n_tab_size) = uipow(output_channels, clut_points, input_channels) else {
+        return Err("CLUT too large in type_lut16_read".into());
+    };
+    if n_tab_size > 0 {
+        let mut t = vec![0u16; n_tab_size];
+        read_u16_slice(io, &mut t)?;
+
+        new_lut.insert_stage(
+            StageLoc::AtEnd,
+            Stage::new_clut_16bit(context_id, clut_points, input_channels, output_channels, Some(&t))?,
+        )?;
+    }
+
+    // Get output tables
+    read_16bit_tables(context_id, io, &mut new_lut, output_channels, output_entries)?;
+
+    *n_items = 1;
+    Ok(Box::new(new_lut))
+}
+
+pub fn type_lut16_write(
+    handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    ptr: &dyn Any,
+    _n_items: usize,
+) -> Result<()> {
+    let Some(new_lut) = ptr.downcast_ref::<Pipeline>() else {
+        return Err("Invalid object to write with type_lut16_write".into());
+    };
+
+    // Disassemble the LUT into components.
+    let parts = split_legacy_lut(handler, new_lut, "LUT16")?;
+
+    let input_channels = new_lut.input_channels();
+    let output_channels = new_lut.output_channels();
+
+    // All curves of a set share the same number of entries
+    for tables in [parts.pre, parts.post].into_iter().flatten() {
+        let n_entries = tables.the_curves[0].table_16().len();
+        if tables.the_curves.iter().any(|curve| curve.table_16().len() != n_entries)
+            || n_entries > u16::MAX as usize
+        {
+            let msg = "Curves with different number of entries not suitable to be saved as LUT16";
+            signal_error(&handler.context_id, Level::Error, ErrorCode::UnknownExtension, msg);
+            return Err(msg.into());
+        }
+    }
+
+    write_u8(io, input_channels as u8)?;
+    write_u8(io, output_channels as u8)?;
+    write_u8(io, parts.clut_points as u8)?;
+    write_u8(io, 0)?; // Padding
+
+    match parts.matrix {
+        Some(matrix) => write_matrix_3x3(io, &matrix.double)?,
+        None => write_matrix_3x3(io, &[1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0])?,
+    }
+
+    let n_entries = |tables: Option<&ToneCurvesStageData>| {
+        tables.map_or(2, |tables| tables.the_curves[0].table_16().len() as u16)
+    };
+    write_u16(io, n_entries(parts.pre))?;
+    write_u16(io, n_entries(parts.post))?;
+
+    // The prelinearization table
+    match parts.pre {
+        Some(pre) => write_16bit_tables(io, pre)?,
+        None => {
+            for _ in 0..input_channels {
+                write_u16(io, 0)?;
+                write_u16(io, 0xffff)?;
+            }
+        }
+    }
+
+    // The 3D CLUT.
+    if let Some(clut) = parts.clut {
+        write_u16_slice(io, clut.table())?;
+    }
+
+    // The postlinearization table
+    match parts.post {
+        Some(post) => write_16bit_tables(io, post)?,
+        None => {
+            for _ in 0..output_channels {
+                write_u16(io, 0)?;
+                write_u16(io, 0xffff)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+type_dup_and_free!(lut16, Pipeline, dup);
+
+
+fn save_as_8_bits(data: &dyn Any) -> bool {
+    data.downcast_ref::<Pipeline>().is_some_and(|lut| lut.save_as_8_bits)
+}
+
+/// Decide which LUT type to use on writing device to PCS tags
+pub fn decide_lut_type_a2b(icc_version: f64, data: &dyn Any) -> Signature {
+    if icc_version < 4.0 {
+        if save_as_8_bits(data) {
+            return sig::types::LUT8;
+        }
+        return sig::types::LUT16;
+    }
+
+    sig::types::LUT_A_TO_B
+}
+
+/// Decide which LUT type to use on writing PCS to device tags
+pub fn decide_lut_type_b2a(icc_version: f64, data: &dyn Any) -> Signature {
+    if icc_version < 4.0 {
+        if save_as_8_bits(data) {
+            return sig::types::LUT8;
+        }
+        return sig::types::LUT16;
+    }
+
+    sig::types::LUT_B_TO_A
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use crate::{
+        sig,
+        types::{CLutStageData, Pipeline, Profile, Stage, StageLoc, ToneCurve, ToneCurvesStageData},
+        DEFAULT_CONTEXT,
+    };
+
+    // Curves, a 3 point CLUT and curves again, all of them with values that fit in 8 bits
+    pub(crate) fn legacy_lut() -> Pipeline {
+        let ctx = &DEFAULT_CONTEXT;
+        let table: Vec<u16> = (0..256u32).map(|i| ((i * i / 255) * 257) as u16).collect();
+        let curve = ToneCurve::build_tabulated_16(ctx, &table).unwrap();
+        let curves = [curve.clone(), curve.clone(), curve];
+
+        let clut: Vec<u16> = (0..27 * 3u32).map(|i| ((i * 37 % 256) * 257) as u16).collect();
+
+        let mut lut = Pipeline::new(ctx, 3, 3).unwrap();
+        lut.insert_stage(StageLoc::AtEnd, Stage::new_tone_curves(ctx, 3, Some(&curves)).unwrap()).unwrap();
+        lut.insert_stage(StageLoc::AtEnd, Stage::new_clut_16bit(ctx, 3, 3, 3, Some(&clut)).unwrap()).unwrap();
+        lut.insert_stage(StageLoc::AtEnd, Stage::new_tone_curves(ctx, 3, Some(&curves)).unwrap()).unwrap();
+        lut
+    }
+
+    pub(crate) fn round_trip(lut: &Pipeline) -> (Vec<u8>, Pipeline) {
+        let mut profile = Profile::new();
+        profile.set_version(2.1).unwrap();
+        profile.write_tag(sig::tags::A_TO_B0, lut).unwrap();
+        let mem = profile.save_to_mem().unwrap();
+
+        let mut profile = Profile::open_mem(&mem).unwrap();
+        let raw = profile.read_raw_tag(sig::tags::A_TO_B0).unwrap();
+        (raw, profile.read_tag::<Pipeline>(sig::tags::A_TO_B0).unwrap().dup().unwrap())
+    }
+
+    pub(crate) fn assert_same_stages(a: &Pipeline, b: &Pipeline) {
+        assert_eq!(a.stages().len(), b.stages().len());
+
+        for (a, b) in a.stages().iter().zip(b.stages()) {
+            assert!(a.r#type() == b.r#type());
+
+            if let (Some(a), Some(b)) = (a.data::<ToneCurvesStageData>(), b.data::<ToneCurvesStageData>()) {
+                for (a, b) in a.the_curves.iter().zip(&b.the_curves) {
+                    assert_eq!(a.table_16(), b.table_16());
+                }
+            }
+            if let (Some(a), Some(b)) = (a.data::<CLutStageData<u16>>(), b.data::<CLutStageData<u16>>()) {
+                assert_eq!(a.table(), b.table());
+            }
+        }
+    }
+
+    #[test]
+    fn lut16_round_trip() {
+        let lut = legacy_lut();
+
+        let (raw, read) = round_trip(&lut);
+        assert_eq!(&raw[..4], b"mft2");
+        assert!(!read.save_as_8_bits);
+        assert_same_stages(&lut, &read);
+
+        let (mut expected, mut out) = ([0u16; 3], [0u16; 3]);
+        lut.eval_16(&[0x1234, 0x8000, 0xfedc], &mut expected);
+        read.eval_16(&[0x1234, 0x8000, 0xfedc], &mut out);
+        assert_eq!(out, expected);
+    }
+}
@@ -0,0 +1,242 @@
+use std::any::Any;
+
+use log::Level;
+
+use crate::{
+    from_16_to_8, from_8_to_16,
+    io::IoHandler,
+    plugin::{read_u8, write_u8},
+    signal_error,
+    state::ErrorCode,
+    types::{Pipeline, Stage, StageLoc, ToneCurve, ToneCurvesStageData},
+    Context, Result, MAX_CHANNELS,
+};
+
+use super::{
+    is_identity_3x3, read_matrix_3x3, split_legacy_lut, uipow, write_matrix_3x3, TagTypeHandler,
+};
+
+// Read 8 bit tables as gamma functions
+fn read_8bit_tables(
+    context_id: &'static Context,
+    io: &mut IoHandler,
+    lut: &mut Pipeline,
+    n_channels: u32,
+) -> Result<()> {
+    if n_channels == 0 || n_channels as usize > MAX_CHANNELS {
+        return Err("Invalid number of channels in read_8bit_tables".into());
+    }
+
+    let mut tables = Vec::with_capacity(n_channels as usize);
+    let mut temp = [0u8; 256];
+    let mut values = [0u16; 256];
+    for _ in 0..n_channels {
+        if (io.read)(io, &mut temp, 256, 1) != 256 {
+            return Err("Read error in read_8bit_tables".into());
+        }
+        for (value, byte) in values.iter_mut().zip(temp) {
+            *value = from_8_to_16(byte);
+        }
+        tables.push(ToneCurve::build_tabulated_16(context_id, &values)?);
+    }
+
+    lut.insert_stage(
+        StageLoc::AtEnd,
+        Stage::new_tone_curves(context_id, n_channels, Some(&tables))?,
+    )
+}
+
+fn write_8bit_tables(
+    handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    n_channels: u32,
+    tables: Option<&ToneCurvesStageData>,
+) -> Result<()> {
+    for i in 0..n_channels as usize {
+        let Some(tables) = tables else {
+            // Identity ramp when there are no curves
+            for j in 0..=255u8 {
+                write_u8(io, j)?;
+            }
+            continue;
+        };
+
+        let table = tables.the_curves[i].table_16();
+
+        // Usual case of identity curves
+        if table.len() == 2 && table[0] == 0 && table[1] == 65535 {
+            for j in 0..=255u8 {
+                write_u8(io, j)?;
+            }
+        } else if table.len() != 256 {
+            let msg = "LUT8 needs 256 entries on prelinearization";
+            signal_error(&handler.context_id, Level::Error, ErrorCode::Range, msg);
+            return Err(msg.into());
+        } else {
+            for value in table {
+                write_u8(io, from_16_to_8(*value))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub fn type_lut8_read(
+    _handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    n_items: &mut usize,
+    _size_of_tag: usize,
+) -> Result<Box<dyn Any>> {
+    *n_items = 0;
+
+    let context_id = io.context_id;
+
+    let input_channels = read_u8(io)? as u32;
+    let output_channels = read_u8(io)? as u32;
+    let clut_points = read_u8(io)? as u32;
+
+    // Impossible value, 0 for no CLUT and then 2 at least
+    if clut_points == 1 {
+        return Err("Invalid number of CLUT points in type_lut8_read".into());
+    }
+
+    read_u8(io)?; // Padding
+
+    // Do some checking
+    if input_channels == 0 || input_channels as usize > MAX_CHANNELS {
+        return Err("Invalid number of input channels in type_lut8_read".into());
+    }
+    if output_channels == 0 || output_channels as usize > MAX_CHANNELS {
+        return Err("Invalid number of output channels in type_lut8_read".into());
+    }
+
+    // Allocates an empty Pipeline
+    let mut new_lut = Pipeline::new(context_id, input_channels, output_channels)?;
+
+    // Read the Matrix
+    let matrix = read_matrix_3x3(io)?;
+
+    // Only operates if not identity...
+    if input_channels == 3 && !is_identity_3x3(&matrix) {
+        new_lut.insert_stage(
+            StageLoc::AtBegin,
+            Stage::new_matrix(context_id, 3, 3, &matrix, None)?,
+        )?;
+    }
+
+    // Get input tables
+    read_8bit_tables(context_id, io, &mut new_lut, input_channels)?;
+
+    // Get 3D CLUT. Check the overflow....
+    let Some(n_tab_size) = uipow(output_channels, clut_points, input_channels) else {
+        return Err("CLUT too large in type_lut8_read".into());
+    };
+    if n_tab_size > 0 {
+        let mut temp = vec![0u8; n_tab_size];
+        if (io.read)(io, &mut temp, n_tab_size, 1) != n_tab_size {
+            return Err("Read error in type_lut8_read".into());
+        }
+        let t = temp.into_iter().map(from_8_to_16).collect::<Vec<_>>();
+
+        new_lut.insert_stage(
+            StageLoc::AtEnd,
+            Stage::new_clut_16bit(context_id, clut_points, input_channels, output_channels, Some(&t))?,
+        )?;
+    }
+
+    // Get output tables
+    read_8bit_tables(context_id, io, &mut new_lut, output_channels)?;
+
+    // So it gets written back as 8 bits
+    new_lut.set_save_as_8_bits(true);
+
+    *n_items = 1;
+    Ok(Box::new(new_lut))
+}
+
+pub fn type_lut8_write(
+    handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    ptr: &dyn Any,
+    _n_items: usize,
+) -> Result<()> {
+    let Some(new_lut) = ptr.downcast_ref::<Pipeline>() else {
+        return Err("Invalid object to write with type_lut8_write".into());
+    };
+
+    if new_lut.stages().is_empty() {
+        let msg = "Empty LUT not suitable to be saved as LUT8";
+        signal_error(&handler.context_id, Level::Error, ErrorCode::UnknownExtension, msg);
+        return Err(msg.into());
+    }
+
+    // Disassemble the LUT into components.
+    let parts = split_legacy_lut(handler, new_lut, "LUT8")?;
+
+    let input_channels = new_lut.input_channels();
+    let output_channels = new_lut.output_channels();
+
+    write_u8(io, input_channels as u8)?;
+    write_u8(io, output_channels as u8)?;
+    write_u8(io, parts.clut_points as u8)?;
+    write_u8(io, 0)?; // Padding
+
+    match parts.matrix {
+        Some(matrix) => write_matrix_3x3(io, &matrix.double)?,
+        None => write_matrix_3x3(io, &[1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0])?,
+    }
+
+    // The prelinearization table
+    write_8bit_tables(handler, io, input_channels, parts.pre)?;
+
+    // The 3D CLUT.
+    if let Some(clut) = parts.clut {
+        for value in clut.table() {
+            write_u8(io, from_16_to_8(*value))?;
+        }
+    }
+
+    // The postlinearization table
+    write_8bit_tables(handler, io, output_channels, parts.post)?;
+
+    Ok(())
+}
+
+type_dup_and_free!(lut8, Pipeline, dup);
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        plugin::tag_type::lut16::tests::{assert_same_stages, legacy_lut, round_trip},
+        sig,
+        types::{Pipeline, Profile, Stage, StageLoc, ToneCurve},
+        DEFAULT_CONTEXT,
+    };
+
+    #[test]
+    fn lut8_round_trip() {
+        let mut lut = legacy_lut();
+        lut.set_save_as_8_bits(true);
+
+        let (raw, read) = round_trip(&lut);
+        assert_eq!(&raw[..4], b"mft1");
+        assert!(read.save_as_8_bits);
+        assert_same_stages(&lut, &read);
+    }
+
+    #[test]
+    fn short_tables_are_not_saved() {
+        let ctx = &DEFAULT_CONTEXT;
+        let curve = ToneCurve::build_tabulated_16(ctx, &[0, 0x4000, 0xffff]).unwrap();
+
+        let mut lut = Pipeline::new(ctx, 1, 1).unwrap();
+        lut.insert_stage(StageLoc::AtEnd, Stage::new_tone_curves(ctx, 1, Some(&[curve])).unwrap()).unwrap();
+        lut.set_save_as_8_bits(true);
+
+        let mut profile = Profile::new();
+        profile.set_version(2.1).unwrap();
+        profile.write_tag(sig::tags::A_TO_B0, &lut).unwrap();
+        assert!(profile.save_to_mem().is_err());
+    }
+}
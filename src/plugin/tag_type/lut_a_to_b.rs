@@ -0,0 +1,250 @@
+use std::{any::Any, mem::size_of};
+
+use log::Level;
+
+use crate::{
+    io::IoHandler,
+    plugin::{read_u16, read_u32, read_u8, write_u16, write_u32, write_u8},
+    sig::mpe_stage::{CLUT, CURVE_SET, MATRIX},
+    signal_error,
+    state::ErrorCode,
+    types::{tag::Base as TagBase, Pipeline, StageLoc},
+    Result, MAX_CHANNELS,
+};
+
+use super::{
+    read_clut, read_matrix, read_set_of_curves, write_clut, write_matrix, write_set_of_curves, TagTypeHandler,
+};
+
+// LutAtoB type
+
+// This structure represents a colour transform. The type contains up to five processing
+// elements which are stored in the AtoBTag tag in the following order: a set of one
+// dimensional curves, a 3 by 3 matrix with offset terms, a set of one dimensional curves,
+// a multidimensional lookup table, and a set of one dimensional output curves.
+// Data are processed using these elements via the following sequence:
+//
+// ("A" curves) -> (multidimensional lookup table - CLUT) -> ("M" curves) -> (matrix) -> ("B" curves).
+
+pub fn type_lut_a_to_b_read(
+    handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    n_items: &mut usize,
+    _size_of_tag: usize,
+) -> Result<Box<dyn Any>> {
+    *n_items = 0;
+
+    let base_offset = (io.tell)(io) - size_of::<TagBase>();
+
+    let input_chan = read_u8(io)? as u32;
+    let output_chan = read_u8(io)? as u32;
+
+    read_u16(io)?; // Padding
+
+    let offset_b = read_u32(io)? as usize;
+    let offset_mat = read_u32(io)? as usize;
+    let offset_m = read_u32(io)? as usize;
+    let offset_c = read_u32(io)? as usize;
+    let offset_a = read_u32(io)? as usize;
+
+    if input_chan == 0 || input_chan as usize >= MAX_CHANNELS {
+        return Err("Invalid number of input channels in type_lut_a_to_b_read".into());
+    }
+    if output_chan == 0 || output_chan as usize >= MAX_CHANNELS {
+        return Err("Invalid number of output channels in type_lut_a_to_b_read".into());
+    }
+
+    // Allocates an empty LUT
+    let mut new_lut = Pipeline::new(io.context_id, input_chan, output_chan)?;
+
+    if offset_a != 0 {
+        let a = read_set_of_curves(handler, io, base_offset + offset_a, input_chan)?;
+        new_lut.insert_stage(StageLoc::AtEnd, a)?;
+    }
+
+    if offset_c != 0 {
+        let clut = read_clut(handler, io, base_offset + offset_c, input_chan, output_chan)?;
+        new_lut.insert_stage(StageLoc::AtEnd, clut)?;
+    }
+
+    if offset_m != 0 {
+        let m = read_set_of_curves(handler, io, base_offset + offset_m, output_chan)?;
+        new_lut.insert_stage(StageLoc::AtEnd, m)?;
+    }
+
+    if offset_mat != 0 {
+        let matrix = read_matrix(io, base_offset + offset_mat)?;
+        new_lut.insert_stage(StageLoc::AtEnd, matrix)?;
+    }
+
+    if offset_b != 0 {
+        let b = read_set_of_curves(handler, io, base_offset + offset_b, output_chan)?;
+        new_lut.insert_stage(StageLoc::AtEnd, b)?;
+    }
+
+    *n_items = 1;
+    Ok(Box::new(new_lut))
+}
+
+pub fn type_lut_a_to_b_write(
+    handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    ptr: &dyn Any,
+    _n_items: usize,
+) -> Result<()> {
+    let Some(lut) = ptr.downcast_ref::<Pipeline>() else {
+        return Err("Invalid object to write with type_lut_a_to_b_write".into());
+    };
+
+    let base_offset = (io.tell)(io) - size_of::<TagBase>();
+
+    let (a, clut, m, matrix, b) = if lut.stages().is_empty() {
+        (None, None, None, None, None)
+    } else if let Some([b]) = lut.check_and_retrieve_stages(&[CURVE_SET]) {
+        (None, None, None, None, Some(b))
+    } else if let Some([m, matrix, b]) = lut.check_and_retrieve_stages(&[CURVE_SET, MATRIX, CURVE_SET]) {
+        (None, None, Some(m), Some(matrix), Some(b))
+    } else if let Some([a, clut, b]) = lut.check_and_retrieve_stages(&[CURVE_SET, CLUT, CURVE_SET]) {
+        (Some(a), Some(clut), None, None, Some(b))
+    } else if let Some([a, clut, m, matrix, b]) =
+        lut.check_and_retrieve_stages(&[CURVE_SET, CLUT, CURVE_SET, MATRIX, CURVE_SET])
+    {
+        (Some(a), Some(clut), Some(m), Some(matrix), Some(b))
+    } else {
+        let msg = "LUT is not suitable to be saved as LutAToB";
+        signal_error(&handler.context_id, Level::Error, ErrorCode::NotSuitable, msg);
+        return Err(msg.into());
+    };
+
+    // Get input, output channels
+    write_u8(io, lut.input_channels() as u8)?;
+    write_u8(io, lut.output_channels() as u8)?;
+    write_u16(io, 0)?;
+
+    // Keep directory to be filled latter
+    let directory_pos = (io.tell)(io);
+
+    // Write a zero directory
+    for _ in 0..5 {
+        write_u32(io, 0)?;
+    }
+
+    let (mut offset_a, mut offset_c, mut offset_m, mut offset_mat, mut offset_b) = (0, 0, 0, 0, 0);
+
+    if let Some(a) = a {
+        offset_a = (io.tell)(io) - base_offset;
+        write_set_of_curves(handler, io, a)?;
+    }
+
+    if let Some(clut) = clut {
+        offset_c = (io.tell)(io) - base_offset;
+        write_clut(handler, io, if lut.save_as_8_bits { 1 } else { 2 }, clut)?;
+    }
+
+    if let Some(m) = m {
+        offset_m = (io.tell)(io) - base_offset;
+        write_set_of_curves(handler, io, m)?;
+    }
+
+    if let Some(matrix) = matrix {
+        offset_mat = (io.tell)(io) - base_offset;
+        write_matrix(handler, io, matrix)?;
+    }
+
+    if let Some(b) = b {
+        offset_b = (io.tell)(io) - base_offset;
+        write_set_of_curves(handler, io, b)?;
+    }
+
+    let current_pos = (io.tell)(io);
+
+    if !(io.seek)(io, directory_pos) {
+        return Err("Seek error in type_lut_a_to_b_write".into());
+    }
+
+    for offset in [offset_b, offset_mat, offset_m, offset_c, offset_a] {
+        write_u32(io, offset as u32)?;
+    }
+
+    if !(io.seek)(io, current_pos) {
+        return Err("Seek error in type_lut_a_to_b_write".into());
+    }
+
+    Ok(())
+}
+
+type_dup_and_free!(lut_a_to_b, Pipeline, dup);
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use crate::{
+        sig,
+        sig::mpe_stage::{CLUT, CURVE_SET, MATRIX},
+        types::{Pipeline, Profile, Signature, Stage, StageLoc, ToneCurve},
+        DEFAULT_CONTEXT,
+    };
+
+    pub(crate) fn curves(gamma: f64) -> Stage {
+        let curve = ToneCurve::build_parametric(&DEFAULT_CONTEXT, 1, &[gamma]).unwrap();
+        Stage::new_tone_curves(&DEFAULT_CONTEXT, 3, Some(&[curve.clone(), curve.clone(), curve])).unwrap()
+    }
+
+    pub(crate) fn clut() -> Stage {
+        let table: Vec<u16> = (0..2 * 3 * 4 * 3u32).map(|i| (i * 911 % 65536) as u16).collect();
+        Stage::new_clut_16bit_granular(&DEFAULT_CONTEXT, &[2, 3, 4], 3, 3, Some(&table)).unwrap()
+    }
+
+    pub(crate) fn matrix() -> Stage {
+        let matrix = [0.5, 0.25, 0.25, 0.0, 1.0, 0.0, 0.125, 0.125, 0.75];
+        Stage::new_matrix(&DEFAULT_CONTEXT, 3, 3, &matrix, Some(&[0.0, 0.0, 0.125])).unwrap()
+    }
+
+    pub(crate) fn round_trip(tag: Signature, lut: &Pipeline) -> (Vec<u8>, Pipeline) {
+        let mut profile = Profile::new();
+        profile.write_tag(tag, lut).unwrap();
+        let mem = profile.save_to_mem().unwrap();
+
+        let mut profile = Profile::open_mem(&mem).unwrap();
+        let raw = profile.read_raw_tag(tag).unwrap();
+        (raw, profile.read_tag::<Pipeline>(tag).unwrap().dup().unwrap())
+    }
+
+    #[test]
+    fn lut_a_to_b_round_trip() {
+        let mut lut = Pipeline::new(&DEFAULT_CONTEXT, 3, 3).unwrap();
+        for stage in [curves(2.2), clut(), curves(1.8), matrix(), curves(1.0 / 2.2)] {
+            lut.insert_stage(StageLoc::AtEnd, stage).unwrap();
+        }
+
+        let (raw, read) = round_trip(sig::tags::A_TO_B0, &lut);
+        assert_eq!(&raw[..4], b"mAB ");
+        assert!(read.check_and_retrieve_stages(&[CURVE_SET, CLUT, CURVE_SET, MATRIX, CURVE_SET]).is_some());
+
+        for input in [[0.0, 0.0, 0.0], [0.2, 0.5, 0.8], [1.0, 0.7, 0.1]] {
+            let (mut expected, mut out) = ([0f32; 3], [0f32; 3]);
+            lut.eval_float(&input, &mut expected);
+            read.eval_float(&input, &mut out);
+
+            for (out, expected) in out.iter().zip(expected) {
+                assert!((out - expected).abs() < 1e-3);
+            }
+        }
+    }
+
+    #[test]
+    fn only_b_curves_are_allowed() {
+        let mut lut = Pipeline::new(&DEFAULT_CONTEXT, 3, 3).unwrap();
+        lut.insert_stage(StageLoc::AtEnd, curves(2.2)).unwrap();
+
+        let (_, read) = round_trip(sig::tags::A_TO_B0, &lut);
+        assert!(read.check_and_retrieve_stages(&[CURVE_SET]).is_some());
+
+        // A CLUT must come with curves on both sides
+        let mut lut = Pipeline::new(&DEFAULT_CONTEXT, 3, 3).unwrap();
+        lut.insert_stage(StageLoc::AtEnd, clut()).unwrap();
+
+        let mut profile = Profile::new();
+        profile.write_tag(sig::tags::A_TO_B0, &lut).unwrap();
+        assert!(profile.save_to_mem().is_err());
+    }
+}
@@ -0,0 +1,218 @@
+use std::{any::Any, mem::size_of};
+
+use log::Level;
+
+use crate::{
+    io::IoHandler,
+    plugin::{read_u16, read_u32, read_u8, write_u16, write_u32, write_u8},
+    sig::mpe_stage::{CLUT, CURVE_SET, MATRIX},
+    signal_error,
+    state::ErrorCode,
+    types::{tag::Base as TagBase, Pipeline, StageLoc},
+    Result, MAX_CHANNELS,
+};
+
+use super::{
+    read_clut, read_matrix, read_set_of_curves, write_clut, write_matrix, write_set_of_curves, TagTypeHandler,
+};
+
+// LutBToA type
+
+// The B curves are applied first, then the matrix, the M curves, the CLUT and the A curves:
+//
+// ("B" curves) -> (matrix) -> ("M" curves) -> (multidimensional lookup table - CLUT) -> ("A" curves).
+
+pub fn type_lut_b_to_a_read(
+    handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    n_items: &mut usize,
+    _size_of_tag: usize,
+) -> Result<Box<dyn Any>> {
+    *n_items = 0;
+
+    let base_offset = (io.tell)(io) - size_of::<TagBase>();
+
+    let input_chan = read_u8(io)? as u32;
+    let output_chan = read_u8(io)? as u32;
+
+    read_u16(io)?; // Padding
+
+    let offset_b = read_u32(io)? as usize;
+    let offset_mat = read_u32(io)? as usize;
+    let offset_m = read_u32(io)? as usize;
+    let offset_c = read_u32(io)? as usize;
+    let offset_a = read_u32(io)? as usize;
+
+    if input_chan == 0 || input_chan as usize >= MAX_CHANNELS {
+        return Err("Invalid number of input channels in type_lut_b_to_a_read".into());
+    }
+    if output_chan == 0 || output_chan as usize >= MAX_CHANNELS {
+        return Err("Invalid number of output channels in type_lut_b_to_a_read".into());
+    }
+
+    // Allocates an empty LUT
+    let mut new_lut = Pipeline::new(io.context_id, input_chan, output_chan)?;
+
+    if offset_b != 0 {
+        let b = read_set_of_curves(handler, io, base_offset + offset_b, input_chan)?;
+        new_lut.insert_stage(StageLoc::AtEnd, b)?;
+    }
+
+    if offset_mat != 0 {
+        let matrix = read_matrix(io, base_offset + offset_mat)?;
+        new_lut.insert_stage(StageLoc::AtEnd, matrix)?;
+    }
+
+    if offset_m != 0 {
+        let m = read_set_of_curves(handler, io, base_offset + offset_m, input_chan)?;
+        new_lut.insert_stage(StageLoc::AtEnd, m)?;
+    }
+
+    if offset_c != 0 {
+        let clut = read_clut(handler, io, base_offset + offset_c, input_chan, output_chan)?;
+        new_lut.insert_stage(StageLoc::AtEnd, clut)?;
+    }
+
+    if offset_a != 0 {
+        let a = read_set_of_curves(handler, io, base_offset + offset_a, output_chan)?;
+        new_lut.insert_stage(StageLoc::AtEnd, a)?;
+    }
+
+    *n_items = 1;
+    Ok(Box::new(new_lut))
+}
+
+pub fn type_lut_b_to_a_write(
+    handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    ptr: &dyn Any,
+    _n_items: usize,
+) -> Result<()> {
+    let Some(lut) = ptr.downcast_ref::<Pipeline>() else {
+        return Err("Invalid object to write with type_lut_b_to_a_write".into());
+    };
+
+    let base_offset = (io.tell)(io) - size_of::<TagBase>();
+
+    let (b, matrix, m, clut, a) = if lut.stages().is_empty() {
+        (None, None, None, None, None)
+    } else if let Some([b]) = lut.check_and_retrieve_stages(&[CURVE_SET]) {
+        (Some(b), None, None, None, None)
+    } else if let Some([b, matrix, m]) = lut.check_and_retrieve_stages(&[CURVE_SET, MATRIX, CURVE_SET]) {
+        (Some(b), Some(matrix), Some(m), None, None)
+    } else if let Some([b, clut, a]) = lut.check_and_retrieve_stages(&[CURVE_SET, CLUT, CURVE_SET]) {
+        (Some(b), None, None, Some(clut), Some(a))
+    } else if let Some([b, matrix, m, clut, a]) =
+        lut.check_and_retrieve_stages(&[CURVE_SET, MATRIX, CURVE_SET, CLUT, CURVE_SET])
+    {
+        (Some(b), Some(matrix), Some(m), Some(clut), Some(a))
+    } else {
+        let msg = "LUT is not suitable to be saved as LutBToA";
+        signal_error(&handler.context_id, Level::Error, ErrorCode::NotSuitable, msg);
+        return Err(msg.into());
+    };
+
+    // Get input, output channels
+    write_u8(io, lut.input_channels() as u8)?;
+    write_u8(io, lut.output_channels() as u8)?;
+    write_u16(io, 0)?;
+
+    // Keep directory to be filled latter
+    let directory_pos = (io.tell)(io);
+
+    // Write a zero directory
+    for _ in 0..5 {
+        write_u32(io, 0)?;
+    }
+
+    let (mut offset_b, mut offset_mat, mut offset_m, mut offset_c, mut offset_a) = (0, 0, 0, 0, 0);
+
+    if let Some(b) = b {
+        offset_b = (io.tell)(io) - base_offset;
+        write_set_of_curves(handler, io, b)?;
+    }
+
+    if let Some(matrix) = matrix {
+        offset_mat = (io.tell)(io) - base_offset;
+        write_matrix(handler, io, matrix)?;
+    }
+
+    if let Some(m) = m {
+        offset_m = (io.tell)(io) - base_offset;
+        write_set_of_curves(handler, io, m)?;
+    }
+
+    if let Some(clut) = clut {
+        offset_c = (io.tell)(io) - base_offset;
+        write_clut(handler, io, if lut.save_as_8_bits { 1 } else { 2 }, clut)?;
+    }
+
+    if let Some(a) = a {
+        offset_a = (io.tell)(io) - base_offset;
+        write_set_of_curves(handler, io, a)?;
+    }
+
+    let current_pos = (io.tell)(io);
+
+    if !(io.seek)(io, directory_pos) {
+        return Err("Seek error in type_lut_b_to_a_write".into());
+    }
+
+    for offset in [offset_b, offset_mat, offset_m, offset_c, offset_a] {
+        write_u32(io, offset as u32)?;
+    }
+
+    if !(io.seek)(io, current_pos) {
+        return Err("Seek error in type_lut_b_to_a_write".into());
+    }
+
+    Ok(())
+}
+
+type_dup_and_free!(lut_b_to_a, Pipeline, dup);
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        plugin::tag_type::lut_a_to_b::tests::{clut, curves, matrix, round_trip},
+        sig,
+        sig::mpe_stage::{CLUT, CURVE_SET, MATRIX},
+        types::{Pipeline, Profile, StageLoc},
+        DEFAULT_CONTEXT,
+    };
+
+    #[test]
+    fn lut_b_to_a_round_trip() {
+        let mut lut = Pipeline::new(&DEFAULT_CONTEXT, 3, 3).unwrap();
+        for stage in [curves(2.2), matrix(), curves(1.8), clut(), curves(1.0 / 2.2)] {
+            lut.insert_stage(StageLoc::AtEnd, stage).unwrap();
+        }
+
+        let (raw, read) = round_trip(sig::tags::B_TO_A0, &lut);
+        assert_eq!(&raw[..4], b"mBA ");
+        assert!(read.check_and_retrieve_stages(&[CURVE_SET, MATRIX, CURVE_SET, CLUT, CURVE_SET]).is_some());
+
+        for input in [[0.0, 0.0, 0.0], [0.2, 0.5, 0.8], [1.0, 0.7, 0.1]] {
+            let (mut expected, mut out) = ([0f32; 3], [0f32; 3]);
+            lut.eval_float(&input, &mut expected);
+            read.eval_float(&input, &mut out);
+
+            for (out, expected) in out.iter().zip(expected) {
+                assert!((out - expected).abs() < 1e-3);
+            }
+        }
+    }
+
+    #[test]
+    fn stages_out_of_order_are_rejected() {
+        // The A to B order is not valid on the way back
+        let mut lut = Pipeline::new(&DEFAULT_CONTEXT, 3, 3).unwrap();
+        for stage in [curves(2.2), clut(), curves(1.8), matrix(), curves(1.0 / 2.2)] {
+            lut.insert_stage(StageLoc::AtEnd, stage).unwrap();
+        }
+
+        let mut profile = Profile::new();
+        profile.write_tag(sig::tags::B_TO_A0, &lut).unwrap();
+        assert!(profile.save_to_mem().is_err());
+    }
+}
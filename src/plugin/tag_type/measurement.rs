@@ -0,0 +1,84 @@
+use std::any::Any;
+
+use crate::{
+    io::IoHandler,
+    plugin::{read_u16f16, read_u32, read_xyz, write_u16f16, write_u32, write_xyz},
+    types::MeasurementConditions,
+    Result,
+};
+
+use super::TagTypeHandler;
+
+pub fn type_measurement_read(
+    _handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    n_items: &mut usize,
+    _size_of_tag: usize,
+) -> Result<Box<dyn Any>> {
+    *n_items = 0;
+
+    let mc = MeasurementConditions {
+        observer: read_u32(io)?,
+        backing: read_xyz(io)?,
+        geometry: read_u32(io)?,
+        flare: read_u16f16(io)?,
+        illuminant_type: read_u32(io)?,
+    };
+
+    *n_items = 1;
+    Ok(Box::new(mc))
+}
+
+pub fn type_measurement_write(
+    _handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    ptr: &dyn Any,
+    _n_items: usize,
+) -> Result<()> {
+    let Some(mc) = ptr.downcast_ref::<MeasurementConditions>() else {
+        return Err("Invalid object to write with type_measurement_write".into());
+    };
+
+    write_u32(io, mc.observer)?;
+    write_xyz(io, mc.backing)?;
+    write_u32(io, mc.geometry)?;
+    write_u16f16(io, mc.flare)?;
+    write_u32(io, mc.illuminant_type)?;
+
+    Ok(())
+}
+
+type_dup_and_free!(measurement, MeasurementConditions);
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        sig,
+        types::{MeasurementConditions, Profile, XYZ},
+    };
+
+    #[test]
+    fn measurement_round_trip() {
+        let mc = MeasurementConditions {
+            observer: 1,
+            backing: XYZ { x: 0.25, y: 0.5, z: 0.125 },
+            geometry: 2,
+            flare: 0.5,
+            illuminant_type: 1,
+        };
+
+        let mut profile = Profile::new();
+        profile.write_tag(sig::tags::MEASUREMENT, &mc).unwrap();
+        let mem = profile.save_to_mem().unwrap();
+
+        let mut profile = Profile::open_mem(&mem).unwrap();
+        assert_eq!(&profile.read_raw_tag(sig::tags::MEASUREMENT).unwrap()[..4], b"meas");
+
+        let read = profile.read_tag::<MeasurementConditions>(sig::tags::MEASUREMENT).unwrap();
+        assert_eq!(read.observer, 1);
+        assert_eq!((read.backing.x, read.backing.y, read.backing.z), (0.25, 0.5, 0.125));
+        assert_eq!(read.geometry, 2);
+        assert_eq!(read.flare, 0.5);
+        assert_eq!(read.illuminant_type, 1);
+    }
+}
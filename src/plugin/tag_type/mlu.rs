@@ -0,0 +1,204 @@
+use std::{any::Any, mem::size_of};
+
+use log::Level;
+
+use crate::{
+    io::IoHandler,
+    plugin::{read_u16, read_u16_slice, read_u32, write_u16, write_u16_slice, write_u32},
+    signal_error,
+    state::ErrorCode,
+    types::{tag::Base as TagBase, MLUEntry, MLU},
+    Result,
+};
+
+use super::TagTypeHandler;
+
+// Multi-localized unicode type
+//
+// Each record is 12 bytes: language and country codes, then length and offset in bytes of a
+// UTF-16 string. The offset is counted from the beginning of the tag.
+
+pub fn type_mlu_read(
+    handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    n_items: &mut usize,
+    size_of_tag: usize,
+) -> Result<Box<dyn Any>> {
+    *n_items = 0;
+
+    let count = read_u32(io)? as usize;
+    let rec_len = read_u32(io)?;
+
+    if rec_len != 12 {
+        let msg = "multiLocalizedUnicodeType of len != 12 is not supported.";
+        signal_error(&handler.context_id, Level::Error, ErrorCode::UnknownExtension, msg);
+        return Err(msg.into());
+    }
+
+    // Count and record size are already read, the records must fit in the rest of the tag
+    if count > size_of_tag.saturating_sub(8) / 12 {
+        let msg = format!("Too many records in multiLocalizedUnicodeType '{}'", count);
+        signal_error(&handler.context_id, Level::Error, ErrorCode::CorruptionDetected, &msg);
+        return Err(msg);
+    }
+
+    let mut mlu = MLU::new(&handler.context_id, count);
+
+    let size_of_header = 12 * count + size_of::<TagBase>();
+    let mut largest_position = 0;
+
+    for _ in 0..count {
+        let language = read_u16(io)?;
+        let country = read_u16(io)?;
+
+        // Now deal with len and offset.
+        let len = read_u32(io)? as usize;
+        let offset = read_u32(io)? as usize;
+
+        // Offset MUST be even because it indexes a block of utf16 chars.
+        if offset & 1 != 0 {
+            return Err("Odd offset in type_mlu_read".into());
+        }
+
+        // Check for overflow
+        if offset < size_of_header + 8 || offset + len > size_of_tag + 8 {
+            return Err("Corrupted string offset in type_mlu_read".into());
+        }
+
+        // True begin of the string
+        let begin_of_this_string = offset - size_of_header - 8;
+
+        mlu.entries.push(MLUEntry {
+            language,
+            country,
+            str_w: begin_of_this_string,
+            len,
+        });
+
+        // To guess maximum size, add offset + len
+        let end_of_this_string = begin_of_this_string + len;
+        largest_position = largest_position.max(end_of_this_string);
+    }
+
+    // Now read the remaining of tag and fill all strings. Subtract the directory
+    if largest_position & 1 != 0 {
+        return Err("Odd string pool size in type_mlu_read".into());
+    }
+
+    mlu.mem_pool = vec![0u16; largest_position / size_of::<u16>()];
+    read_u16_slice(io, &mut mlu.mem_pool)?;
+
+    *n_items = 1;
+    Ok(Box::new(mlu))
+}
+
+pub fn type_mlu_write(
+    _handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    ptr: &dyn Any,
+    _n_items: usize,
+) -> Result<()> {
+    let Some(mlu) = ptr.downcast_ref::<MLU>() else {
+        return Err("Invalid object to write with type_mlu_write".into());
+    };
+
+    // Identical strings are stored only once
+    let mut pool = Vec::<u16>::with_capacity(mlu.mem_pool.len());
+    let mut offsets = Vec::<usize>::with_capacity(mlu.entries.len());
+
+    for (i, entry) in mlu.entries.iter().enumerate() {
+        let wide = mlu.entry_wide(entry);
+
+        let shared = mlu.entries[..i]
+            .iter()
+            .zip(&offsets)
+            .find(|(previous, _)| mlu.entry_wide(previous) == wide)
+            .map(|(_, offset)| *offset);
+
+        match shared {
+            Some(offset) => offsets.push(offset),
+            None => {
+                offsets.push(pool.len() * size_of::<u16>());
+                pool.extend_from_slice(wide);
+            }
+        }
+    }
+
+    write_u32(io, mlu.entries.len() as u32)?;
+    write_u32(io, 12)?;
+
+    let header_size = 12 * mlu.entries.len() + size_of::<TagBase>();
+
+    for (entry, offset) in mlu.entries.iter().zip(offsets) {
+        write_u16(io, entry.language)?;
+        write_u16(io, entry.country)?;
+        write_u32(io, entry.len as u32)?;
+        write_u32(io, (offset + header_size + 8) as u32)?;
+    }
+
+    write_u16_slice(io, &pool)
+}
+
+type_dup_and_free!(mlu, MLU);
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        sig,
+        types::{Profile, MLU},
+        DEFAULT_CONTEXT,
+    };
+
+    fn ascii(mlu: &MLU, lang: [u8; 2], cntr: [u8; 2]) -> String {
+        let mut buffer = [0u8; 64];
+        let len = mlu.get_ascii(lang, cntr, &mut buffer).unwrap();
+        String::from_utf8_lossy(&buffer[..len - 1]).into_owned()
+    }
+
+    #[test]
+    fn identical_strings_are_shared() {
+        let mut mlu = MLU::new(&DEFAULT_CONTEXT, 3);
+        mlu.set_ascii(*b"en", *b"US", b"Color").unwrap();
+        mlu.set_ascii(*b"es", *b"ES", b"Color").unwrap();
+        mlu.set_ascii(*b"en", *b"GB", b"Colour").unwrap();
+
+        let mut profile = Profile::new();
+        profile.write_tag(sig::tags::PROFILE_DESCRIPTION, &mlu).unwrap();
+        let mem = profile.save_to_mem().unwrap();
+
+        let mut profile = Profile::open_mem(&mem).unwrap();
+
+        // Tag base, count and record size come before the 12 byte records
+        let raw = profile.read_raw_tag(sig::tags::PROFILE_DESCRIPTION).unwrap();
+        assert_eq!(&raw[..4], b"mluc");
+        let offset = |i: usize| u32::from_be_bytes(raw[16 + 12 * i + 8..16 + 12 * i + 12].try_into().unwrap());
+        assert_eq!(offset(0), offset(1));
+        assert_ne!(offset(0), offset(2));
+
+        // Both strings are stored once, with no padding in between
+        assert_eq!(raw.len(), 16 + 3 * 12 + 2 * ("Color".len() + "Colour".len()));
+
+        let mlu = profile.read_tag::<MLU>(sig::tags::PROFILE_DESCRIPTION).unwrap();
+        assert_eq!(mlu.get_translations_count(), 3);
+        assert_eq!(ascii(mlu, *b"en", *b"US"), "Color");
+        assert_eq!(ascii(mlu, *b"es", *b"ES"), "Color");
+        assert_eq!(ascii(mlu, *b"en", *b"GB"), "Colour");
+    }
+
+    #[test]
+    fn huge_count_is_rejected() {
+        let mut mlu = MLU::new(&DEFAULT_CONTEXT, 1);
+        mlu.set_ascii(*b"en", *b"US", b"Color").unwrap();
+
+        let mut profile = Profile::new();
+        profile.write_tag(sig::tags::PROFILE_DESCRIPTION, &mlu).unwrap();
+        let mut mem = profile.save_to_mem().unwrap();
+
+        // The count follows the type signature and the reserved field
+        let pos = mem.windows(4).rposition(|w| w == b"mluc").unwrap();
+        mem[pos + 8..pos + 12].copy_from_slice(&0xFFFF_FFFFu32.to_be_bytes());
+
+        let mut profile = Profile::open_mem(&mem).unwrap();
+        assert!(profile.read_tag::<MLU>(sig::tags::PROFILE_DESCRIPTION).is_err());
+    }
+}
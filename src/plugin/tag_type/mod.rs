@@ -2,10 +2,11 @@ use once_cell::sync::Lazy;
 use paste::paste;
 use std::{any::Any, sync::Arc};
 
-use crate::{io::IoHandler, sig, types::Signature, Result, Context};
+use crate::{io::IoHandler, sig, types::Signature, Context, Result};
 
 use super::Base;
 
+#[derive(Clone)]
 pub struct TagTypeHandler {
     pub signature: Signature,
     pub read: fn(
@@ -32,7 +33,14 @@ pub struct TagType {
 }
 
 macro_rules! type_dup_and_free {
+    // Objects whose copy may fail, such as pipelines, provide a fallible `dup`
+    ($tag_type:ident, $type:ty, dup) => {
+        type_dup_and_free!(@impl $tag_type, $type, |value: &$type| value.dup());
+    };
     ($tag_type:ident, $type:ty) => {
+        type_dup_and_free!(@impl $tag_type, $type, |value: &$type| Ok::<_, String>(value.clone()));
+    };
+    (@impl $tag_type:ident, $type:ty, $dup:expr) => {
         paste::paste! {
             pub fn [<type_ $tag_type _dup>](
                 _handler: &TagTypeHandler,
@@ -40,8 +48,13 @@ macro_rules! type_dup_and_free {
                 _n_items: usize,
             ) -> Result<Box<dyn Any>> {
                 match ptr.downcast_ref::<$type>() {
-                    None => Err("Invalid object to duplicate with [<type_ $tag_type _dup>]".into()),
-                    Some(value) => Ok(Box::new(value.clone())),
+                    None => Err(concat!(
+                        "Invalid object to duplicate with type_",
+                        stringify!($tag_type),
+                        "_dup"
+                    )
+                    .into()),
+                    Some(value) => Ok(Box::new(($dup)(value)?)),
                 }
             }
 
@@ -54,24 +67,82 @@ macro_rules! type_dup_and_free {
 
 pub(crate) mod chromaticity;
 pub(crate) mod colorant_order_type;
+pub(crate) mod colorant_table;
+pub(crate) mod crd_info;
+pub(crate) mod curve;
 pub(crate) mod data;
+pub(crate) mod date_time;
+pub(crate) mod dictionary;
 mod functions;
+pub(crate) mod lut16;
+pub(crate) mod lut8;
+pub(crate) mod lut_a_to_b;
+pub(crate) mod lut_b_to_a;
+pub(crate) mod measurement;
+pub(crate) mod mlu;
+pub(crate) mod mpe;
+pub(crate) mod mpe_clut;
+pub(crate) mod mpe_curve;
+pub(crate) mod mpe_matrix;
+pub(crate) mod named_color;
+pub(crate) mod parametric_curve;
+pub(crate) mod profile_sequence_desc;
+pub(crate) mod profile_sequence_id;
+pub(crate) mod response_curve_set16;
 pub(crate) mod s15_fixed16;
+pub(crate) mod screening;
 pub(crate) mod signature;
 pub(crate) mod text;
 pub(crate) mod text_description;
 pub(crate) mod u16_fixed16;
+pub(crate) mod ucr_bg;
+pub(crate) mod uint16_array;
+pub(crate) mod uint32_array;
+pub(crate) mod uint64_array;
+pub(crate) mod uint8_array;
+pub(crate) mod vcgt;
+pub(crate) mod video_signal;
+pub(crate) mod viewing_conditions;
 pub(crate) mod xyz;
 
 use chromaticity::*;
 use colorant_order_type::*;
+use colorant_table::*;
+use crd_info::*;
+use curve::*;
 use data::*;
+use date_time::*;
+use dictionary::*;
 pub(crate) use functions::*;
+use lut16::*;
+use lut8::*;
+use lut_a_to_b::*;
+use lut_b_to_a::*;
+use measurement::*;
+use mlu::*;
+use mpe::*;
+use mpe_clut::*;
+use mpe_curve::*;
+use mpe_matrix::*;
+use named_color::*;
+use parametric_curve::*;
+use profile_sequence_desc::*;
+use profile_sequence_id::*;
+use response_curve_set16::*;
 use s15_fixed16::*;
+use screening::*;
 use signature::*;
 use text::*;
 use text_description::*;
 use u16_fixed16::*;
+use ucr_bg::*;
+use uint16_array::*;
+use uint32_array::*;
+use uint64_array::*;
+use uint8_array::*;
+use vcgt::*;
+use video_signal::*;
+use viewing_conditions::*;
 use xyz::*;
 
 macro_rules! TypeHandler {
@@ -83,14 +154,14 @@ macro_rules! TypeHandler {
                 write: [<type_ $x _write>],
                 dup: [<type_ $x _dup>],
                 free: [<type_ $x _free>],
-                context_id: crate::DEFAULT_CONTEXT,
+                context_id: crate::DEFAULT_CONTEXT.clone(),
                 icc_version: 0,
             }
         }
     };
 }
 macro_rules! TypeMpeHandler {
-    ($t:ty, $x:ident) => {
+    ($t:path, $x:ident) => {
         paste::paste! {
             TagTypeHandler {
                 signature: $t,
@@ -98,7 +169,7 @@ macro_rules! TypeMpeHandler {
                 write: [<type_ $x _write>],
                 dup: generic_mpe_dup,
                 free: generic_mpe_free,
-                context_id: crate::DEFAULT_CONTEXT,
+                context_id: crate::DEFAULT_CONTEXT.clone(),
                 icc_version: 0,
             }
         }
@@ -108,13 +179,71 @@ macro_rules! TypeMpeHandler {
 pub static SUPPORTED_TAG_TYPES: Lazy<Vec<TagTypeHandler>> = Lazy::new(|| {
     vec![
         TypeHandler!(sig::types::XYZ, xyz),
+        TypeHandler!(sig::types::CURVE, curve),
+        TypeHandler!(sig::types::PARAMETRIC_CURVE, parametric_curve),
+        TypeHandler!(sig::types::LUT16, lut16),
+        TypeHandler!(sig::types::LUT8, lut8),
+        TypeHandler!(sig::types::LUT_A_TO_B, lut_a_to_b),
+        TypeHandler!(sig::types::LUT_B_TO_A, lut_b_to_a),
         TypeHandler!(sig::types::CHROMATICITY, chromaticity),
         TypeHandler!(sig::types::COLORANT_ORDER, colorant_order_type),
+        TypeHandler!(sig::types::COLORANT_TABLE, colorant_table),
         TypeHandler!(sig::types::S15_FIXED16_ARRAY, s15_fixed16),
         TypeHandler!(sig::types::U16_FIXED16_ARRAY, u16_fixed16),
         TypeHandler!(sig::types::SIGNATURE, signature),
         TypeHandler!(sig::types::TEXT, text),
+        TypeHandler!(sig::types::MULTI_LOCALIZED_UNICODE, mlu),
+        TypeHandler!(sig::types::MULTI_PROCESS_ELEMENT, mpe),
+        TypeHandler!(sig::types::NAMED_COLOR2, named_color),
+        TypeHandler!(sig::types::DICT, dictionary),
+        TypeHandler!(sig::types::PROFILE_SEQUENCE_DESC, profile_sequence_desc),
+        TypeHandler!(sig::types::PROFILE_SEQUENCE_ID, profile_sequence_id),
+        TypeHandler!(sig::types::MEASUREMENT, measurement),
+        TypeHandler!(sig::types::VIEWING_CONDITIONS, viewing_conditions),
+        TypeHandler!(sig::types::SCREENING, screening),
+        TypeHandler!(sig::types::UCR_BG, ucr_bg),
+        TypeHandler!(sig::types::CRD_INFO, crd_info),
+        TypeHandler!(sig::types::VCGT, vcgt),
+        TypeHandler!(sig::types::CICP, video_signal),
+        TypeHandler!(sig::types::DATE_TIME, date_time),
+        TypeHandler!(sig::types::UINT8_ARRAY, uint8_array),
+        TypeHandler!(sig::types::UINT16_ARRAY, uint16_array),
+        TypeHandler!(sig::types::UINT32_ARRAY, uint32_array),
+        TypeHandler!(sig::types::UINT64_ARRAY, uint64_array),
+        TypeHandler!(sig::types::RESPONSE_CURVE_SET16, response_curve_set16),
         TypeHandler!(sig::types::DATA, data),
         TypeHandler!(sig::types::TEXT_DESCRIPTION, text_description),
     ]
 });
+
+pub static SUPPORTED_MPE_TYPES: Lazy<Vec<TagTypeHandler>> = Lazy::new(|| {
+    vec![
+        TypeMpeHandler!(sig::mpe_stage::CURVE_SET, mpe_curve),
+        TypeMpeHandler!(sig::mpe_stage::MATRIX, mpe_matrix),
+        TypeMpeHandler!(sig::mpe_stage::CLUT, mpe_clut),
+    ]
+});
+
+/// Returns the handler for a tag type. Plug-in types take precedence over the built-in ones.
+pub fn get_tag_type_handler(
+    context_id: &'static Context,
+    sig: Signature,
+) -> Option<&'static TagTypeHandler> {
+    context_id
+        .tag_types
+        .iter()
+        .find(|handler| handler.signature == sig)
+        .or_else(|| SUPPORTED_TAG_TYPES.iter().find(|handler| handler.signature == sig))
+}
+
+/// Returns the handler for a multi process element. Plug-in elements take precedence over the built-in ones.
+pub fn get_mpe_type_handler(
+    context_id: &'static Context,
+    sig: Signature,
+) -> Option<&'static TagTypeHandler> {
+    context_id
+        .mpe_types
+        .iter()
+        .find(|handler| handler.signature == sig)
+        .or_else(|| SUPPORTED_MPE_TYPES.iter().find(|handler| handler.signature == sig))
+}
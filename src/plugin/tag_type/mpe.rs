@@ -0,0 +1,164 @@
+use std::{any::Any, cell::RefCell, mem::size_of};
+
+use log::Level;
+
+use crate::{
+    io::IoHandler,
+    plugin::{read_signature, read_u16, read_u32, write_alignment, write_type_base, write_u16, write_u32},
+    sig, signal_error,
+    state::ErrorCode,
+    types::{tag::Base as TagBase, Pipeline, Stage, StageLoc},
+    Result, MAX_CHANNELS,
+};
+
+use super::{get_mpe_type_handler, read_position_table, write_position_table, TagTypeHandler};
+
+// This is the main dispatcher for MPE
+fn read_mpe_elem(
+    handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    cargo: &dyn Any,
+    _n: usize,
+    size_of_tag: usize,
+) -> Result<()> {
+    let Some(new_lut) = cargo.downcast_ref::<RefCell<Pipeline>>() else {
+        return Err("Invalid cargo in read_mpe_elem".into());
+    };
+
+    // Take signature and channels for each element.
+    let element_sig = read_signature(io)?;
+
+    // The reserved placeholder
+    read_u32(io)?;
+
+    // Reserved for future use, there is nothing to read
+    if element_sig == sig::mpe_stage::BACS || element_sig == sig::mpe_stage::EACS {
+        return Ok(());
+    }
+
+    // Read diverse MPE types
+    let Some(type_handler) = get_mpe_type_handler(io.context_id, element_sig) else {
+        // An unknown element was found.
+        let msg = format!("Unknown MPE type '{:x}' found.", element_sig.0);
+        signal_error(&handler.context_id, Level::Error, ErrorCode::UnknownExtension, &msg);
+        return Err(msg);
+    };
+
+    let mut type_handler = type_handler.clone();
+    type_handler.context_id = handler.context_id.clone();
+    type_handler.icc_version = handler.icc_version;
+
+    let mut n_items = 0;
+    let mpe = (type_handler.read)(&type_handler, io, &mut n_items, size_of_tag)?;
+
+    let Ok(mpe) = mpe.downcast::<Stage>() else {
+        return Err("Invalid element in read_mpe_elem".into());
+    };
+
+    new_lut.borrow_mut().insert_stage(StageLoc::AtEnd, *mpe)
+}
+
+// This is the main dispatcher for MPE
+pub fn type_mpe_read(
+    handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    n_items: &mut usize,
+    _size_of_tag: usize,
+) -> Result<Box<dyn Any>> {
+    *n_items = 0;
+
+    let base_offset = (io.tell)(io) - size_of::<TagBase>();
+
+    // Read channels and element count
+    let input_chans = read_u16(io)? as u32;
+    let output_chans = read_u16(io)? as u32;
+
+    if input_chans == 0 || input_chans as usize >= MAX_CHANNELS {
+        return Err("Invalid number of input channels in type_mpe_read".into());
+    }
+    if output_chans == 0 || output_chans as usize >= MAX_CHANNELS {
+        return Err("Invalid number of output channels in type_mpe_read".into());
+    }
+
+    // Allocates an empty LUT
+    let new_lut = RefCell::new(Pipeline::new(io.context_id, input_chans, output_chans)?);
+
+    let element_count = read_u32(io)? as usize;
+    read_position_table(handler, io, element_count, base_offset, &new_lut, read_mpe_elem)?;
+
+    let new_lut = new_lut.into_inner();
+
+    // Check channel count
+    if input_chans != new_lut.input_channels() || output_chans != new_lut.output_channels() {
+        return Err("Inconsistent number of channels in type_mpe_read".into());
+    }
+
+    // Success
+    *n_items = 1;
+    Ok(Box::new(new_lut))
+}
+
+fn write_mpe_elem(
+    handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    cargo: &dyn Any,
+    n: usize,
+    _size_of_tag: usize,
+) -> Result<()> {
+    let Some(lut) = cargo.downcast_ref::<Pipeline>() else {
+        return Err("Invalid cargo in write_mpe_elem".into());
+    };
+
+    let mpe = &lut.stages()[n];
+    let element_sig = mpe.r#type();
+
+    let Some(type_handler) = get_mpe_type_handler(io.context_id, element_sig) else {
+        let msg = format!("Found unknown MPE type '{:x}'", element_sig.0);
+        signal_error(&handler.context_id, Level::Error, ErrorCode::UnknownExtension, &msg);
+        return Err(msg);
+    };
+
+    let mut type_handler = type_handler.clone();
+    type_handler.context_id = handler.context_id.clone();
+    type_handler.icc_version = handler.icc_version;
+
+    write_type_base(io, element_sig)?;
+    (type_handler.write)(&type_handler, io, mpe, 1)?;
+
+    write_alignment(io)
+}
+
+pub fn type_mpe_write(
+    handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    ptr: &dyn Any,
+    _n_items: usize,
+) -> Result<()> {
+    let Some(lut) = ptr.downcast_ref::<Pipeline>() else {
+        return Err("Invalid object to write with type_mpe_write".into());
+    };
+
+    let base_offset = (io.tell)(io) - size_of::<TagBase>();
+
+    let elem_count = lut.stages().len();
+
+    // Write the head
+    write_u16(io, lut.input_channels() as u16)?;
+    write_u16(io, lut.output_channels() as u16)?;
+    write_u32(io, elem_count as u32)?;
+
+    write_position_table(handler, io, 0, elem_count, base_offset, lut, write_mpe_elem)
+}
+
+type_dup_and_free!(mpe, Pipeline, dup);
+
+pub fn generic_mpe_dup(_handler: &TagTypeHandler, ptr: &dyn Any, _n_items: usize) -> Result<Box<dyn Any>> {
+    match ptr.downcast_ref::<Stage>() {
+        None => Err("Invalid object to duplicate with generic_mpe_dup".into()),
+        Some(mpe) => Ok(Box::new(mpe.dup()?)),
+    }
+}
+
+pub fn generic_mpe_free(_handler: &TagTypeHandler, ptr: Box<dyn Any>) {
+    drop(ptr);
+}
@@ -0,0 +1,112 @@
+use std::any::Any;
+
+use log::Level;
+
+use crate::{
+    io::IoHandler,
+    plugin::{read_f32, read_u16, write_f32, write_u16},
+    signal_error,
+    state::ErrorCode,
+    types::{cube_size, CLutStageData, Stage},
+    Result, MAX_INPUT_DIMENSIONS,
+};
+
+use super::TagTypeHandler;
+
+pub fn type_mpe_clut_read(
+    _handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    n_items: &mut usize,
+    _size_of_tag: usize,
+) -> Result<Box<dyn Any>> {
+    *n_items = 0;
+
+    let input_chans = read_u16(io)? as u32;
+    let output_chans = read_u16(io)? as u32;
+
+    if input_chans == 0 || output_chans == 0 {
+        return Err("Invalid number of channels in type_mpe_clut_read".into());
+    }
+
+    let mut dimensions_8 = [0u8; 16];
+    if (io.read)(io, &mut dimensions_8, 1, 16) != 16 {
+        return Err("Read error in type_mpe_clut_read".into());
+    }
+
+    // Copy MAX_INPUT_DIMENSIONS at most. Expand to u32
+    let n_max_grids = (input_chans as usize).min(MAX_INPUT_DIMENSIONS);
+    let mut grid_points = [0u32; MAX_INPUT_DIMENSIONS];
+    for (points, points_8) in grid_points.iter_mut().zip(&dimensions_8[..n_max_grids]) {
+        // Impossible value, 0 for no CLUT and then 2 at least
+        if *points_8 == 1 {
+            return Err("Invalid number of grid points in type_mpe_clut_read".into());
+        }
+        *points = *points_8 as u32;
+    }
+
+    let Some(n_entries) =
+        cube_size(&grid_points[..n_max_grids]).and_then(|n| n.checked_mul(output_chans as usize))
+    else {
+        return Err("CLUT too large in type_mpe_clut_read".into());
+    };
+
+    // Read and sanitize the data
+    let mut table = vec![0f32; n_entries];
+    for value in table.iter_mut() {
+        *value = read_f32(io)?;
+    }
+
+    // Allocate the true CLUT
+    let mpe = Stage::new_clut_float_granular(
+        io.context_id,
+        &grid_points[..n_max_grids],
+        input_chans,
+        output_chans,
+        Some(&table),
+    )?;
+
+    *n_items = 1;
+    Ok(Box::new(mpe))
+}
+
+// Write a CLUT in floating point
+pub fn type_mpe_clut_write(
+    handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    ptr: &dyn Any,
+    _n_items: usize,
+) -> Result<()> {
+    let Some(mpe) = ptr.downcast_ref::<Stage>() else {
+        return Err("Invalid object to write with type_mpe_clut_write".into());
+    };
+
+    // Check for maximum number of channels supported by lcms
+    if mpe.input_channels() as usize > MAX_INPUT_DIMENSIONS {
+        return Err("Too many input channels in type_mpe_clut_write".into());
+    }
+
+    // Only floats are supported in MPE
+    let Some(clut) = mpe.data::<CLutStageData<f32>>() else {
+        let msg = "Only floating point CLUT can be saved in multi process elements";
+        signal_error(&handler.context_id, Level::Error, ErrorCode::NotSuitable, msg);
+        return Err(msg.into());
+    };
+
+    write_u16(io, mpe.input_channels() as u16)?;
+    write_u16(io, mpe.output_channels() as u16)?;
+
+    let mut dimensions_8 = [0u8; 16];
+    for (points, n_samples) in dimensions_8.iter_mut().zip(&clut.params.n_samples[..mpe.input_channels() as usize]) {
+        *points = *n_samples as u8;
+    }
+
+    if !(io.write)(io, 16, &dimensions_8) {
+        return Err("Write error in type_mpe_clut_write".into());
+    }
+
+    for value in clut.table() {
+        write_f32(io, *value)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,320 @@
+use std::{any::Any, cell::RefCell, mem::size_of};
+
+use log::Level;
+
+use crate::{
+    io::IoHandler,
+    plugin::{read_f32, read_signature, read_u16, read_u32, write_f32, write_signature, write_u16, write_u32},
+    sig, signal_error,
+    state::ErrorCode,
+    types::{
+        tag::Base as TagBase, CurveSegment, Stage, ToneCurve, ToneCurvesStageData, MINUS_INF, PLUS_INF,
+    },
+    Result, MAX_CHANNELS,
+};
+
+use super::{read_position_table, write_position_table, TagTypeHandler};
+
+// Number of parameters of each formula segment type
+const PARAMS_BY_TYPE: [usize; 3] = [4, 5, 5];
+
+// Read an embedded segmented curve, which takes at most size_of_element bytes
+fn read_segmented_curve(handler: &TagTypeHandler, io: &mut IoHandler, size_of_element: usize) -> Result<ToneCurve> {
+    // Neither the element nor the file can be exceeded
+    let end_of_element = ((io.tell)(io) + size_of_element).min(io.reported_size);
+
+    let element_sig = read_signature(io)?;
+    if element_sig != sig::curve_segment::SEGMENTED {
+        return Err("Invalid segmented curve in read_segmented_curve".into());
+    }
+    read_u32(io)?; // Reserved
+
+    let count = read_u16(io)? as usize;
+    read_u16(io)?; // Reserved
+
+    if count < 1 {
+        return Err("Segmented curve without segments in read_segmented_curve".into());
+    }
+
+    let mut segments = vec![
+        CurveSegment {
+            x0: 0.0,
+            x1: 0.0,
+            r#type: 0,
+            params: [0.0; 10],
+            sampled_points: Vec::new(),
+        };
+        count
+    ];
+
+    // Read breakpoints
+    let mut prev_break = MINUS_INF; // - infinite
+    for segment in segments[..count - 1].iter_mut() {
+        segment.x0 = prev_break;
+        segment.x1 = read_f32(io)? as f64;
+        prev_break = segment.x1;
+    }
+
+    segments[count - 1].x0 = prev_break;
+    segments[count - 1].x1 = PLUS_INF; // A big f32 number
+
+    // Read segments
+    for segment in segments.iter_mut() {
+        let element_sig = read_signature(io)?;
+        read_u32(io)?; // Reserved
+
+        match element_sig {
+            sig::curve_segment::FORMULA => {
+                let r#type = read_u16(io)? as usize;
+                read_u16(io)?; // Reserved
+
+                if r#type > 2 {
+                    return Err("Unknown formula segment type in read_segmented_curve".into());
+                }
+
+                segment.r#type = r#type as i32 + 6;
+                for param in segment.params[..PARAMS_BY_TYPE[r#type]].iter_mut() {
+                    *param = read_f32(io)? as f64;
+                }
+            }
+            sig::curve_segment::SAMPLED => {
+                let count = read_u32(io)? as usize;
+
+                let remaining = end_of_element.saturating_sub((io.tell)(io));
+                if count > remaining / size_of::<f32>() {
+                    return Err("Too many sampled points in read_segmented_curve".into());
+                }
+
+                // The first point is implicit in the last stage, we allocate an extra note to be populated latter on
+                let mut sampled_points = vec![0f32; count + 1];
+                for point in sampled_points[1..].iter_mut() {
+                    *point = read_f32(io)?;
+                }
+
+                segment.sampled_points = sampled_points;
+            }
+            _ => {
+                let msg = format!("Unknown curve element type '{:x}' found.", element_sig.0);
+                signal_error(&handler.context_id, Level::Error, ErrorCode::UnknownExtension, &msg);
+                return Err(msg);
+            }
+        }
+    }
+
+    // Explore for missing implicit points. The first point of a sampled segment is the value of
+    // the curve at the start of that segment.
+    for i in 0..count {
+        if segments[i].r#type == 0 {
+            let curve = ToneCurve::build_segmented(&handler.context_id, &segments)?;
+            segments[i].sampled_points[0] = curve.eval_f32(segments[i].x0 as f32);
+        }
+    }
+
+    ToneCurve::build_segmented(&handler.context_id, &segments)
+}
+
+fn read_mpe_curve(
+    handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    cargo: &dyn Any,
+    _n: usize,
+    size_of_tag: usize,
+) -> Result<()> {
+    let Some(gamma_tables) = cargo.downcast_ref::<RefCell<Vec<ToneCurve>>>() else {
+        return Err("Invalid cargo in read_mpe_curve".into());
+    };
+
+    let curve = read_segmented_curve(handler, io, size_of_tag)?;
+    gamma_tables.borrow_mut().push(curve);
+
+    Ok(())
+}
+
+pub fn type_mpe_curve_read(
+    handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    n_items: &mut usize,
+    _size_of_tag: usize,
+) -> Result<Box<dyn Any>> {
+    *n_items = 0;
+
+    let base_offset = (io.tell)(io) - size_of::<TagBase>();
+
+    let input_chans = read_u16(io)? as u32;
+    let output_chans = read_u16(io)? as u32;
+
+    if input_chans != output_chans {
+        return Err("Curve set with different input and output channels in type_mpe_curve_read".into());
+    }
+    if input_chans as usize >= MAX_CHANNELS {
+        return Err("Too many curves in type_mpe_curve_read".into());
+    }
+
+    let gamma_tables = RefCell::new(Vec::<ToneCurve>::with_capacity(input_chans as usize));
+    read_position_table(handler, io, input_chans as usize, base_offset, &gamma_tables, read_mpe_curve)?;
+
+    let mpe = Stage::new_tone_curves(io.context_id, input_chans, Some(&gamma_tables.into_inner()))?;
+
+    *n_items = 1;
+    Ok(Box::new(mpe))
+}
+
+// Write a single segmented curve. NO CHECK IS PERFORMED ON VALIDITY
+fn write_segmented_curve(handler: &TagTypeHandler, io: &mut IoHandler, curve: &ToneCurve) -> Result<()> {
+    let segments = curve.segments();
+
+    if segments.is_empty() {
+        let msg = "Tabulated curves cannot be saved as segmented curves";
+        signal_error(&handler.context_id, Level::Error, ErrorCode::NotSuitable, msg);
+        return Err(msg.into());
+    }
+
+    write_signature(io, sig::curve_segment::SEGMENTED)?;
+    write_u32(io, 0)?;
+    write_u16(io, segments.len() as u16)?;
+    write_u16(io, 0)?;
+
+    // Write the break-points
+    for segment in &segments[..segments.len() - 1] {
+        write_f32(io, segment.x1 as f32)?;
+    }
+
+    // Write the segments
+    for segment in segments {
+        if segment.r#type == 0 {
+            // This is a sampled curve. First point is implicit in the ICC format, but not in our representation
+            let Some((_, points)) = segment.sampled_points.split_first() else {
+                let msg = "Sampled segment without points cannot be saved";
+                signal_error(&handler.context_id, Level::Error, ErrorCode::NotSuitable, msg);
+                return Err(msg.into());
+            };
+
+            write_signature(io, sig::curve_segment::SAMPLED)?;
+            write_u32(io, 0)?;
+            write_u32(io, points.len() as u32)?;
+
+            for point in points {
+                write_f32(io, *point)?;
+            }
+        } else {
+            // This is a formula-based
+            write_signature(io, sig::curve_segment::FORMULA)?;
+            write_u32(io, 0)?;
+
+            // We only allow 1, 2 and 3 as types
+            let r#type = segment.r#type - 6;
+            if !(0..=2).contains(&r#type) {
+                let msg = format!("Segment of type {} cannot be saved as formula", segment.r#type);
+                signal_error(&handler.context_id, Level::Error, ErrorCode::NotSuitable, &msg);
+                return Err(msg);
+            }
+
+            write_u16(io, r#type as u16)?;
+            write_u16(io, 0)?;
+
+            for param in &segment.params[..PARAMS_BY_TYPE[r#type as usize]] {
+                write_f32(io, *param as f32)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn write_mpe_curve(
+    handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    cargo: &dyn Any,
+    n: usize,
+    _size_of_tag: usize,
+) -> Result<()> {
+    let Some(curves) = cargo.downcast_ref::<ToneCurvesStageData>() else {
+        return Err("Invalid cargo in write_mpe_curve".into());
+    };
+
+    write_segmented_curve(handler, io, &curves.the_curves[n])
+}
+
+pub fn type_mpe_curve_write(
+    handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    ptr: &dyn Any,
+    _n_items: usize,
+) -> Result<()> {
+    let Some(mpe) = ptr.downcast_ref::<Stage>() else {
+        return Err("Invalid object to write with type_mpe_curve_write".into());
+    };
+    let Some(curves) = mpe.data::<ToneCurvesStageData>() else {
+        return Err("Invalid stage in type_mpe_curve_write".into());
+    };
+
+    let base_offset = (io.tell)(io) - size_of::<TagBase>();
+
+    write_u16(io, mpe.input_channels() as u16)?;
+    write_u16(io, mpe.input_channels() as u16)?;
+
+    write_position_table(handler, io, 0, mpe.input_channels() as usize, base_offset, curves, write_mpe_curve)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        sig,
+        types::{CurveSegment, Pipeline, Profile, Stage, StageLoc, ToneCurve, MINUS_INF, PLUS_INF},
+        DEFAULT_CONTEXT,
+    };
+
+    fn segment(x0: f64, x1: f64, r#type: i32, sampled_points: Vec<f32>) -> CurveSegment {
+        let mut params = [0.0; 10];
+        params[..2].copy_from_slice(&[1.0, 1.0]);
+
+        CurveSegment {
+            x0,
+            x1,
+            r#type,
+            params,
+            sampled_points,
+        }
+    }
+
+    // A profile holding an identity curve whose middle segment is sampled
+    fn sampled_curve_profile() -> Vec<u8> {
+        let segments = [
+            segment(MINUS_INF, 0.0, 6, Vec::new()),
+            segment(0.0, 1.0, 0, vec![0.0, 0.25, 0.5, 0.75, 1.0]),
+            segment(1.0, PLUS_INF, 6, Vec::new()),
+        ];
+        let curve = ToneCurve::build_segmented(&DEFAULT_CONTEXT, &segments).unwrap();
+
+        let mut lut = Pipeline::new(&DEFAULT_CONTEXT, 1, 1).unwrap();
+        lut.insert_stage(StageLoc::AtEnd, Stage::new_tone_curves(&DEFAULT_CONTEXT, 1, Some(&[curve])).unwrap())
+            .unwrap();
+
+        let mut profile = Profile::new();
+        profile.write_tag(sig::tags::D_TO_B0, &lut).unwrap();
+        profile.save_to_mem().unwrap()
+    }
+
+    #[test]
+    fn sampled_segment_round_trip() {
+        let mem = sampled_curve_profile();
+        let mut profile = Profile::open_mem(&mem).unwrap();
+        let lut = profile.read_tag::<Pipeline>(sig::tags::D_TO_B0).unwrap();
+
+        let mut out = [0f32];
+        lut.eval_float(&[0.5], &mut out);
+        assert!((out[0] - 0.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn huge_sampled_count_is_rejected() {
+        let mut mem = sampled_curve_profile();
+
+        // The point count follows the 'samf' signature and its reserved field
+        let pos = mem.windows(4).position(|w| w == b"samf").unwrap();
+        mem[pos + 8..pos + 12].copy_from_slice(&0xFFFF_FFF0u32.to_be_bytes());
+
+        let mut profile = Profile::open_mem(&mem).unwrap();
+        assert!(profile.read_tag::<Pipeline>(sig::tags::D_TO_B0).is_err());
+    }
+}
@@ -0,0 +1,78 @@
+use std::any::Any;
+
+use crate::{
+    io::IoHandler,
+    plugin::{read_f32, read_u16, write_f32, write_u16},
+    types::{MatrixStageData, Stage},
+    Result, MAX_CHANNELS,
+};
+
+use super::TagTypeHandler;
+
+// The matrix is organized as an array of PxQ+Q elements, where P is the number of input channels to the
+// matrix, and Q is the number of output channels.
+pub fn type_mpe_matrix_read(
+    _handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    n_items: &mut usize,
+    _size_of_tag: usize,
+) -> Result<Box<dyn Any>> {
+    *n_items = 0;
+
+    let input_chans = read_u16(io)? as u32;
+    let output_chans = read_u16(io)? as u32;
+
+    // Input and output chans may be ANY (up to 0xffff),
+    // but we choose to limit to 16 channels for now
+    if input_chans as usize >= MAX_CHANNELS || output_chans as usize >= MAX_CHANNELS {
+        return Err("Too many channels in type_mpe_matrix_read".into());
+    }
+
+    let n_elems = input_chans as usize * output_chans as usize;
+
+    let mut matrix = vec![0f64; n_elems];
+    for value in matrix.iter_mut() {
+        *value = read_f32(io)? as f64;
+    }
+
+    let mut offsets = vec![0f64; output_chans as usize];
+    for value in offsets.iter_mut() {
+        *value = read_f32(io)? as f64;
+    }
+
+    let mpe = Stage::new_matrix(io.context_id, output_chans, input_chans, &matrix, Some(&offsets))?;
+
+    *n_items = 1;
+    Ok(Box::new(mpe))
+}
+
+pub fn type_mpe_matrix_write(
+    _handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    ptr: &dyn Any,
+    _n_items: usize,
+) -> Result<()> {
+    let Some(mpe) = ptr.downcast_ref::<Stage>() else {
+        return Err("Invalid object to write with type_mpe_matrix_write".into());
+    };
+    let Some(matrix) = mpe.data::<MatrixStageData>() else {
+        return Err("Invalid stage in type_mpe_matrix_write".into());
+    };
+
+    write_u16(io, mpe.input_channels() as u16)?;
+    write_u16(io, mpe.output_channels() as u16)?;
+
+    let n_elems = mpe.input_channels() as usize * mpe.output_channels() as usize;
+    for value in &matrix.double[..n_elems] {
+        write_f32(io, *value as f32)?;
+    }
+
+    for i in 0..mpe.output_channels() as usize {
+        match &matrix.offset {
+            Some(offset) => write_f32(io, offset[i] as f32)?,
+            None => write_f32(io, 0.0)?,
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,132 @@
+use std::any::Any;
+
+use log::Level;
+
+use crate::{
+    io::IoHandler,
+    plugin::{read_u16_slice, read_u32, write_u16_slice, write_u32},
+    signal_error,
+    state::ErrorCode,
+    types::NamedColor,
+    Result, MAX_CHANNELS,
+};
+
+use super::{read_name, write_name, TagTypeHandler};
+
+pub fn type_named_color_read(
+    handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    n_items: &mut usize,
+    size_of_tag: usize,
+) -> Result<Box<dyn Any>> {
+    *n_items = 0;
+
+    read_u32(io)?; // Vendor flag
+    let count = read_u32(io)? as usize;
+    let n_device_coords = read_u32(io)? as usize;
+
+    let prefix = read_name(io)?;
+    let suffix = read_name(io)?;
+
+    if n_device_coords > MAX_CHANNELS {
+        let msg = format!("Too many device coordinates '{}'", n_device_coords);
+        signal_error(&handler.context_id, Level::Error, ErrorCode::Range, &msg);
+        return Err(msg);
+    }
+
+    // Each color takes a 32 byte name, 3 PCS coordinates and the device coordinates
+    if count > size_of_tag / (32 + 6 + 2 * n_device_coords) {
+        let msg = format!("Too many named colors '{}'", count);
+        signal_error(&handler.context_id, Level::Error, ErrorCode::CorruptionDetected, &msg);
+        return Err(msg);
+    }
+
+    let mut v = NamedColor::new(io.context_id, n_device_coords, &prefix, &suffix)?;
+
+    for _ in 0..count {
+        let root = read_name(io)?;
+
+        let mut pcs = [0u16; 3];
+        read_u16_slice(io, &mut pcs)?;
+
+        let mut colorant = [0u16; MAX_CHANNELS];
+        read_u16_slice(io, &mut colorant[..n_device_coords])?;
+
+        v.append(&root, pcs, Some(&colorant))?;
+    }
+
+    *n_items = 1;
+    Ok(Box::new(v))
+}
+
+// Saves a named color list into a named color profile
+pub fn type_named_color_write(
+    _handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    ptr: &dyn Any,
+    _n_items: usize,
+) -> Result<()> {
+    let Some(named_color_list) = ptr.downcast_ref::<NamedColor>() else {
+        return Err("Invalid object to write with type_named_color_write".into());
+    };
+
+    write_u32(io, 0)?; // Vendor flag
+    write_u32(io, named_color_list.count() as u32)?;
+    write_u32(io, named_color_list.colorant_count as u32)?;
+
+    write_name(io, &named_color_list.prefix)?;
+    write_name(io, &named_color_list.suffix)?;
+
+    for entry in named_color_list.iter() {
+        write_name(io, &entry.name)?;
+        write_u16_slice(io, &entry.pcs)?;
+        write_u16_slice(io, &entry.device_colorant[..named_color_list.colorant_count])?;
+    }
+
+    Ok(())
+}
+
+type_dup_and_free!(named_color, NamedColor);
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        sig,
+        types::{NamedColor, Profile},
+        DEFAULT_CONTEXT,
+    };
+
+    fn named_color_profile() -> Vec<u8> {
+        let mut list = NamedColor::new(&DEFAULT_CONTEXT, 2, "PANTONE ", " C").unwrap();
+        list.append("100", [0x8000, 0x7000, 0x9000], Some(&[0x1000, 0x2000])).unwrap();
+        list.append("200", [0x4000, 0x8080, 0x8080], Some(&[0xffff, 0])).unwrap();
+
+        let mut profile = Profile::new();
+        profile.write_tag(sig::tags::NAMED_COLOR2, &list).unwrap();
+        profile.save_to_mem().unwrap()
+    }
+
+    #[test]
+    fn named_color_round_trip() {
+        let mem = named_color_profile();
+        let mut profile = Profile::open_mem(&mem).unwrap();
+        let list = profile.read_tag::<NamedColor>(sig::tags::NAMED_COLOR2).unwrap();
+
+        assert_eq!(list.count(), 2);
+        assert_eq!(list.prefix, "PANTONE ");
+        assert_eq!(list.index("200"), Some(1));
+        assert_eq!(list.colorants(0), Some(&[0x1000, 0x2000][..]));
+    }
+
+    #[test]
+    fn huge_count_is_rejected() {
+        let mut mem = named_color_profile();
+
+        // The count follows the type signature, the reserved field and the vendor flag
+        let pos = mem.windows(4).rposition(|w| w == b"ncl2").unwrap();
+        mem[pos + 12..pos + 16].copy_from_slice(&0xFFFF_FFF0u32.to_be_bytes());
+
+        let mut profile = Profile::open_mem(&mem).unwrap();
+        assert!(profile.read_tag::<NamedColor>(sig::tags::NAMED_COLOR2).is_err());
+    }
+}
@@ -0,0 +1,125 @@
+use std::any::Any;
+
+use log::Level;
+
+use crate::{
+    io::IoHandler,
+    plugin::{read_s15f16, read_u16, write_s15f16, write_u16},
+    signal_error,
+    state::ErrorCode,
+    types::ToneCurve,
+    Result,
+};
+
+use super::TagTypeHandler;
+
+// Number of parameters of each ICC function type, 0 to 4
+const PARAMS_BY_TYPE: [usize; 5] = [1, 3, 4, 5, 7];
+
+pub fn type_parametric_curve_read(
+    handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    n_items: &mut usize,
+    _size_of_tag: usize,
+) -> Result<Box<dyn Any>> {
+    *n_items = 0;
+
+    let r#type = read_u16(io)?;
+    read_u16(io)?; // Reserved
+
+    if r#type > 4 {
+        let msg = format!("Unknown parametric curve type '{}'", r#type);
+        signal_error(&handler.context_id, Level::Error, ErrorCode::UnknownExtension, &msg);
+        return Err(msg);
+    }
+
+    let mut params = [0f64; 10];
+    for param in params.iter_mut().take(PARAMS_BY_TYPE[r#type as usize]) {
+        *param = read_s15f16(io)?;
+    }
+
+    let curve = ToneCurve::build_parametric(&handler.context_id, r#type as i32 + 1, &params)?;
+
+    *n_items = 1;
+    Ok(Box::new(curve))
+}
+
+pub fn type_parametric_curve_write(
+    handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    ptr: &dyn Any,
+    _n_items: usize,
+) -> Result<()> {
+    let Some(curve) = ptr.downcast_ref::<ToneCurve>() else {
+        return Err("Invalid object to write with type_parametric_curve_write".into());
+    };
+
+    let typen = curve.segments.first().map_or(0, |segment| segment.r#type);
+
+    if curve.segments.len() > 1 || typen < 1 {
+        let msg = "Multisegment or Inverted parametric curves cannot be written";
+        signal_error(&handler.context_id, Level::Error, ErrorCode::UnknownExtension, msg);
+        return Err(msg.into());
+    }
+
+    if typen > 5 {
+        let msg = "Unsupported parametric curve";
+        signal_error(&handler.context_id, Level::Error, ErrorCode::UnknownExtension, msg);
+        return Err(msg.into());
+    }
+
+    let segment = &curve.segments[0];
+
+    write_u16(io, (typen - 1) as u16)?;
+    write_u16(io, 0)?; // Reserved
+
+    for param in &segment.params[..PARAMS_BY_TYPE[typen as usize - 1]] {
+        write_s15f16(io, *param)?;
+    }
+
+    Ok(())
+}
+
+type_dup_and_free!(parametric_curve, ToneCurve);
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        sig,
+        types::{Profile, ToneCurve},
+        DEFAULT_CONTEXT,
+    };
+
+    fn round_trip(curve: &ToneCurve) -> (Vec<u8>, ToneCurve) {
+        let mut profile = Profile::new();
+        profile.write_tag(sig::tags::GRAY_TRC, curve).unwrap();
+        let mem = profile.save_to_mem().unwrap();
+
+        let mut profile = Profile::open_mem(&mem).unwrap();
+        let raw = profile.read_raw_tag(sig::tags::GRAY_TRC).unwrap();
+        (raw, profile.read_tag::<ToneCurve>(sig::tags::GRAY_TRC).unwrap().clone())
+    }
+
+    #[test]
+    fn parametric_curve_round_trip() {
+        let params = [2.4, 1.0 / 1.055, 0.055 / 1.055, 1.0 / 12.92, 0.04045];
+        let curve = ToneCurve::build_parametric(&DEFAULT_CONTEXT, 4, &params).unwrap();
+
+        let (raw, read) = round_trip(&curve);
+        assert_eq!(&raw[..4], b"para");
+        assert_eq!(read.parametric_type(), 4);
+        for (read, param) in read.params().unwrap().iter().zip(params) {
+            assert!((read - param).abs() < 1.0 / 65536.0);
+        }
+        assert!((read.eval_f32(0.5) - curve.eval_f32(0.5)).abs() < 1e-4);
+    }
+
+    #[test]
+    fn inverted_curve_is_saved_as_table() {
+        let curve = ToneCurve::build_parametric(&DEFAULT_CONTEXT, -1, &[2.2]).unwrap();
+
+        let (raw, read) = round_trip(&curve);
+        assert_eq!(&raw[..4], b"curv");
+        assert!((read.eval_f32(0.25) - curve.eval_f32(0.25)).abs() < 1e-3);
+    }
+}
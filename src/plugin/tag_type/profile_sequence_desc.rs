@@ -0,0 +1,136 @@
+use std::any::Any;
+
+use crate::{
+    io::IoHandler,
+    plugin::{read_signature, read_u32, read_u64, write_signature, write_u32, write_u64},
+    types::Seq,
+    Result,
+};
+
+use super::{read_embedded_text, save_description, TagTypeHandler};
+
+pub fn type_profile_sequence_desc_read(
+    handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    n_items: &mut usize,
+    size_of_tag: usize,
+) -> Result<Box<dyn Any>> {
+    *n_items = 0;
+
+    let start = (io.tell)(io);
+
+    let count = read_u32(io)? as usize;
+
+    // Each description takes at least 20 bytes
+    if count > size_of_tag / 20 {
+        return Err("Too many descriptions in type_profile_sequence_desc_read".into());
+    }
+
+    let mut out_seq = Seq::new(io.context_id, count)?;
+
+    // Get structures as well
+    for sec in out_seq.seq.iter_mut() {
+        sec.device_mfg = read_signature(io)?;
+        sec.device_model = read_signature(io)?;
+        sec.attributes = read_u64(io)?;
+        sec.technology = read_signature(io)?;
+
+        let remaining = size_of_tag.saturating_sub((io.tell)(io) - start);
+        *sec.manufacturer = read_embedded_text(handler, io, remaining)?;
+
+        let remaining = size_of_tag.saturating_sub((io.tell)(io) - start);
+        *sec.model = read_embedded_text(handler, io, remaining)?;
+    }
+
+    *n_items = 1;
+    Ok(Box::new(out_seq))
+}
+
+pub fn type_profile_sequence_desc_write(
+    handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    ptr: &dyn Any,
+    _n_items: usize,
+) -> Result<()> {
+    let Some(seq) = ptr.downcast_ref::<Seq>() else {
+        return Err("Invalid object to write with type_profile_sequence_desc_write".into());
+    };
+
+    write_u32(io, seq.len() as u32)?;
+
+    for sec in &seq.seq {
+        write_signature(io, sec.device_mfg)?;
+        write_signature(io, sec.device_model)?;
+        write_u64(io, sec.attributes)?;
+        write_signature(io, sec.technology)?;
+
+        save_description(handler, io, &sec.manufacturer)?;
+        save_description(handler, io, &sec.model)?;
+    }
+
+    Ok(())
+}
+
+type_dup_and_free!(profile_sequence_desc, Seq);
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use crate::{
+        sig,
+        types::{Profile, ProfileID, Seq, Signature, MLU},
+        DEFAULT_CONTEXT,
+    };
+
+    pub(crate) fn ascii(mlu: &MLU) -> String {
+        let mut buffer = [0u8; 64];
+        let len = mlu.get_ascii(*b"en", *b"US", &mut buffer).unwrap();
+        String::from_utf8_lossy(&buffer[..len - 1]).into_owned()
+    }
+
+    pub(crate) fn sequence() -> Seq {
+        let mut seq = Seq::new(&DEFAULT_CONTEXT, 2).unwrap();
+
+        for (i, desc) in seq.seq.iter_mut().enumerate() {
+            desc.device_mfg = sig::technology::FILM_SCANNER;
+            desc.device_model = sig::technology::DIGITAL_CAMERA;
+            desc.attributes = 1 << i;
+            desc.technology = sig::technology::REFLECTIVE_SCANNER;
+            desc.profile_id = ProfileID { id8: [i as u8 + 1; 16] };
+            desc.manufacturer.set_ascii(*b"en", *b"US", format!("Maker {}", i).as_bytes()).unwrap();
+            desc.model.set_ascii(*b"en", *b"US", format!("Model {}", i).as_bytes()).unwrap();
+            desc.description.set_ascii(*b"en", *b"US", format!("Profile {}", i).as_bytes()).unwrap();
+        }
+
+        seq
+    }
+
+    pub(crate) fn round_trip(version: f64, tag: Signature) -> (Vec<u8>, Seq) {
+        let mut profile = Profile::new();
+        profile.set_version(version).unwrap();
+        profile.write_tag(tag, &sequence()).unwrap();
+        let mem = profile.save_to_mem().unwrap();
+
+        let mut profile = Profile::open_mem(&mem).unwrap();
+        let raw = profile.read_raw_tag(tag).unwrap();
+        (raw, profile.read_tag::<Seq>(tag).unwrap().clone())
+    }
+
+    #[test]
+    fn profile_sequence_desc_round_trip() {
+        for (version, text_type) in [(2.1, b"desc"), (4.3, b"mluc")] {
+            let (raw, seq) = round_trip(version, sig::tags::PROFILE_SEQUENCE_DESC);
+            assert_eq!(&raw[..4], b"pseq");
+            assert_eq!(&raw[32..36], text_type);
+
+            assert_eq!(seq.len(), 2);
+            for (i, desc) in seq.seq.iter().enumerate() {
+                assert!(desc.device_mfg == sig::technology::FILM_SCANNER);
+                assert!(desc.device_model == sig::technology::DIGITAL_CAMERA);
+                assert_eq!(desc.attributes, 1 << i);
+                assert!(desc.technology == sig::technology::REFLECTIVE_SCANNER);
+                assert_eq!(ascii(&desc.manufacturer), format!("Maker {}", i));
+                assert_eq!(ascii(&desc.model), format!("Model {}", i));
+            }
+        }
+    }
+}
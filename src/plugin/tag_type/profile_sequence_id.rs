@@ -0,0 +1,119 @@
+use std::{any::Any, cell::RefCell, mem::size_of};
+
+use crate::{
+    io::IoHandler,
+    plugin::{read_u32, write_u32},
+    types::{tag::Base as TagBase, Seq},
+    Result,
+};
+
+use super::{read_embedded_text, read_position_table, save_description, write_position_table, TagTypeHandler};
+
+// Each entry is the profile ID followed by the description of the profile
+fn read_seq_id(
+    handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    cargo: &dyn Any,
+    n: usize,
+    size_of_tag: usize,
+) -> Result<()> {
+    let Some(out_seq) = cargo.downcast_ref::<RefCell<Seq>>() else {
+        return Err("Invalid cargo in read_seq_id".into());
+    };
+    let mut out_seq = out_seq.borrow_mut();
+    let seq = &mut out_seq.seq[n];
+
+    let mut id8 = [0u8; 16];
+    if (io.read)(io, &mut id8, 16, 1) != 16 {
+        return Err("Read error in read_seq_id".into());
+    }
+    seq.profile_id.id8 = id8;
+
+    // Get the description
+    *seq.description = read_embedded_text(handler, io, size_of_tag.saturating_sub(16))?;
+
+    Ok(())
+}
+
+pub fn type_profile_sequence_id_read(
+    handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    n_items: &mut usize,
+    _size_of_tag: usize,
+) -> Result<Box<dyn Any>> {
+    *n_items = 0;
+
+    let base_offset = (io.tell)(io) - size_of::<TagBase>();
+
+    // Get table count
+    let count = read_u32(io)? as usize;
+
+    // Allocate an empty structure
+    let out_seq = RefCell::new(Seq::new(io.context_id, count)?);
+
+    // Read the position table
+    read_position_table(handler, io, count, base_offset, &out_seq, read_seq_id)?;
+
+    // Success
+    *n_items = 1;
+    Ok(Box::new(out_seq.into_inner()))
+}
+
+fn write_seq_id(
+    handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    cargo: &dyn Any,
+    n: usize,
+    _size_of_tag: usize,
+) -> Result<()> {
+    let Some(seq) = cargo.downcast_ref::<Seq>() else {
+        return Err("Invalid cargo in write_seq_id".into());
+    };
+
+    let id8 = unsafe { seq.seq[n].profile_id.id8 };
+    if !(io.write)(io, 16, &id8) {
+        return Err("Write error in write_seq_id".into());
+    }
+
+    // Store here the MLU
+    save_description(handler, io, &seq.seq[n].description)
+}
+
+pub fn type_profile_sequence_id_write(
+    handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    ptr: &dyn Any,
+    _n_items: usize,
+) -> Result<()> {
+    let Some(seq) = ptr.downcast_ref::<Seq>() else {
+        return Err("Invalid object to write with type_profile_sequence_id_write".into());
+    };
+
+    let base_offset = (io.tell)(io) - size_of::<TagBase>();
+
+    write_u32(io, seq.len() as u32)?;
+
+    write_position_table(handler, io, 0, seq.len(), base_offset, seq, write_seq_id)
+}
+
+type_dup_and_free!(profile_sequence_id, Seq);
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        plugin::tag_type::profile_sequence_desc::tests::{ascii, round_trip},
+        sig,
+    };
+
+    #[test]
+    fn profile_sequence_id_round_trip() {
+        let (raw, seq) = round_trip(4.3, sig::tags::PROFILE_SEQUENCE_ID);
+        assert_eq!(&raw[..4], b"psid");
+
+        assert_eq!(seq.len(), 2);
+        for (i, desc) in seq.seq.iter().enumerate() {
+            assert_eq!(unsafe { desc.profile_id.id8 }, [i as u8 + 1; 16]);
+            assert_eq!(ascii(&desc.description), format!("Profile {}", i));
+        }
+    }
+}
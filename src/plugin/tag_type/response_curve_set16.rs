@@ -0,0 +1,258 @@
+use std::{any::Any, mem::size_of};
+
+use crate::{
+    io::IoHandler,
+    plugin::{
+        read_s15f16, read_signature, read_u16, read_u32, read_xyz, write_s15f16, write_signature,
+        write_u16, write_u32, write_xyz,
+    },
+    types::{tag::Base as TagBase, Response16, ResponseCurve, ResponseCurveSet},
+    Result, MAX_CHANNELS,
+};
+
+use super::TagTypeHandler;
+
+// Each response is a device code, a reserved word and the measurement
+const RESPONSE16_SIZE: usize = 8;
+
+fn read_response_curve(
+    io: &mut IoHandler,
+    n_channels: usize,
+    size_of_tag: usize,
+) -> Result<ResponseCurve> {
+    let measurement_unit = read_signature(io)?;
+
+    let mut counts = [0usize; MAX_CHANNELS];
+    for count in counts[..n_channels].iter_mut() {
+        *count = read_u32(io)? as usize;
+
+        if *count > size_of_tag / RESPONSE16_SIZE {
+            return Err("Too many measurements in type_response_curve_set16_read".into());
+        }
+    }
+
+    let mut solid_xyz = Vec::with_capacity(n_channels);
+    for _ in 0..n_channels {
+        solid_xyz.push(read_xyz(io)?);
+    }
+
+    let mut responses = Vec::with_capacity(n_channels);
+    for &count in &counts[..n_channels] {
+        let mut channel = Vec::with_capacity(count);
+
+        for _ in 0..count {
+            let device_code = read_u16(io)?;
+            read_u16(io)?; // Reserved
+            let measurement = read_s15f16(io)?;
+
+            channel.push(Response16 {
+                device_code,
+                measurement,
+            });
+        }
+
+        responses.push(channel);
+    }
+
+    Ok(ResponseCurve {
+        measurement_unit,
+        solid_xyz,
+        responses,
+    })
+}
+
+pub fn type_response_curve_set16_read(
+    _handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    n_items: &mut usize,
+    size_of_tag: usize,
+) -> Result<Box<dyn Any>> {
+    *n_items = 0;
+
+    let base_offset = (io.tell)(io) - size_of::<TagBase>();
+
+    let n_channels = read_u16(io)? as usize;
+    let n_curves = read_u16(io)? as usize;
+
+    if n_channels == 0 || n_channels > MAX_CHANNELS {
+        return Err("Wrong number of channels in type_response_curve_set16_read".into());
+    }
+
+    if n_curves > size_of_tag / size_of::<u32>() {
+        return Err("Too many curves in type_response_curve_set16_read".into());
+    }
+
+    let mut offsets = vec![0u32; n_curves];
+    for offset in offsets.iter_mut() {
+        *offset = read_u32(io)?;
+    }
+
+    let mut curves = Vec::with_capacity(n_curves);
+    for offset in offsets {
+        if !(io.seek)(io, base_offset + offset as usize) {
+            return Err("Seek error in type_response_curve_set16_read".into());
+        }
+
+        curves.push(read_response_curve(io, n_channels, size_of_tag)?);
+    }
+
+    *n_items = 1;
+    Ok(Box::new(ResponseCurveSet { n_channels, curves }))
+}
+
+pub fn type_response_curve_set16_write(
+    _handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    ptr: &dyn Any,
+    _n_items: usize,
+) -> Result<()> {
+    let Some(set) = ptr.downcast_ref::<ResponseCurveSet>() else {
+        return Err("Invalid object to write with type_response_curve_set16_write".into());
+    };
+
+    let n_channels = set.n_channels;
+    if n_channels == 0 || n_channels > MAX_CHANNELS || set.curves.len() > u16::MAX as usize {
+        return Err("Wrong response curve set in type_response_curve_set16_write".into());
+    }
+
+    // All curves should have one entry per channel
+    if set
+        .curves
+        .iter()
+        .any(|curve| curve.solid_xyz.len() != n_channels || curve.responses.len() != n_channels)
+    {
+        return Err("Wrong number of channels in type_response_curve_set16_write".into());
+    }
+
+    let base_offset = (io.tell)(io) - size_of::<TagBase>();
+
+    write_u16(io, n_channels as u16)?;
+    write_u16(io, set.curves.len() as u16)?;
+
+    // Keep directory to be filled latter
+    let directory_pos = (io.tell)(io);
+
+    // Write a zero directory
+    for _ in &set.curves {
+        write_u32(io, 0)?;
+    }
+
+    let mut offsets = Vec::with_capacity(set.curves.len());
+    for curve in &set.curves {
+        offsets.push((io.tell)(io) - base_offset);
+
+        write_signature(io, curve.measurement_unit)?;
+
+        for channel in &curve.responses {
+            write_u32(io, channel.len() as u32)?;
+        }
+
+        for &xyz in &curve.solid_xyz {
+            write_xyz(io, xyz)?;
+        }
+
+        for response in curve.responses.iter().flatten() {
+            write_u16(io, response.device_code)?;
+            write_u16(io, 0)?; // Reserved
+            write_s15f16(io, response.measurement)?;
+        }
+    }
+
+    let current_pos = (io.tell)(io);
+
+    if !(io.seek)(io, directory_pos) {
+        return Err("Seek error in type_response_curve_set16_write".into());
+    }
+
+    for offset in offsets {
+        write_u32(io, offset as u32)?;
+    }
+
+    if !(io.seek)(io, current_pos) {
+        return Err("Seek error in type_response_curve_set16_write".into());
+    }
+
+    Ok(())
+}
+
+type_dup_and_free!(response_curve_set16, ResponseCurveSet);
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        sig,
+        types::{Profile, Response16, ResponseCurve, ResponseCurveSet, Signature, XYZ},
+    };
+
+    fn response_curve(measurement_unit: Signature, scale: f64) -> ResponseCurve {
+        let responses = (0..2)
+            .map(|channel| {
+                (0..=channel + 1)
+                    .map(|i| Response16 {
+                        device_code: (i * 0x7fff) as u16,
+                        measurement: scale * i as f64,
+                    })
+                    .collect()
+            })
+            .collect();
+
+        ResponseCurve {
+            measurement_unit,
+            solid_xyz: vec![XYZ { x: 0.25, y: 0.5, z: 0.125 }; 2],
+            responses,
+        }
+    }
+
+    #[test]
+    fn response_curve_set_round_trip() {
+        let set = ResponseCurveSet {
+            n_channels: 2,
+            curves: vec![
+                response_curve(sig::response_curve::STATUS_T, 0.5),
+                response_curve(sig::response_curve::STATUS_A, 1.5),
+            ],
+        };
+
+        let mut profile = Profile::new();
+        profile.write_tag(sig::tags::OUTPUT_RESPONSE, &set).unwrap();
+        let mem = profile.save_to_mem().unwrap();
+
+        let mut profile = Profile::open_mem(&mem).unwrap();
+        let read = profile
+            .read_tag::<ResponseCurveSet>(sig::tags::OUTPUT_RESPONSE)
+            .unwrap();
+
+        assert_eq!(read.n_channels, 2);
+        assert_eq!(read.curves.len(), 2);
+        for (read, curve) in read.curves.iter().zip(&set.curves) {
+            assert!(read.measurement_unit == curve.measurement_unit);
+
+            for xyz in &read.solid_xyz {
+                assert_eq!((xyz.x, xyz.y, xyz.z), (0.25, 0.5, 0.125));
+            }
+
+            assert_eq!(read.responses.len(), curve.responses.len());
+            for (read, channel) in read.responses.iter().zip(&curve.responses) {
+                assert_eq!(read.len(), channel.len());
+                for (read, response) in read.iter().zip(channel) {
+                    assert_eq!(read.device_code, response.device_code);
+                    assert_eq!(read.measurement, response.measurement);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn mismatched_channels_are_rejected() {
+        let mut curve = response_curve(sig::response_curve::STATUS_T, 1.0);
+        curve.solid_xyz.pop();
+        let set = ResponseCurveSet {
+            n_channels: 2,
+            curves: vec![curve],
+        };
+
+        let mut profile = Profile::new();
+        profile.write_tag(sig::tags::OUTPUT_RESPONSE, &set).unwrap();
+        assert!(profile.save_to_mem().is_err());
+    }
+}
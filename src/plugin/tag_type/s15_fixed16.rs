@@ -14,7 +14,7 @@ pub fn type_s15_fixed16_read(_handler: &TagTypeHandler, io: &mut IoHandler, n_it
         array_f64[i] = read_s15f16(io)?;
     }
 
-    *n_items = 1;
+    *n_items = n;
     let value = Box::new(array_f64);
     Ok(value)
 }
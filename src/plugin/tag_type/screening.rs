@@ -0,0 +1,117 @@
+use std::any::Any;
+
+use crate::{
+    io::IoHandler,
+    plugin::{read_s15f16, read_u32, write_s15f16, write_u32},
+    types::{Screening, ScreeningChannel},
+    Result, MAX_CHANNELS,
+};
+
+use super::TagTypeHandler;
+
+pub fn type_screening_read(
+    _handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    n_items: &mut usize,
+    _size_of_tag: usize,
+) -> Result<Box<dyn Any>> {
+    *n_items = 0;
+
+    let mut sc = Screening {
+        flag: read_u32(io)?,
+        n_channels: read_u32(io)?,
+        channels: [ScreeningChannel {
+            frequency: 0.0,
+            screen_angle: 0.0,
+            spot_shape: 0,
+        }; MAX_CHANNELS],
+    };
+
+    if sc.n_channels as usize > MAX_CHANNELS - 1 {
+        sc.n_channels = MAX_CHANNELS as u32 - 1;
+    }
+
+    for channel in sc.channels[..sc.n_channels as usize].iter_mut() {
+        channel.frequency = read_s15f16(io)?;
+        channel.screen_angle = read_s15f16(io)?;
+        channel.spot_shape = read_u32(io)?;
+    }
+
+    *n_items = 1;
+    Ok(Box::new(sc))
+}
+
+pub fn type_screening_write(
+    _handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    ptr: &dyn Any,
+    _n_items: usize,
+) -> Result<()> {
+    let Some(sc) = ptr.downcast_ref::<Screening>() else {
+        return Err("Invalid object to write with type_screening_write".into());
+    };
+
+    if sc.n_channels as usize > MAX_CHANNELS {
+        return Err("Too many channels in type_screening_write".into());
+    }
+
+    write_u32(io, sc.flag)?;
+    write_u32(io, sc.n_channels)?;
+
+    for channel in &sc.channels[..sc.n_channels as usize] {
+        write_s15f16(io, channel.frequency)?;
+        write_s15f16(io, channel.screen_angle)?;
+        write_u32(io, channel.spot_shape)?;
+    }
+
+    Ok(())
+}
+
+type_dup_and_free!(screening, Screening);
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        sig,
+        types::{Profile, Screening, ScreeningChannel},
+        MAX_CHANNELS,
+    };
+
+    #[test]
+    fn screening_round_trip() {
+        let mut sc = Screening {
+            flag: 1,
+            n_channels: 2,
+            channels: [ScreeningChannel {
+                frequency: 0.0,
+                screen_angle: 0.0,
+                spot_shape: 0,
+            }; MAX_CHANNELS],
+        };
+        sc.channels[0] = ScreeningChannel {
+            frequency: 150.0,
+            screen_angle: 45.0,
+            spot_shape: 1,
+        };
+        sc.channels[1] = ScreeningChannel {
+            frequency: 133.5,
+            screen_angle: 15.0,
+            spot_shape: 3,
+        };
+
+        let mut profile = Profile::new();
+        profile.write_tag(sig::tags::SCREENING, &sc).unwrap();
+        let mem = profile.save_to_mem().unwrap();
+
+        let mut profile = Profile::open_mem(&mem).unwrap();
+        assert_eq!(&profile.read_raw_tag(sig::tags::SCREENING).unwrap()[..4], b"scrn");
+
+        let read = profile.read_tag::<Screening>(sig::tags::SCREENING).unwrap();
+        assert_eq!((read.flag, read.n_channels), (1, 2));
+        for (read, channel) in read.channels.iter().zip(&sc.channels).take(2) {
+            assert_eq!(read.frequency, channel.frequency);
+            assert_eq!(read.screen_angle, channel.screen_angle);
+            assert_eq!(read.spot_shape, channel.spot_shape);
+        }
+    }
+}
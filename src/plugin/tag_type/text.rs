@@ -76,7 +76,7 @@ pub fn type_text_write(
 
 type_dup_and_free!(text, MLU);
 
-pub fn decide_text_type(icc_version: f64, _data: Box<dyn Any>) -> Signature {
+pub fn decide_text_type(icc_version: f64, _data: &dyn Any) -> Signature {
     if icc_version >= 4.0 {
         sig::types::MULTI_LOCALIZED_UNICODE
     } else {
@@ -8,7 +8,7 @@ use crate::{
     Result, NO_COUNTRY, NO_LANGUAGE, inlines::align_long,
 };
 
-use super::{TagTypeHandler, write_utf16_slice};
+use super::{read_utf16_slice, write_utf16_slice, TagTypeHandler};
 
 pub fn type_text_description_read(
     handler: &TagTypeHandler,
@@ -57,8 +57,8 @@ pub fn type_text_description_read(
         // Unicode code
         return Ok(Box::new(mlu));
     }
-    let _unicode_count = match read_u32(io) {
-        Ok(value) => value,
+    let unicode_count = match read_u32(io) {
+        Ok(value) => value as usize,
         Err(_) => {
             return Ok(Box::new(mlu));
         }
@@ -66,6 +66,17 @@ pub fn type_text_description_read(
 
     let size_of_tag = size_of_tag - 2 * size_of::<u32>();
 
+    // Skip the Unicode string as well, so embedded descriptions leave the stream right after them
+    if size_of_tag < unicode_count * size_of::<u16>() {
+        return Ok(Box::new(mlu));
+    }
+    let mut dummy = vec![0u16; unicode_count];
+    if read_utf16_slice(io, &mut dummy).is_err() {
+        return Ok(Box::new(mlu));
+    }
+
+    let size_of_tag = size_of_tag - unicode_count * size_of::<u16>();
+
     // Skip ScriptCode code if present. Some buggy profiles have less data
     // than strictly required. We need to skip it as this type my come
     // embedded in other types.
@@ -95,81 +106,82 @@ pub fn type_text_description_write(
     ptr: &dyn Any,
     _n_items: usize,
 ) -> Result<()> {
+    match ptr.downcast_ref::<MLU>() {
+        None => Err("Invalid object to write with type_text_description_write".into()),
+        Some(mlu) => write_text_description(io, mlu, true),
+    }
+}
+
+/// Writes a text description. Padding up to the alignment is only wanted for whole tags, as
+/// descriptions embedded in other types are read back to back.
+pub(crate) fn write_text_description(io: &mut IoHandler, mlu: &MLU, pad: bool) -> Result<()> {
     fn write_err() -> Result<()> {
         Err("Write error in type_text_description_write".into())
     }
 
-    match ptr.downcast_ref::<MLU>() {
-        None => Err("Invalid object to write with type_text_description_write".into()),
-        Some(mlu) => {
-            let filler = [0u8; 68];
-
-            // Get the len of string
-            let len = match mlu.get_ascii(NO_LANGUAGE, NO_COUNTRY, &mut []) {
-                Some(value) => value,
-                None => return Err("No ascii text to write in type_text_description_write".into()),
-            };
+    let filler = [0u8; 68];
 
-            // Get both representations
-            let mut text = vec![0u8; len];
-            let mut wide = vec![0u16; len];
+    // Get the len of string
+    let len = match mlu.get_ascii(NO_LANGUAGE, NO_COUNTRY, &mut []) {
+        Some(value) => value,
+        None => return Err("No ascii text to write in type_text_description_write".into()),
+    };
 
-            if let None = mlu.get_ascii(NO_LANGUAGE, NO_COUNTRY, &mut text) {
-                return Err("No text to write in type_text_description_write".into());
-            }
-            mlu.get_wide(NO_LANGUAGE, NO_COUNTRY, &mut wide);
-
-            // Tell the real text len
-            let len_text = text.len();
-            // Compute a total tag size requirement
-            let len_tag_requirement = 8  // Alignment
-                + 4                             // count
-                + len_text                      // desc[count]
-                + 4                             // ucLangCode
-                + 4                             // ucCount
-                + (2 * len_text)                // ucDesc[ucCount]
-                + 2                             // scCode
-                + 1                             // scCount
-                + 67                            // scDesc[67]
-                + 0;
-            let len_aligned = align_long(len_tag_requirement);
-
-            write_u32(io, len_text as u32)?;
-            if !(io.write)(io, len_text, &text) {
-                return write_err();
-            }
+    // Get both representations
+    let mut text = vec![0u8; len];
+    let mut wide = vec![0u16; len];
 
-            write_u32(io, 0)?;  // ucLanguageCode
+    if mlu.get_ascii(NO_LANGUAGE, NO_COUNTRY, &mut text).is_none() {
+        return Err("No text to write in type_text_description_write".into());
+    }
+    mlu.get_wide(NO_LANGUAGE, NO_COUNTRY, &mut wide);
+
+    // Tell the real text len
+    let len_text = text.len();
+    // Compute a total tag size requirement
+    let len_tag_requirement = 8  // Alignment
+        + 4                             // count
+        + len_text                      // desc[count]
+        + 4                             // ucLangCode
+        + 4                             // ucCount
+        + (2 * len_text)                // ucDesc[ucCount]
+        + 2                             // scCode
+        + 1                             // scCount
+        + 67;                           // scDesc[67]
+    let len_aligned = align_long(len_tag_requirement);
+
+    write_u32(io, len_text as u32)?;
+    if !(io.write)(io, len_text, &text) {
+        return write_err();
+    }
 
-            write_u32(io, len_text as u32)?;
-            write_utf16_slice(io, &wide)?;
+    write_u32(io, 0)?;  // ucLanguageCode
 
-            // ScriptCode Code & count (unused)
-            write_u16(io, 0)?;
-            write_u8(io, 0)?;
+    write_u32(io, len_text as u32)?;
+    write_utf16_slice(io, &wide)?;
 
-            if !(io.write)(io, 67usize, &filler) {
-                return write_err();
-            }
+    // ScriptCode Code & count (unused)
+    write_u16(io, 0)?;
+    write_u8(io, 0)?;
 
-            // possibly add pad at the end of tag
-            if len_aligned - len_tag_requirement > 0 {
-                if !(io.write)(io, len_aligned - len_tag_requirement, &filler) {
-                    return write_err();
-                }
-            }
+    if !(io.write)(io, 67usize, &filler) {
+        return write_err();
+    }
 
-            Ok(())
-        }
+    // possibly add pad at the end of tag
+    if pad && len_aligned - len_tag_requirement > 0 && !(io.write)(io, len_aligned - len_tag_requirement, &filler) {
+        return write_err();
     }
+
+    Ok(())
 }
 
 type_dup_and_free!(text_description, MLU);
 
-pub fn decide_text_desc_type(icc_version: f64, _data: Box<dyn Any>) -> Signature {
+pub fn decide_text_desc_type(icc_version: f64, _data: &dyn Any) -> Signature {
     if icc_version >= 4.0 {
         sig::types::MULTI_LOCALIZED_UNICODE
     } else {
-        sig::types::TEXT
+        sig::types::TEXT_DESCRIPTION
     }
 }
@@ -0,0 +1,130 @@
+use std::{any::Any, mem::size_of};
+
+use crate::{
+    io::IoHandler,
+    plugin::{read_u16_slice, read_u32, write_u16_slice, write_u32},
+    types::{ToneCurve, UcrBg, MLU},
+    Result, NO_COUNTRY, NO_LANGUAGE,
+};
+
+use super::TagTypeHandler;
+
+fn read_ucr_bg_curve(handler: &TagTypeHandler, io: &mut IoHandler, size_of_tag: &mut usize) -> Result<ToneCurve> {
+    if *size_of_tag < size_of::<u32>() {
+        return Err("Tag is too small in type_ucr_bg_read".into());
+    }
+    let count = read_u32(io)? as usize;
+    *size_of_tag -= size_of::<u32>();
+
+    if *size_of_tag / size_of::<u16>() < count {
+        return Err("Tag is too small in type_ucr_bg_read".into());
+    }
+    let mut table = vec![0u16; count];
+    read_u16_slice(io, &mut table)?;
+    *size_of_tag -= count * size_of::<u16>();
+
+    ToneCurve::build_tabulated_16(&handler.context_id, &table)
+}
+
+pub fn type_ucr_bg_read(
+    handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    n_items: &mut usize,
+    size_of_tag: usize,
+) -> Result<Box<dyn Any>> {
+    *n_items = 0;
+
+    let mut size_of_tag = size_of_tag;
+
+    // First curve is Under color removal
+    let ucr = read_ucr_bg_curve(handler, io, &mut size_of_tag)?;
+
+    // Second curve is Black generation
+    let bg = read_ucr_bg_curve(handler, io, &mut size_of_tag)?;
+
+    // Now comes the text. The length is specified by the tag size
+    let mut text = vec![0u8; size_of_tag];
+    if (io.read)(io, &mut text, size_of::<u8>(), size_of_tag) != size_of_tag {
+        return Err("Read error in type_ucr_bg_read".into());
+    }
+
+    let mut desc = MLU::new(&handler.context_id, 1);
+    desc.set_ascii(NO_LANGUAGE, NO_COUNTRY, &text)?;
+
+    *n_items = 1;
+    Ok(Box::new(UcrBg {
+        ucr,
+        bg,
+        desc: Box::new(desc),
+    }))
+}
+
+pub fn type_ucr_bg_write(
+    _handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    ptr: &dyn Any,
+    _n_items: usize,
+) -> Result<()> {
+    let Some(value) = ptr.downcast_ref::<UcrBg>() else {
+        return Err("Invalid object to write with type_ucr_bg_write".into());
+    };
+
+    // First curve is Under color removal
+    write_u32(io, value.ucr.table_16().len() as u32)?;
+    write_u16_slice(io, value.ucr.table_16())?;
+
+    // Then black generation
+    write_u32(io, value.bg.table_16().len() as u32)?;
+    write_u16_slice(io, value.bg.table_16())?;
+
+    // Now comes the text. The length is specified by the tag size
+    let text_size = value.desc.get_ascii(NO_LANGUAGE, NO_COUNTRY, &mut []).unwrap_or(0);
+    let mut text = vec![0u8; text_size];
+    value.desc.get_ascii(NO_LANGUAGE, NO_COUNTRY, &mut text);
+
+    // Up to and including the terminator
+    let len = text.iter().position(|&c| c == 0).map_or(text.len(), |pos| pos + 1);
+    if !(io.write)(io, len, &text[..len]) {
+        return Err("Write error in type_ucr_bg_write".into());
+    }
+
+    Ok(())
+}
+
+type_dup_and_free!(ucr_bg, UcrBg);
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        sig,
+        types::{Profile, ToneCurve, UcrBg, MLU},
+        DEFAULT_CONTEXT, NO_COUNTRY, NO_LANGUAGE,
+    };
+
+    #[test]
+    fn ucr_bg_round_trip() {
+        let mut desc = MLU::new(&DEFAULT_CONTEXT, 1);
+        desc.set_ascii(NO_LANGUAGE, NO_COUNTRY, b"Heavy GCR").unwrap();
+
+        let value = UcrBg {
+            ucr: ToneCurve::build_tabulated_16(&DEFAULT_CONTEXT, &[0, 0x4000, 0x9000]).unwrap(),
+            bg: ToneCurve::build_tabulated_16(&DEFAULT_CONTEXT, &[0, 0x2000, 0x8000, 0xffff]).unwrap(),
+            desc: Box::new(desc),
+        };
+
+        let mut profile = Profile::new();
+        profile.write_tag(sig::tags::UCR_BG, &value).unwrap();
+        let mem = profile.save_to_mem().unwrap();
+
+        let mut profile = Profile::open_mem(&mem).unwrap();
+        assert_eq!(&profile.read_raw_tag(sig::tags::UCR_BG).unwrap()[..4], b"bfd ");
+
+        let read = profile.read_tag::<UcrBg>(sig::tags::UCR_BG).unwrap();
+        assert_eq!(read.ucr.table_16(), &[0, 0x4000, 0x9000]);
+        assert_eq!(read.bg.table_16(), &[0, 0x2000, 0x8000, 0xffff]);
+
+        let mut text = [0u8; 16];
+        let len = read.desc.get_ascii(NO_LANGUAGE, NO_COUNTRY, &mut text).unwrap();
+        assert_eq!(&text[..len - 1], b"Heavy GCR");
+    }
+}
@@ -0,0 +1,65 @@
+use std::{any::Any, mem::size_of};
+
+use crate::{
+    io::IoHandler,
+    plugin::{read_u16_slice, write_u16_slice},
+    Result,
+};
+
+use super::TagTypeHandler;
+
+pub fn type_uint16_array_read(
+    _handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    n_items: &mut usize,
+    size_of_tag: usize,
+) -> Result<Box<dyn Any>> {
+    *n_items = 0;
+
+    let n = size_of_tag / size_of::<u16>();
+    let mut array = vec![0u16; n];
+
+    read_u16_slice(io, &mut array)?;
+
+    *n_items = 1;
+    Ok(Box::new(array))
+}
+
+pub fn type_uint16_array_write(
+    _handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    ptr: &dyn Any,
+    _n_items: usize,
+) -> Result<()> {
+    let Some(array) = ptr.downcast_ref::<Vec<u16>>() else {
+        return Err("Invalid object to write with type_uint16_array_write".into());
+    };
+
+    write_u16_slice(io, array)?;
+
+    Ok(())
+}
+
+type_dup_and_free!(uint16_array, Vec<u16>);
+
+#[cfg(test)]
+mod tests {
+    use crate::{io::IoHandler, plugin::get_tag_type_handler, sig, DEFAULT_CONTEXT};
+
+    #[test]
+    fn uint16_array_round_trip() {
+        let handler = get_tag_type_handler(&DEFAULT_CONTEXT, sig::types::UINT16_ARRAY).unwrap();
+        let array = vec![0u16, 0x1234, 0xffff];
+
+        let mut io = IoHandler::mem_writer(&DEFAULT_CONTEXT);
+        (handler.write)(handler, &mut io, &array, 1).unwrap();
+        let mem = io.into_mem().unwrap();
+        assert_eq!(mem, b"\x00\x00\x12\x34\xff\xff");
+
+        let mut io = IoHandler::from_mem(&DEFAULT_CONTEXT, &mem);
+        let mut n_items = 0;
+        let read = (handler.read)(handler, &mut io, &mut n_items, mem.len()).unwrap();
+        assert_eq!(n_items, 1);
+        assert_eq!(*read.downcast::<Vec<u16>>().unwrap(), array);
+    }
+}
@@ -0,0 +1,69 @@
+use std::{any::Any, mem::size_of};
+
+use crate::{
+    io::IoHandler,
+    plugin::{read_u32, write_u32},
+    Result,
+};
+
+use super::TagTypeHandler;
+
+pub fn type_uint32_array_read(
+    _handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    n_items: &mut usize,
+    size_of_tag: usize,
+) -> Result<Box<dyn Any>> {
+    *n_items = 0;
+
+    let n = size_of_tag / size_of::<u32>();
+    let mut array = vec![0u32; n];
+
+    for value in array.iter_mut() {
+        *value = read_u32(io)?;
+    }
+
+    *n_items = 1;
+    Ok(Box::new(array))
+}
+
+pub fn type_uint32_array_write(
+    _handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    ptr: &dyn Any,
+    _n_items: usize,
+) -> Result<()> {
+    let Some(array) = ptr.downcast_ref::<Vec<u32>>() else {
+        return Err("Invalid object to write with type_uint32_array_write".into());
+    };
+
+    for value in array {
+        write_u32(io, *value)?;
+    }
+
+    Ok(())
+}
+
+type_dup_and_free!(uint32_array, Vec<u32>);
+
+#[cfg(test)]
+mod tests {
+    use crate::{io::IoHandler, plugin::get_tag_type_handler, sig, DEFAULT_CONTEXT};
+
+    #[test]
+    fn uint32_array_round_trip() {
+        let handler = get_tag_type_handler(&DEFAULT_CONTEXT, sig::types::UINT32_ARRAY).unwrap();
+        let array = vec![0u32, 0x12345678, 0xffffffff];
+
+        let mut io = IoHandler::mem_writer(&DEFAULT_CONTEXT);
+        (handler.write)(handler, &mut io, &array, 1).unwrap();
+        let mem = io.into_mem().unwrap();
+        assert_eq!(mem, b"\x00\x00\x00\x00\x12\x34\x56\x78\xff\xff\xff\xff");
+
+        let mut io = IoHandler::from_mem(&DEFAULT_CONTEXT, &mem);
+        let mut n_items = 0;
+        let read = (handler.read)(handler, &mut io, &mut n_items, mem.len()).unwrap();
+        assert_eq!(n_items, 1);
+        assert_eq!(*read.downcast::<Vec<u32>>().unwrap(), array);
+    }
+}
@@ -0,0 +1,69 @@
+use std::{any::Any, mem::size_of};
+
+use crate::{
+    io::IoHandler,
+    plugin::{read_u64, write_u64},
+    Result,
+};
+
+use super::TagTypeHandler;
+
+pub fn type_uint64_array_read(
+    _handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    n_items: &mut usize,
+    size_of_tag: usize,
+) -> Result<Box<dyn Any>> {
+    *n_items = 0;
+
+    let n = size_of_tag / size_of::<u64>();
+    let mut array = vec![0u64; n];
+
+    for value in array.iter_mut() {
+        *value = read_u64(io)?;
+    }
+
+    *n_items = 1;
+    Ok(Box::new(array))
+}
+
+pub fn type_uint64_array_write(
+    _handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    ptr: &dyn Any,
+    _n_items: usize,
+) -> Result<()> {
+    let Some(array) = ptr.downcast_ref::<Vec<u64>>() else {
+        return Err("Invalid object to write with type_uint64_array_write".into());
+    };
+
+    for value in array {
+        write_u64(io, *value)?;
+    }
+
+    Ok(())
+}
+
+type_dup_and_free!(uint64_array, Vec<u64>);
+
+#[cfg(test)]
+mod tests {
+    use crate::{io::IoHandler, plugin::get_tag_type_handler, sig, DEFAULT_CONTEXT};
+
+    #[test]
+    fn uint64_array_round_trip() {
+        let handler = get_tag_type_handler(&DEFAULT_CONTEXT, sig::types::UINT64_ARRAY).unwrap();
+        let array = vec![0x0123456789abcdefu64, u64::MAX];
+
+        let mut io = IoHandler::mem_writer(&DEFAULT_CONTEXT);
+        (handler.write)(handler, &mut io, &array, 1).unwrap();
+        let mem = io.into_mem().unwrap();
+        assert_eq!(mem, b"\x01\x23\x45\x67\x89\xab\xcd\xef\xff\xff\xff\xff\xff\xff\xff\xff");
+
+        let mut io = IoHandler::from_mem(&DEFAULT_CONTEXT, &mem);
+        let mut n_items = 0;
+        let read = (handler.read)(handler, &mut io, &mut n_items, mem.len()).unwrap();
+        assert_eq!(n_items, 1);
+        assert_eq!(*read.downcast::<Vec<u64>>().unwrap(), array);
+    }
+}
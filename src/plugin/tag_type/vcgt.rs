@@ -0,0 +1,226 @@
+use std::any::Any;
+
+use log::Level;
+
+use crate::{
+    from_8_to_16,
+    io::IoHandler,
+    plugin::{
+        read_s15f16, read_u16, read_u16_slice, read_u32, read_u8, write_s15f16, write_u16, write_u32,
+    },
+    quick_saturate_word, signal_error,
+    state::ErrorCode,
+    types::ToneCurve,
+    Result,
+};
+
+use super::TagTypeHandler;
+
+// Type of the vcgt tag: table or formula
+const VIDEO_CARD_GAMMA_TABLE_TYPE: u32 = 0;
+const VIDEO_CARD_GAMMA_FORMULA_TYPE: u32 = 1;
+
+pub fn type_vcgt_read(
+    handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    n_items: &mut usize,
+    size_of_tag: usize,
+) -> Result<Box<dyn Any>> {
+    *n_items = 0;
+
+    // Read tag type
+    let tag_type = read_u32(io)?;
+
+    let curves: [ToneCurve; 3] = match tag_type {
+        // In this case, gamma is stored as a table
+        VIDEO_CARD_GAMMA_TABLE_TYPE => {
+            // Check channel count
+            let n_channels = read_u16(io)?;
+            let n_elems = read_u16(io)? as usize;
+            let mut n_bytes = read_u16(io)?;
+
+            // A single channel is not supported, 3 channels only
+            if n_channels != 3 {
+                let msg = format!("Unsupported number of channels for VCGT '{}'", n_channels);
+                signal_error(&handler.context_id, Level::Error, ErrorCode::UnknownExtension, &msg);
+                return Err(msg);
+            }
+
+            // Adobe's quirk fixup. Fixing broken profiles...
+            if n_elems == 256 && n_bytes == 1 && size_of_tag == 1576 {
+                n_bytes = 2;
+            }
+
+            let mut read_channel = || -> Result<ToneCurve> {
+                let mut table = vec![0u16; n_elems];
+
+                // On depending on byte depth
+                match n_bytes {
+                    // One byte, 0..255
+                    1 => {
+                        for value in table.iter_mut() {
+                            *value = from_8_to_16(read_u8(io)?);
+                        }
+                    }
+                    // One word 0..65535
+                    2 => {
+                        read_u16_slice(io, &mut table)?;
+                    }
+                    // One dword, only the 16 most significant bits are kept
+                    4 => {
+                        for value in table.iter_mut() {
+                            *value = (read_u32(io)? >> 16) as u16;
+                        }
+                    }
+                    // Unsupported
+                    _ => {
+                        let msg = format!("Unsupported bit depth for VCGT '{}'", n_bytes as u32 * 8);
+                        signal_error(&handler.context_id, Level::Error, ErrorCode::UnknownExtension, &msg);
+                        return Err(msg);
+                    }
+                }
+
+                ToneCurve::build_tabulated_16(&handler.context_id, &table)
+            };
+
+            [read_channel()?, read_channel()?, read_channel()?]
+        }
+        VIDEO_CARD_GAMMA_FORMULA_TYPE => {
+            let mut read_channel = || -> Result<ToneCurve> {
+                let gamma = read_s15f16(io)?;
+                let min = read_s15f16(io)?;
+                let max = read_s15f16(io)?;
+
+                // Parametric curve type 5 is:
+                // Y = (aX + b)^Gamma + e | X >= d
+                // Y = cX + f             | X < d
+
+                // vcgt formula is:
+                // Y = (Max - Min) * (X ^ Gamma) + Min
+
+                // So, the translation is
+                // a = (Max - Min) ^ ( 1 / Gamma)
+                // e = Min
+                // b=c=d=f=0
+                let params = [gamma, (max - min).powf(1.0 / gamma), 0.0, 0.0, 0.0, min, 0.0];
+
+                ToneCurve::build_parametric(&handler.context_id, 5, &params)
+            };
+
+            [read_channel()?, read_channel()?, read_channel()?]
+        }
+        _ => {
+            let msg = format!("Unsupported tag type for VCGT '{}'", tag_type);
+            signal_error(&handler.context_id, Level::Error, ErrorCode::UnknownExtension, &msg);
+            return Err(msg);
+        }
+    };
+
+    *n_items = 1;
+    Ok(Box::new(curves))
+}
+
+pub fn type_vcgt_write(
+    _handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    ptr: &dyn Any,
+    _n_items: usize,
+) -> Result<()> {
+    let Some(curves) = ptr.downcast_ref::<[ToneCurve; 3]>() else {
+        return Err("Invalid object to write with type_vcgt_write".into());
+    };
+
+    // Check if we can save it as parametric
+    if curves.iter().all(|curve| curve.parametric_type() == 5) {
+        write_u32(io, VIDEO_CARD_GAMMA_FORMULA_TYPE)?;
+
+        // Save parameters
+        for curve in curves {
+            let params = &curve.segments()[0].params;
+
+            let gamma = params[0];
+            let min = params[5];
+            let max = params[1].powf(gamma) + min;
+
+            write_s15f16(io, gamma)?;
+            write_s15f16(io, min)?;
+            write_s15f16(io, max)?;
+        }
+    } else {
+        // Always store as a table of 256 words
+        write_u32(io, VIDEO_CARD_GAMMA_TABLE_TYPE)?;
+        write_u16(io, 3)?;
+        write_u16(io, 256)?;
+        write_u16(io, 2)?;
+
+        for curve in curves {
+            for j in 0..256 {
+                let v = curve.eval_f32(j as f32 / 255.0);
+                write_u16(io, quick_saturate_word(v as f64 * 65535.0))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+type_dup_and_free!(vcgt, [ToneCurve; 3]);
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        sig,
+        types::{Profile, ToneCurve},
+        DEFAULT_CONTEXT,
+    };
+
+    fn round_trip(curves: &[ToneCurve; 3]) -> (Vec<u8>, Profile<'static, 'static, 'static>) {
+        let mut profile = Profile::new();
+        profile.write_tag(sig::tags::VCGT, curves).unwrap();
+        let mem = profile.save_to_mem().unwrap();
+
+        let mut profile = Profile::open_mem(&mem).unwrap();
+        let raw = profile.read_raw_tag(sig::tags::VCGT).unwrap();
+        (raw, profile)
+    }
+
+    #[test]
+    fn vcgt_table_round_trip() {
+        let curves = [0u32, 1, 2].map(|c| {
+            let table: Vec<u16> = (0..256u32).map(|i| (i * (255 - c * 32) + c * 512) as u16).collect();
+            ToneCurve::build_tabulated_16(&DEFAULT_CONTEXT, &table).unwrap()
+        });
+
+        let (raw, mut profile) = round_trip(&curves);
+        assert_eq!(&raw[..4], b"vcgt");
+        assert_eq!(u32::from_be_bytes(raw[8..12].try_into().unwrap()), 0);
+
+        let ramp = profile.vcgt_ramp(256).unwrap();
+        for (ramp, curve) in ramp.iter().zip(&curves) {
+            assert_eq!(ramp, curve.table_16());
+        }
+    }
+
+    #[test]
+    fn vcgt_formula_round_trip() {
+        let params = [2.5, 0.8f64.powf(1.0 / 2.5), 0.0, 0.0, 0.0, 0.125, 0.0];
+        let curve = ToneCurve::build_parametric(&DEFAULT_CONTEXT, 5, &params).unwrap();
+        let curves = [curve.clone(), curve.clone(), curve.clone()];
+
+        let (raw, mut profile) = round_trip(&curves);
+        assert_eq!(u32::from_be_bytes(raw[8..12].try_into().unwrap()), 1);
+
+        let read = profile.read_tag::<[ToneCurve; 3]>(sig::tags::VCGT).unwrap();
+        for read in read {
+            assert_eq!(read.parametric_type(), 5);
+            for x in [0.0, 0.3, 1.0] {
+                assert!((read.eval_f32(x) - curve.eval_f32(x)).abs() < 1e-4);
+            }
+        }
+
+        // Y = (Max - Min) * X ^ Gamma + Min, with Min = 0.125 and Max = 0.925
+        let ramp = profile.vcgt_ramp(2).unwrap();
+        assert_eq!(ramp[0], [0x2000, 0xeccc]);
+        assert!(profile.vcgt_ramp(1).is_err());
+    }
+}
@@ -0,0 +1,75 @@
+use std::any::Any;
+
+use crate::{
+    io::IoHandler,
+    plugin::{read_u8, write_u8},
+    types::VideoSignalType,
+    Result,
+};
+
+use super::TagTypeHandler;
+
+pub fn type_video_signal_read(
+    _handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    n_items: &mut usize,
+    size_of_tag: usize,
+) -> Result<Box<dyn Any>> {
+    *n_items = 0;
+
+    if size_of_tag < 4 {
+        return Err("Tag is too small in type_video_signal_read".into());
+    }
+
+    let cicp = VideoSignalType {
+        color_primaries: read_u8(io)?,
+        transfer_characteristics: read_u8(io)?,
+        matrix_coefficients: read_u8(io)?,
+        video_full_range_flag: read_u8(io)?,
+    };
+
+    *n_items = 1;
+    Ok(Box::new(cicp))
+}
+
+pub fn type_video_signal_write(
+    _handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    ptr: &dyn Any,
+    _n_items: usize,
+) -> Result<()> {
+    let Some(cicp) = ptr.downcast_ref::<VideoSignalType>() else {
+        return Err("Invalid object to write with type_video_signal_write".into());
+    };
+
+    write_u8(io, cicp.color_primaries)?;
+    write_u8(io, cicp.transfer_characteristics)?;
+    write_u8(io, cicp.matrix_coefficients)?;
+    write_u8(io, cicp.video_full_range_flag)?;
+
+    Ok(())
+}
+
+type_dup_and_free!(video_signal, VideoSignalType);
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        sig,
+        types::{Profile, VideoSignalType},
+    };
+
+    #[test]
+    fn video_signal_round_trip() {
+        let mut profile = Profile::new();
+        profile.write_tag(sig::tags::CICP, &VideoSignalType::BT2100_PQ).unwrap();
+        let mem = profile.save_to_mem().unwrap();
+
+        let mut profile = Profile::open_mem(&mem).unwrap();
+        let raw = profile.read_raw_tag(sig::tags::CICP).unwrap();
+        assert_eq!(raw, b"cicp\0\0\0\0\x09\x10\x00\x01");
+
+        let read = profile.read_tag::<VideoSignalType>(sig::tags::CICP).unwrap();
+        assert_eq!(*read, VideoSignalType::BT2100_PQ);
+    }
+}
@@ -0,0 +1,76 @@
+use std::any::Any;
+
+use crate::{
+    io::IoHandler,
+    plugin::{read_u32, read_xyz, write_u32, write_xyz},
+    types::ViewingConditions,
+    Result,
+};
+
+use super::TagTypeHandler;
+
+pub fn type_viewing_conditions_read(
+    _handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    n_items: &mut usize,
+    _size_of_tag: usize,
+) -> Result<Box<dyn Any>> {
+    *n_items = 0;
+
+    let vc = ViewingConditions {
+        illuminant: read_xyz(io)?,
+        surround: read_xyz(io)?,
+        illuminant_type: read_u32(io)?,
+    };
+
+    *n_items = 1;
+    Ok(Box::new(vc))
+}
+
+pub fn type_viewing_conditions_write(
+    _handler: &TagTypeHandler,
+    io: &mut IoHandler,
+    ptr: &dyn Any,
+    _n_items: usize,
+) -> Result<()> {
+    let Some(vc) = ptr.downcast_ref::<ViewingConditions>() else {
+        return Err("Invalid object to write with type_viewing_conditions_write".into());
+    };
+
+    write_xyz(io, vc.illuminant)?;
+    write_xyz(io, vc.surround)?;
+    write_u32(io, vc.illuminant_type)?;
+
+    Ok(())
+}
+
+type_dup_and_free!(viewing_conditions, ViewingConditions);
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        sig,
+        types::{Profile, ViewingConditions, XYZ},
+    };
+
+    #[test]
+    fn viewing_conditions_round_trip() {
+        let vc = ViewingConditions {
+            illuminant: XYZ { x: 76.0, y: 80.0, z: 65.25 },
+            surround: XYZ { x: 15.25, y: 16.0, z: 13.0 },
+            illuminant_type: 1,
+        };
+
+        let mut profile = Profile::new();
+        profile.write_tag(sig::tags::VIEWING_CONDITIONS, &vc).unwrap();
+        let mem = profile.save_to_mem().unwrap();
+
+        let mut profile = Profile::open_mem(&mem).unwrap();
+        assert_eq!(&profile.read_raw_tag(sig::tags::VIEWING_CONDITIONS).unwrap()[..4], b"view");
+
+        let read = profile.read_tag::<ViewingConditions>(sig::tags::VIEWING_CONDITIONS).unwrap();
+        assert_eq!((read.illuminant.x, read.illuminant.y, read.illuminant.z), (76.0, 80.0, 65.25));
+        assert_eq!((read.surround.x, read.surround.y, read.surround.z), (15.25, 16.0, 13.0));
+        assert_eq!(read.illuminant_type, 1);
+    }
+}
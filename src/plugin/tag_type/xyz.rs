@@ -25,6 +25,6 @@ pub fn type_xyz_write(_handler: &TagTypeHandler, io: &mut IoHandler, ptr: &dyn A
 
 type_dup_and_free!(xyz, XYZ);
 
-pub fn decide_xyz_type(_icc_version: f64, _data: Box<dyn Any>) -> Signature {
+pub fn decide_xyz_type(_icc_version: f64, _data: &dyn Any) -> Signature {
     sig::types::XYZ
 }
@@ -43,3 +43,27 @@ pub const COLOR13: Signature = Signature(0x44434C52);
 pub const COLOR14: Signature = Signature(0x45434C52);
 pub const COLOR15: Signature = Signature(0x46434C52);
 pub const LUVK: Signature = Signature(0x4C75764B);
+
+/// Returns the number of channels of a color space, or `None` if the space is unknown.
+pub fn channels_of(color_space: Signature) -> Option<u32> {
+    let n = match color_space {
+        MCH1 | COLOR1 | GRAY => 1,
+        MCH2 | COLOR2 => 2,
+        XYZ | LAB | LUV | YCBCR | YXY | RGB | HSV | HLS | CMY | MCH3 | COLOR3 => 3,
+        LUVK | CMYK | MCH4 | COLOR4 => 4,
+        MCH5 | COLOR5 => 5,
+        MCH6 | COLOR6 => 6,
+        MCH7 | COLOR7 => 7,
+        MCH8 | COLOR8 => 8,
+        MCH9 | COLOR9 => 9,
+        MCHA | COLOR10 => 10,
+        MCHB | COLOR11 => 11,
+        MCHC | COLOR12 => 12,
+        MCHD | COLOR13 => 13,
+        MCHE | COLOR14 => 14,
+        MCHF | COLOR15 => 15,
+        _ => return None,
+    };
+
+    Some(n)
+}
@@ -40,8 +40,6 @@ impl ErrorCode {
     }
 }
 
-pub fn default_error_handler_log_function(_context_id: &Context, level: Level, error_code: Option<ErrorCode>, text: &'static str) {
-    if let Some(error_code) = error_code {
-        log!(level, "[{}] => {}", error_code.unwrap(), text)
-    }
+pub fn default_error_handler_log_function(_context_id: &Context, level: Level, error_code: ErrorCode, text: &str) {
+    log!(level, "[{}] => {}", error_code.unwrap(), text)
 }
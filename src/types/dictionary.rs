@@ -1,11 +1,69 @@
-use crate::list;
+use log::Level;
+
+use crate::{signal_error, state::ErrorCode, Context, Result};
 
 use super::MLU;
 
+#[derive(Clone)]
 pub struct Entry {
-    pub next: list::Link<Entry>,
-    pub display_name: Box<MLU>,
-    pub display_value: Box<MLU>,
+    pub display_name: Option<MLU>,
+    pub display_value: Option<MLU>,
     pub name: String,
     pub value: String,
 }
+
+/// Name/value pairs, as stored in the metadata tag. Entries keep their insertion order.
+#[derive(Clone)]
+pub struct Dictionary {
+    pub context_id: &'static Context,
+    pub(crate) entries: Vec<Entry>,
+}
+
+impl Dictionary {
+    pub fn new(context_id: &'static Context) -> Dictionary {
+        Dictionary {
+            context_id,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Adds an entry. Names must be unique.
+    pub fn insert(
+        &mut self,
+        name: &str,
+        value: &str,
+        display_name: Option<&MLU>,
+        display_value: Option<&MLU>,
+    ) -> Result<()> {
+        if self.get(name).is_some() {
+            let msg = format!("Duplicated dictionary entry '{}'", name);
+            signal_error(self.context_id, Level::Error, ErrorCode::AlreadyDefined, &msg);
+            return Err(msg);
+        }
+
+        self.entries.push(Entry {
+            display_name: display_name.cloned(),
+            display_value: display_value.cloned(),
+            name: name.to_owned(),
+            value: value.to_owned(),
+        });
+
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Entry> {
+        self.entries.iter().find(|entry| entry.name == name)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Entry> {
+        self.entries.iter()
+    }
+}
@@ -36,7 +36,7 @@ impl<T: Copy> InterpParams<T> {
             return result;
         }
 
-        default_factory(n_inputs, n_outputs, flags)
+        default_interp_factory(n_inputs, n_outputs, flags)
     }
 
     pub(crate) fn compute_ex(
@@ -110,7 +110,7 @@ impl<T: Copy> InterpParams<T> {
     ) -> Result<InterpParams<T>> {
         Self::compute_ex(
             context_id,
-            &[n_samples; MAX_INPUT_DIMENSIONS],
+            &[n_samples; MAX_INPUT_DIMENSIONS][..input_chan.min(MAX_INPUT_DIMENSIONS)],
             input_chan,
             output_chan,
             table,
@@ -119,10 +119,24 @@ impl<T: Copy> InterpParams<T> {
     }
 }
 
+// With 16 bit nodes and 16 bit weights, the products of the interpolation do not fit in an i32.
+// They are computed in 64 bits.
+#[inline]
+fn tetrahedral_rest(c1: i32, c2: i32, c3: i32, rx: i32, ry: i32, rz: i32) -> i64 {
+    c1 as i64 * rx as i64 + c2 as i64 * ry as i64 + c3 as i64 * rz as i64
+}
+
+// Same as round_fixed_to_int(to_fixed_domain(rest)), in 64 bits
+#[inline]
+fn round_rest_to_int(rest: i64) -> i64 {
+    let rest = rest + ((rest + 0x7fff) / 0xffff);
+    (rest + 0x8000) >> 16
+}
+
 #[inline]
 fn linear_interp(a: i32, l: i32, h: i32) -> u16 {
-    let dif = ((h - l) * a) as u32 + 0x8000;
-    let dif = (dif >> 16) + l as u32;
+    let dif = (h - l) as i64 * a as i64 + 0x8000;
+    let dif = (dif >> 16) + l as i64;
 
     dif as u16
 }
@@ -151,10 +165,11 @@ fn lin_lerp_1d<'a>(value: &[u16], output: &'a mut [u16], p: &InterpParams<u16>)
 
 #[inline]
 fn fclamp(v: f32) -> f32 {
-    if v.is_nan() {
-        return 0.0;
+    if v < 1.0e-9 || v.is_nan() {
+        0.0
+    } else {
+        v.min(1.0)
     }
-    v.clamp(1e-9, 1.0)
 }
 
 fn lin_lerp_1d_f32<'a>(value: &[f32], output: &'a mut [f32], p: &InterpParams<f32>) -> &'a [f32] {
@@ -345,8 +360,7 @@ fn bilinear_interp_16<'a>(input: &[u16], output: &'a mut [u16], p: &InterpParams
         }
         macro_rules! lerp {
             ($a:expr, $l: expr, $h: expr) => {
-                ($l as i32 + round_fixed_to_int((($h as i32 - $l as i32) * $a as i32)) as i32)
-                    as u16
+                ($l as i64 + ((($h as i64 - $l as i64) * $a as i64 + 0x8000) >> 16)) as u16
             };
         }
         let d00 = dens!(x0, y0);
@@ -495,8 +509,7 @@ fn trilinear_interp_16<'a>(
         }
         macro_rules! lerp {
             ($a:expr, $l: expr, $h: expr) => {
-                ($l as i32 + round_fixed_to_int((($h as i32 - $l as i32) * $a as i32)) as i32)
-                    as u16
+                ($l as i64 + ((($h as i64 - $l as i64) * $a as i64 + 0x8000) >> 16)) as u16
             };
         }
         let d000 = dens!(x0, y0, z0);
@@ -649,29 +662,27 @@ fn tetrahedral_interp_16<'a>(
     let ry = fixed_rest_to_int(fy);
     let rz = fixed_rest_to_int(fz);
 
+    // The upper nodes are offsets from the base node, as the table is sliced there
     let x0 = x0 * p.opta[2] as i32;
-    let mut x1 = x0
-        + if input[0] == 0xFFFF {
-            0
-        } else {
-            p.opta[2] as i32
-        };
+    let mut x1 = if input[0] == 0xFFFF {
+        0
+    } else {
+        p.opta[2] as i32
+    };
 
     let y0 = y0 * p.opta[1] as i32;
-    let mut y1 = y0
-        + if input[1] == 0xFFFF {
-            0
-        } else {
-            p.opta[1] as i32
-        };
+    let mut y1 = if input[1] == 0xFFFF {
+        0
+    } else {
+        p.opta[1] as i32
+    };
 
     let z0 = z0 * p.opta[0] as i32;
-    let mut z1 = z0
-        + if input[2] == 0xFFFF {
-            0
-        } else {
-            p.opta[0] as i32
-        };
+    let mut z1 = if input[2] == 0xFFFF {
+        0
+    } else {
+        p.opta[0] as i32
+    };
 
     let mut lut_table = &p.table[((x0 + y0 + z0) as usize)..];
     let mut i = 0usize;
@@ -688,8 +699,8 @@ fn tetrahedral_interp_16<'a>(
                 let c3 = c3 - c2;
                 let c2 = c2 - c1;
                 let c1 = c1 - c0;
-                let rest = (c1 * rx) + (c2 * ry) + (c3 * rz) + 0x8001;
-                output[i] = (c0 + ((rest + (rest >> 16)) >> 16)) as u16;
+                let rest = tetrahedral_rest(c1, c2, c3, rx, ry, rz) + 0x8001;
+                output[i] = (c0 as i64 + ((rest + (rest >> 16)) >> 16)) as u16;
                 i = i + 1;
                 lut_table = &lut_table[1..];
             }
@@ -705,8 +716,8 @@ fn tetrahedral_interp_16<'a>(
                 let c2 = c2 - c1;
                 let c1 = c1 - c3;
                 let c3 = c3 - c0;
-                let rest = (c1 * rx) + (c2 * ry) + (c3 * rz) + 0x8001;
-                output[i] = (c0 + ((rest + (rest >> 16)) >> 16)) as u16;
+                let rest = tetrahedral_rest(c1, c2, c3, rx, ry, rz) + 0x8001;
+                output[i] = (c0 as i64 + ((rest + (rest >> 16)) >> 16)) as u16;
                 i = i + 1;
                 lut_table = &lut_table[1..];
             }
@@ -722,8 +733,8 @@ fn tetrahedral_interp_16<'a>(
                 let c2 = c2 - c3;
                 let c3 = c3 - c1;
                 let c1 = c1 - c0;
-                let rest = (c1 * rx) + (c2 * ry) + (c3 * rz) + 0x8001;
-                output[i] = (c0 + ((rest + (rest >> 16)) >> 16)) as u16;
+                let rest = tetrahedral_rest(c1, c2, c3, rx, ry, rz) + 0x8001;
+                output[i] = (c0 as i64 + ((rest + (rest >> 16)) >> 16)) as u16;
                 i = i + 1;
                 lut_table = &lut_table[1..];
             }
@@ -741,8 +752,8 @@ fn tetrahedral_interp_16<'a>(
                 let c3 = c3 - c1;
                 let c1 = c1 - c2;
                 let c2 = c2 - c0;
-                let rest = (c1 * rx) + (c2 * ry) + (c3 * rz) + 0x8001;
-                output[i] = (c0 + ((rest + (rest >> 16)) >> 16)) as u16;
+                let rest = tetrahedral_rest(c1, c2, c3, rx, ry, rz) + 0x8001;
+                output[i] = (c0 as i64 + ((rest + (rest >> 16)) >> 16)) as u16;
                 i = i + 1;
                 lut_table = &lut_table[1..];
             }
@@ -758,8 +769,8 @@ fn tetrahedral_interp_16<'a>(
                 let c1 = c1 - c3;
                 let c3 = c3 - c2;
                 let c2 = c2 - c0;
-                let rest = (c1 * rx) + (c2 * ry) + (c3 * rz) + 0x8001;
-                output[i] = (c0 + ((rest + (rest >> 16)) >> 16)) as u16;
+                let rest = tetrahedral_rest(c1, c2, c3, rx, ry, rz) + 0x8001;
+                output[i] = (c0 as i64 + ((rest + (rest >> 16)) >> 16)) as u16;
                 i = i + 1;
                 lut_table = &lut_table[1..];
             }
@@ -775,8 +786,8 @@ fn tetrahedral_interp_16<'a>(
                 let c1 = c1 - c2;
                 let c2 = c2 - c3;
                 let c3 = c3 - c0;
-                let rest = (c1 * rx) + (c2 * ry) + (c3 * rz) + 0x8001;
-                output[i] = (c0 + ((rest + (rest >> 16)) >> 16)) as u16;
+                let rest = tetrahedral_rest(c1, c2, c3, rx, ry, rz) + 0x8001;
+                output[i] = (c0 as i64 + ((rest + (rest >> 16)) >> 16)) as u16;
                 i = i + 1;
                 lut_table = &lut_table[1..];
             }
@@ -895,8 +906,8 @@ fn eval_4_inputs<'a>(input: &[u16], output: &'a mut [u16], p16: &InterpParams<u1
             }
         };
 
-        let rest = (c1 * rx) + (c2 * ry) + (c3 * rz);
-        tmp1[out_chan] = (c0 + round_fixed_to_int(to_fixed_domain(rest))) as u16;
+        let rest = tetrahedral_rest(c1, c2, c3, rx, ry, rz);
+        tmp1[out_chan] = (c0 as i64 + round_rest_to_int(rest)) as u16;
     }
 
     lut_table = &p16.table[(k1 as usize)..];
@@ -958,8 +969,8 @@ fn eval_4_inputs<'a>(input: &[u16], output: &'a mut [u16], p16: &InterpParams<u1
             }
         };
 
-        let rest = (c1 * rx) + (c2 * ry) + (c3 * rz);
-        tmp2[out_chan] = (c0 + round_fixed_to_int(to_fixed_domain(rest))) as u16;
+        let rest = tetrahedral_rest(c1, c2, c3, rx, ry, rz);
+        tmp2[out_chan] = (c0 as i64 + round_rest_to_int(rest)) as u16;
     }
 
     for i in 0..p16.n_outputs {
@@ -1100,7 +1111,7 @@ eval_fns!(13, 12);
 eval_fns!(14, 13);
 eval_fns!(15, 14);
 
-pub(crate) fn default_factory(
+pub(crate) fn default_interp_factory(
     n_inputs: usize,
     n_outputs: usize,
     flags: u32,
@@ -1285,3 +1296,88 @@ impl From<InterpFn<f32>> for InterpFunction {
         InterpFunction::F32(val)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DEFAULT_CONTEXT;
+
+    // A 3x3x3 grid holding a linear function of the node indices, which tetrahedral interpolation
+    // reproduces exactly
+    fn linear_clut() -> InterpParams<u16> {
+        let table = (0..27u16)
+            .flat_map(|i| {
+                let (x, y, z) = (i / 9, (i / 3) % 3, i % 3);
+                [1000 * x + 100 * y + 10 * z, 2000 - 500 * x]
+            })
+            .collect::<Vec<_>>();
+
+        InterpParams::compute_ex(&DEFAULT_CONTEXT, &[3, 3, 3], 3, 2, &table, 0).unwrap()
+    }
+
+    #[test]
+    fn tetrahedral_16_matches_linear_clut() {
+        let p = linear_clut();
+
+        for input in [
+            [0u16, 0, 0],
+            [0x8000, 0x4000, 0xc000],
+            [0xc000, 0xffff, 0x1234],
+            [0xffff, 0xffff, 0xffff],
+            [0x2000, 0xe000, 0x8000],
+        ] {
+            let mut output = [0u16; 2];
+            tetrahedral_interp_16(&input, &mut output, &p);
+
+            // Position of the input on the grid
+            let [x, y, z] = input.map(|v| v as f64 * 2.0 / 65535.0);
+            let expected = [1000.0 * x + 100.0 * y + 10.0 * z, 2000.0 - 500.0 * x];
+
+            for (got, expected) in output.iter().zip(expected) {
+                assert!((*got as f64 - expected).abs() <= 1.0, "{:?}: {} != {}", input, got, expected);
+            }
+        }
+    }
+
+    // A 2 point identity CLUT goes from 0 to 0xffff in a single cell, the steepest possible
+    fn identity_clut(n_inputs: usize) -> InterpParams<u16> {
+        let table = (0..1usize << n_inputs)
+            .flat_map(|i| (0..n_inputs).rev().map(move |bit| ((i >> bit) & 1) as u16 * 0xffff))
+            .collect::<Vec<_>>();
+
+        InterpParams::compute_ex(&DEFAULT_CONTEXT, &vec![2; n_inputs], n_inputs, n_inputs, &table, 0).unwrap()
+    }
+
+    #[test]
+    fn steep_clut_does_not_overflow() {
+        let inputs = [[0x1234u16, 0xfedc, 0x8000, 0x7fff], [0xffff, 0x0001, 0xfffe, 0xc000]];
+
+        for n_inputs in [3, 4] {
+            let p = identity_clut(n_inputs);
+
+            for input in &inputs {
+                let mut output = [0u16; MAX_STAGE_CHANNELS];
+                match p.interpolation {
+                    InterpFunction::U16(interp) => interp(&input[..n_inputs], &mut output, &p),
+                    _ => unreachable!(),
+                };
+
+                for (got, expected) in output.iter().zip(&input[..n_inputs]) {
+                    assert!(got.abs_diff(*expected) <= 1, "{:?}: {} != {}", input, got, expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn linear_interp_full_range() {
+        for (l, h) in [(0, 0xffff), (0xffff, 0), (0x1000, 0xf000)] {
+            for a in [0, 0x4000, 0x8000, 0xfffe] {
+                let expected = l as f64 + (h - l) as f64 * a as f64 / 65536.0;
+                let got = linear_interp(a, l, h);
+
+                assert!((got as f64 - expected).abs() <= 1.0, "{} {} {}: {} != {}", a, l, h, got, expected);
+            }
+        }
+    }
+}
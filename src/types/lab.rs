@@ -1,5 +1,82 @@
+use crate::{quick_saturate_word, MAX_ENCODEABLE_AB4, MIN_ENCODEABLE_AB4};
+
+use super::{LCh, XYZ};
+
+#[derive(Copy, Clone)]
 pub struct Lab {
     pub l: f64,
     pub a: f64,
     pub b: f64,
 }
+
+impl Lab {
+    /// Decodes a V4 16 bit Lab value.
+    pub fn from_encoded(w_lab: &[u16]) -> Lab {
+        Lab {
+            l: w_lab[0] as f64 / 655.35,
+            a: (w_lab[1] as f64 / 257.0) - 128.0,
+            b: (w_lab[2] as f64 / 257.0) - 128.0,
+        }
+    }
+
+    /// Encodes as V4 16 bit Lab. Out of range values are clamped.
+    pub fn to_encoded(&self) -> [u16; 3] {
+        let l = self.l.clamp(0.0, 100.0);
+        let a = self.a.clamp(MIN_ENCODEABLE_AB4, MAX_ENCODEABLE_AB4);
+        let b = self.b.clamp(MIN_ENCODEABLE_AB4, MAX_ENCODEABLE_AB4);
+
+        [
+            quick_saturate_word(l * 655.35),
+            quick_saturate_word((a + 128.0) * 257.0),
+            quick_saturate_word((b + 128.0) * 257.0),
+        ]
+    }
+
+    pub fn to_lch(&self) -> LCh {
+        LCh {
+            l: self.l,
+            c: (self.a * self.a + self.b * self.b).sqrt(),
+            h: atan2deg(self.b, self.a),
+        }
+    }
+
+    /// Converts to XYZ, relative to the given white point.
+    pub fn to_xyz(&self, white_point: XYZ) -> XYZ {
+        let y = (self.l + 16.0) / 116.0;
+        let x = y + 0.002 * self.a;
+        let z = y - 0.005 * self.b;
+
+        XYZ {
+            x: f_1(x) * white_point.x,
+            y: f_1(y) * white_point.y,
+            z: f_1(z) * white_point.z,
+        }
+    }
+}
+
+// Hue in degrees, 0..360
+fn atan2deg(a: f64, b: f64) -> f64 {
+    let mut h = if a == 0.0 && b == 0.0 { 0.0 } else { a.atan2(b) };
+
+    h = h.to_degrees();
+
+    while h > 360.0 {
+        h -= 360.0;
+    }
+    while h < 0.0 {
+        h += 360.0;
+    }
+
+    h
+}
+
+// Inverse of the Lab companding function
+fn f_1(t: f64) -> f64 {
+    const LIMIT: f64 = 24.0 / 116.0;
+
+    if t <= LIMIT {
+        return (108.0 / 841.0) * (t - (16.0 / 116.0));
+    }
+
+    t * t * t
+}
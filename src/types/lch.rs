@@ -1,5 +1,20 @@
+use super::Lab;
+
+#[derive(Copy, Clone)]
 pub struct LCh {
     pub l: f64,
     pub c: f64,
     pub h: f64,
 }
+
+impl LCh {
+    pub fn to_lab(&self) -> Lab {
+        let h = self.h.to_radians();
+
+        Lab {
+            l: self.l,
+            a: self.c * h.cos(),
+            b: self.c * h.sin(),
+        }
+    }
+}
@@ -1,7 +1,92 @@
+use crate::MATRIX_DET_TOLERANCE;
+
 use super::Vec3;
 
+/// A 3x3 matrix, stored by rows.
+#[derive(Copy, Clone, Debug)]
 pub struct Mat3 {
     pub x: Vec3,
     pub y: Vec3,
     pub z: Vec3,
 }
+
+impl Mat3 {
+    pub const fn new(x: Vec3, y: Vec3, z: Vec3) -> Mat3 {
+        Mat3 { x, y, z }
+    }
+
+    pub const fn identity() -> Mat3 {
+        Mat3 {
+            x: Vec3::new(1.0, 0.0, 0.0),
+            y: Vec3::new(0.0, 1.0, 0.0),
+            z: Vec3::new(0.0, 0.0, 1.0),
+        }
+    }
+
+    /// Builds a matrix from 9 values in row order.
+    pub const fn from_array(m: [f64; 9]) -> Mat3 {
+        Mat3 {
+            x: Vec3::new(m[0], m[1], m[2]),
+            y: Vec3::new(m[3], m[4], m[5]),
+            z: Vec3::new(m[6], m[7], m[8]),
+        }
+    }
+
+    /// Returns the 9 values in row order, as matrix stages and tags expect them.
+    pub const fn to_array(&self) -> [f64; 9] {
+        [
+            self.x.x, self.x.y, self.x.z, self.y.x, self.y.y, self.y.z, self.z.x, self.z.y, self.z.z,
+        ]
+    }
+
+    /// Multiplies the matrix by a column vector.
+    pub fn eval(&self, v: &Vec3) -> Vec3 {
+        Vec3::new(self.x.dot(v), self.y.dot(v), self.z.dot(v))
+    }
+
+    /// Matrix product, self * b.
+    pub fn per(&self, b: &Mat3) -> Mat3 {
+        let cols = [
+            Vec3::new(b.x.x, b.y.x, b.z.x),
+            Vec3::new(b.x.y, b.y.y, b.z.y),
+            Vec3::new(b.x.z, b.y.z, b.z.z),
+        ];
+        let row = |r: &Vec3| Vec3::new(r.dot(&cols[0]), r.dot(&cols[1]), r.dot(&cols[2]));
+
+        Mat3::new(row(&self.x), row(&self.y), row(&self.z))
+    }
+
+    /// Inverse of the matrix, or `None` if it is singular.
+    pub fn inverse(&self) -> Option<Mat3> {
+        let a = self;
+
+        let c0 = a.y.y * a.z.z - a.y.z * a.z.y;
+        let c1 = -a.y.x * a.z.z + a.y.z * a.z.x;
+        let c2 = a.y.x * a.z.y - a.y.y * a.z.x;
+
+        let det = a.x.x * c0 + a.x.y * c1 + a.x.z * c2;
+
+        // singular matrix; can't invert
+        if det.abs() < MATRIX_DET_TOLERANCE {
+            return None;
+        }
+
+        Some(Mat3 {
+            x: Vec3::new(
+                c0 / det,
+                (a.x.z * a.z.y - a.x.y * a.z.z) / det,
+                (a.x.y * a.y.z - a.x.z * a.y.y) / det,
+            ),
+            y: Vec3::new(
+                c1 / det,
+                (a.x.x * a.z.z - a.x.z * a.z.x) / det,
+                (a.x.z * a.y.x - a.x.x * a.y.z) / det,
+            ),
+            z: Vec3::new(
+                c2 / det,
+                (a.x.y * a.z.x - a.x.x * a.z.y) / det,
+                (a.x.x * a.y.y - a.x.y * a.y.x) / det,
+            ),
+        })
+    }
+}
@@ -1,5 +1,6 @@
 use super::XYZ;
 
+#[derive(Clone, Copy)]
 pub struct MeasurementConditions {
     pub observer: u32,
     pub backing: XYZ,
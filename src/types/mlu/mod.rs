@@ -1,24 +1,17 @@
-use std::{mem::size_of, sync::Arc};
-
-use codepage::to_encoding;
-use encoding_rs::mem::{convert_utf16_to_utf8, convert_utf8_to_utf16};
+use std::mem::{size_of, size_of_val};
 
 use crate::{plugin::adjust_endianess16, Context};
 
 use super::Dup;
 
 pub struct MLU {
-    context_id: Context,
-    allocated_entries: usize,
-    used_entries: usize,
-    entries: Vec<Entry>,
-    pool_size: usize,
-    pool_used: usize,
-    mem_pool: Vec<u16>,
+    pub(crate) context_id: Context,
+    pub(crate) entries: Vec<Entry>,
+    pub(crate) mem_pool: Vec<u16>,
 }
 
 #[derive(Clone)]
-struct Entry {
+pub(crate) struct Entry {
     pub language: u16,
     pub country: u16,
     pub str_w: usize,
@@ -29,125 +22,58 @@ impl<'a> MLU {
     pub fn new(context_id: &Context, n_items: usize) -> MLU {
         MLU {
             context_id: context_id.clone(),
-            allocated_entries: n_items,
-            used_entries: 0,
-            entries: Vec::<Entry>::with_capacity(n_items as usize),
-            pool_size: 0,
-            pool_used: 0,
+            entries: Vec::<Entry>::with_capacity(n_items),
             mem_pool: Vec::<u16>::new(),
         }
     }
 
-    fn grow_pool(&mut self) -> Result<(), String> {
-        let size = if self.pool_size == 0 {
-            256
-        } else {
-            self.pool_size * 2
-        };
-
-        // Check for overflow
-        if size < self.pool_size {
-            return Err("Overflow in MLU::grow_pool".into());
-        };
-
-        // "Reallocate" the pool
-        self.mem_pool.resize(size as usize, 0);
-        self.pool_size = size;
-
-        Ok(())
-    }
-
-    fn grow_table(&mut self) -> Result<(), String> {
-        let alloc = self.allocated_entries * 2;
-
-        // Check for overflow
-        if alloc / 2 != self.allocated_entries {
-            return Err("Overflow in MLU::grow_table".into());
-        }
-
-        self.entries.resize(
-            alloc as usize,
-            Entry {
-                language: 0,
-                country: 0,
-                str_w: 0,
-                len: 0,
-            },
-        );
-        self.allocated_entries = alloc;
-
-        Ok(())
-    }
-
-    fn search_entry(&self, lang_code: u16, cntr_code: u16) -> i32 {
+    fn search_entry(&self, lang_code: u16, cntr_code: u16) -> Option<usize> {
         // Iterate whole table
-        for i in 0..self.used_entries {
-            if self.entries[i as usize].country == cntr_code
-                && self.entries[i as usize].language == lang_code
-            {
-                return i as i32;
-            }
-        }
-
-        // Not found
-        -1
+        self.entries
+            .iter()
+            .position(|entry| entry.country == cntr_code && entry.language == lang_code)
     }
 
     fn add_block(&mut self, block: &[u16], lang_code: u16, cntr_code: u16) -> Result<(), String> {
-        let size = block.len();
-
-        // Is there any room available?
-        if self.used_entries >= self.allocated_entries {
-            self.grow_table()?;
-        }
-
         // Only one ASCII string
-        if self.search_entry(lang_code, cntr_code) >= 0 {
+        if self.search_entry(lang_code, cntr_code).is_some() {
             return Err("ASCII string already exists in MLU::add_block".into());
         }
 
-        // Check for size
-        while ((self.pool_size - self.pool_used) as usize) < (size * size_of::<u16>()) {
-            self.grow_pool()?;
-        }
-
-        let offset = self.pool_used;
-
-        let ptr = &mut self.mem_pool[(offset as usize)..];
+        let offset = self.mem_pool.len();
 
         // Set the entry
-        ptr.copy_from_slice(&block);
-        self.pool_used += size * size_of::<u16>();
+        self.mem_pool.extend_from_slice(block);
 
         self.entries.push(Entry {
             language: lang_code,
             country: cntr_code,
             str_w: offset * size_of::<u16>(),
-            len: (size * size_of::<u16>()),
+            len: size_of_val(block),
         });
 
         Ok(())
     }
 
+    /// Adds an ASCII string. The string ends at the first zero byte, if any.
     pub fn set_ascii(
         &mut self,
         lang_code: [u8; 2],
         cntr_code: [u8; 2],
         ascii_str: &[u8],
     ) -> Result<(), String> {
-        let len = ascii_str.len();
+        let len = ascii_str.iter().position(|&c| c == 0).unwrap_or(ascii_str.len());
         let lang = str_to_16(&lang_code);
         let cntr = str_to_16(&cntr_code);
 
-        let encoding = to_encoding(437).unwrap();
-        let (utf8_str, _, _) = encoding.decode(ascii_str);
-
-        let mut w_str = vec![0 as u16; len];
-
-        let chars_written = convert_utf8_to_utf16(utf8_str.as_bytes(), &mut w_str);
-        w_str.truncate(chars_written);
+        // Len == 0 would prevent operation, so we set a empty string pointing to zero
+        let w_str = if len == 0 {
+            vec![0u16]
+        } else {
+            ascii_str[..len].iter().map(|&c| c as u16).collect()
+        };
 
-        self.add_block(&w_str.as_slice(), lang, cntr)
+        self.add_block(&w_str, lang, cntr)
     }
 
     pub fn set_wide(
@@ -162,51 +88,36 @@ impl<'a> MLU {
         self.add_block(wide_str, lang, cntr)
     }
 
-    pub fn free(mut self) {
-        self.entries.clear();
-
+    pub fn free(self) {
         drop(self);
     }
 
     fn _get_wide(&'a self, lang_code: u16, cntr_code: u16) -> Option<(&'a [u16], u16, u16)> {
         let mlu = self;
 
-        let mut best = -1;
-
-        if mlu.allocated_entries <= 0 {
-            return None;
-        }
-
-        for i in 0..(mlu.used_entries as usize) {
-            let v = &mlu.entries[i];
+        let mut best = None;
 
+        for (i, v) in mlu.entries.iter().enumerate() {
             if v.language == lang_code {
-                if best == -1 {
-                    best = i as i32;
+                if best.is_none() {
+                    best = Some(i);
                 }
 
                 if v.country == cntr_code {
-                    best = i as i32;
+                    best = Some(i);
                     break;
                 }
             }
         }
 
         // No string found? Return first one
-        if best == -1 {
-            best = 0;
-        }
+        let v = mlu.entries.get(best.unwrap_or(0))?;
 
-        let v = &mlu.entries[best as usize];
-
-        Some((
-            &mlu.mem_pool[((v.str_w as usize) / size_of::<u16>())..]
-                [..((v.len as usize) / size_of::<u16>())],
-            v.language,
-            v.country,
-        ))
+        Some((mlu.entry_wide(v), v.language, v.country))
     }
 
+    /// Gets an ASCII string, replacing characters out of range by '?'. An empty buffer asks for
+    /// the length. Returns the number of bytes, including the terminating zero.
     pub fn get_ascii(
         &self,
         lang_code: [u8; 2],
@@ -219,34 +130,30 @@ impl<'a> MLU {
 
         // GetWideChar
         let (wide, _, _) = self._get_wide(lang, cntr)?;
-        if wide.len() == 0 {
-            return Some(0);
-        }
-
-        let mut utf8_str = vec![0u8; wide.len() * 3];
-
-        let chars_written = convert_utf16_to_utf8(wide, &mut utf8_str);
-
-        let encoding = to_encoding(437).unwrap();
-        let (ascii_str, _, _) =
-            encoding.encode(std::str::from_utf8(&utf8_str[..chars_written]).unwrap());
-
-        let mut ascii_len = ascii_str.len();
+        let mut ascii_len = wide.len();
 
+        // Maybe we want only to know the len?
         if buf_size == 0 {
-            return Some(ascii_len);
+            return Some(ascii_len + 1); // Note the zero at the end
         }
 
         // Some clipping may be required
-        if buf_size < ascii_len {
-            ascii_len = buf_size
+        if buf_size < ascii_len + 1 {
+            ascii_len = buf_size - 1;
         }
 
-        buffer[..ascii_len].copy_from_slice(&ascii_str);
+        // Process each character
+        for (c, &wc) in buffer.iter_mut().zip(&wide[..ascii_len]) {
+            *c = if wc < 0xff { wc as u8 } else { b'?' };
+        }
 
-        Some(ascii_len)
+        // We put a termination "\0"
+        buffer[ascii_len] = 0;
+        Some(ascii_len + 1)
     }
 
+    /// Gets a wide string. An empty buffer asks for the length.
+    /// Returns the number of characters, including the terminating zero.
     pub fn get_wide(
         &self,
         lang_code: [u8; 2],
@@ -259,24 +166,22 @@ impl<'a> MLU {
 
         // GetWideChar
         let (wide, _, _) = self._get_wide(lang, cntr)?;
-        if wide.len() == 0 {
-            return Some(0);
-        }
-
         let mut wide_len = wide.len();
 
+        // Maybe we want only to know the len?
         if buf_size == 0 {
-            return Some(wide_len);
+            return Some(wide_len + 1);
         }
 
         // Some clipping may be required
-        if buf_size < wide_len {
-            wide_len = buf_size
+        if buf_size < wide_len + 1 {
+            wide_len = buf_size - 1;
         }
 
-        buffer[..wide_len].copy_from_slice(&wide);
+        buffer[..wide_len].copy_from_slice(&wide[..wide_len]);
+        buffer[wide_len] = 0;
 
-        Some(wide_len)
+        Some(wide_len + 1)
     }
 
     pub fn get_translation(
@@ -294,7 +199,22 @@ impl<'a> MLU {
     }
 
     pub fn get_translations_count(&self) -> usize {
-        self.used_entries
+        self.entries.len()
+    }
+
+    // The string of an entry, without terminator
+    pub(crate) fn entry_wide(&self, entry: &Entry) -> &[u16] {
+        &self.mem_pool[(entry.str_w / size_of::<u16>())..][..(entry.len / size_of::<u16>())]
+    }
+}
+
+impl PartialEq for MLU {
+    /// Two MLUs are equal if they hold the same translations in the same order.
+    fn eq(&self, other: &Self) -> bool {
+        self.entries.len() == other.entries.len()
+            && self.entries.iter().zip(&other.entries).all(|(a, b)| {
+                a.language == b.language && a.country == b.country && self.entry_wide(a) == other.entry_wide(b)
+            })
     }
 }
 
@@ -303,25 +223,11 @@ impl Dup for MLU {
     where
         Self: Sized,
     {
-        let mut new_mlu = Self::new(context_id, self.used_entries);
-
-        // Should never happen
-        if new_mlu.allocated_entries < self.used_entries {
-            return Err("Corrupted construction of MLU in MLU::dup.".into());
-        }
-
-        for entry in &self.entries {
-            new_mlu.entries.push(entry.clone());
-        }
-        new_mlu.mem_pool.resize(self.pool_used as usize, 0);
-
-        new_mlu.pool_size = self.pool_size;
-
-        new_mlu.mem_pool.copy_from_slice(&self.mem_pool);
-
-        new_mlu.pool_used = self.pool_used;
-
-        Ok(new_mlu)
+        Ok(MLU {
+            context_id: context_id.clone(),
+            entries: self.entries.clone(),
+            mem_pool: self.mem_pool.clone(),
+        })
     }
 }
 
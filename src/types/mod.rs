@@ -15,6 +15,7 @@ mod named_color;
 mod pipeline;
 pub mod profile;
 mod profile_id;
+mod response_curve;
 mod screening;
 mod seq;
 mod signature;
@@ -31,6 +32,7 @@ mod xyz;
 pub use curve_segment::CurveSegment;
 pub use data::Data;
 pub use date_time::DateTime;
+pub use dictionary::Dictionary;
 pub use dictionary::Entry as DictionaryEntry;
 pub use dup::Dup;
 pub use format::Format;
@@ -41,19 +43,23 @@ pub use lch::LCh;
 pub use mat3::Mat3;
 pub use measurement_conditions::MeasurementConditions;
 pub use mlu::MLU;
+pub(crate) use mlu::Entry as MLUEntry;
 pub use named_color::{NamedColor, NamedColorEntry};
 pub use pipeline::Eval16Fn as PipelineEval16Fn;
 pub use pipeline::EvalFloatFn as PipelineEvalFloatFn;
 pub use pipeline::{
-    CLutStageData, CLutStageDataTab, MatrixStageData, Pipeline, Stage, StageDupElemFn, StageEvalFn,
-    StageFreeElemFn, ToneCurvesStageData,
+    CLutStageData, MatrixStageData, Pipeline, Stage, StageDupElemFn, StageEvalFn, StageFreeElemFn,
+    StageLoc, ToneCurvesStageData,
 };
+pub(crate) use pipeline::cube_size;
 pub use profile::Profile;
 pub use profile_id::ProfileID;
+pub use response_curve::{Response16, ResponseCurve, ResponseCurveSet};
 pub use screening::{Screening, ScreeningChannel};
 pub use seq::{PSeqDesc, Seq};
 pub use signature::Signature;
 pub use tone_curve::ToneCurve;
+pub(crate) use tone_curve::{MINUS_INF, PLUS_INF};
 pub use transform::{
     Stride, Transform, Transform2Factory, Transform2Fn, TransformFactory, TransformFn,
     TransformFunc,
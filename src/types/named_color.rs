@@ -1,17 +1,98 @@
-use crate::{list, Context, MAX_CHANNELS};
+use log::Level;
 
+use crate::{signal_error, state::ErrorCode, Context, Result, MAX_CHANNELS};
+
+#[derive(Clone)]
 pub struct NamedColorEntry {
     pub name: String,
     pub pcs: [u16; 3],
     pub device_colorant: [u16; MAX_CHANNELS as usize],
 }
 
+#[derive(Clone)]
 pub struct NamedColor {
     pub context_id: &'static Context,
-    pub n_colors: usize,
-    pub allocated: usize,
     pub colorant_count: usize,
     pub prefix: String,
     pub suffix: String,
-    pub list: list::Link<NamedColorEntry>,
+    pub(crate) list: Vec<NamedColorEntry>,
+}
+
+impl NamedColor {
+    /// Allocates an empty named color list. Colors are added with `append`.
+    pub fn new(
+        context_id: &'static Context,
+        colorant_count: usize,
+        prefix: &str,
+        suffix: &str,
+    ) -> Result<NamedColor> {
+        if colorant_count > MAX_CHANNELS {
+            let msg = format!("Too many device coordinates '{}'", colorant_count);
+            signal_error(context_id, Level::Error, ErrorCode::Range, &msg);
+            return Err(msg);
+        }
+
+        Ok(NamedColor {
+            context_id,
+            colorant_count,
+            prefix: prefix.to_owned(),
+            suffix: suffix.to_owned(),
+            list: Vec::new(),
+        })
+    }
+
+    /// Appends a color. Missing device colorants are set to zero.
+    pub fn append(&mut self, name: &str, pcs: [u16; 3], colorant: Option<&[u16]>) -> Result<()> {
+        let mut device_colorant = [0u16; MAX_CHANNELS];
+
+        if let Some(colorant) = colorant {
+            if colorant.len() < self.colorant_count {
+                let msg = format!(
+                    "Named color '{}' has {} colorants, {} expected",
+                    name,
+                    colorant.len(),
+                    self.colorant_count
+                );
+                signal_error(self.context_id, Level::Error, ErrorCode::Range, &msg);
+                return Err(msg);
+            }
+
+            device_colorant[..self.colorant_count].copy_from_slice(&colorant[..self.colorant_count]);
+        }
+
+        self.list.push(NamedColorEntry {
+            name: name.to_owned(),
+            pcs,
+            device_colorant,
+        });
+
+        Ok(())
+    }
+
+    /// Returns the number of colors in the list.
+    pub fn count(&self) -> usize {
+        self.list.len()
+    }
+
+    /// Finds a color by name, ignoring case.
+    pub fn index(&self, name: &str) -> Option<usize> {
+        self.list
+            .iter()
+            .position(|entry| entry.name.eq_ignore_ascii_case(name))
+    }
+
+    pub fn get(&self, n: usize) -> Option<&NamedColorEntry> {
+        self.list.get(n)
+    }
+
+    /// Returns the device colorants in use of a color.
+    pub fn colorants(&self, n: usize) -> Option<&[u16]> {
+        self.list
+            .get(n)
+            .map(|entry| &entry.device_colorant[..self.colorant_count])
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &NamedColorEntry> {
+        self.list.iter()
+    }
 }
@@ -1,31 +1,286 @@
+mod stage;
+
+use std::any::Any;
+
+use log::Level;
+
+pub use stage::{Stage, StageDupElemFn, StageEvalFn, StageFreeElemFn};
+pub use stage::CLutData as CLutStageData;
+pub use stage::MatrixData as MatrixStageData;
+pub use stage::ToneCurvesData as ToneCurvesStageData;
+pub(crate) use stage::cube_size;
+
+use crate::{
+    plugin::{DupUserDataFn, FreeUserDataFn},
+    quick_saturate_word, signal_error,
+    state::ErrorCode,
+    types::Signature,
+    Context, Result, MAX_CHANNELS, MAX_STAGE_CHANNELS,
+};
+
 pub type Eval16Fn = fn(In: &[u16], Out: &mut [u16], data: &Box<dyn Any>);
 pub type EvalFloatFn = fn(In: &[f32], Out: &mut [f32], data: &Box<dyn Any>);
 
+/// Where to insert a stage in a pipeline.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StageLoc {
+    AtBegin,
+    AtEnd,
+}
+
 pub struct Pipeline {
-    pub(crate) elements: list::Link<Stage>,
-    pub(crate) context_id: Arc<crate::Context>,
+    pub(crate) elements: Vec<Stage>,
+    pub(crate) context_id: &'static Context,
     pub(crate) input_channels: u32,
     pub(crate) output_channels: u32,
 
-    pub(crate) data: Box<dyn Any>,
+    // Optimized evaluators replace the stage by stage evaluation when set
+    pub(crate) data: Option<Box<dyn Any>>,
 
-    pub(crate) eval_16_fn: Eval16Fn,
-    pub(crate) eval_float_fn: EvalFloatFn,
-    pub(crate) free_data_fn: FreeUserDataFn,
-    pub(crate) dup_data_fn: DupUserDataFn,
+    pub(crate) eval_16_fn: Option<Eval16Fn>,
+    pub(crate) eval_float_fn: Option<EvalFloatFn>,
+    pub(crate) free_data_fn: Option<FreeUserDataFn>,
+    pub(crate) dup_data_fn: Option<DupUserDataFn>,
 
     pub(crate) save_as_8_bits: bool,
 }
-mod stage;
 
-use std::any::Any;
-use std::sync::Arc;
+impl Pipeline {
+    /// Creates an empty pipeline. Stages must be inserted later on.
+    pub fn new(context_id: &'static Context, input_channels: u32, output_channels: u32) -> Result<Pipeline> {
+        // A value of zero in channels is allowed as placeholder
+        if input_channels as usize >= MAX_CHANNELS || output_channels as usize >= MAX_CHANNELS {
+            let msg = format!(
+                "Invalid number of channels in pipeline ({}->{})",
+                input_channels, output_channels
+            );
+            signal_error(context_id, Level::Error, ErrorCode::Range, &msg);
+            return Err(msg);
+        }
 
-pub use stage::{Stage, StageDupElemFn, StageEvalFn, StageFreeElemFn};
-pub use stage::CLutData as CLutStageData;
-pub use stage::Tab as CLutStageDataTab;
-pub use stage::MatrixData as MatrixStageData;
-pub use stage::ToneCurvesData as ToneCurvesStageData;
+        Ok(Pipeline {
+            elements: Vec::new(),
+            context_id,
+            input_channels,
+            output_channels,
+            data: None,
+            eval_16_fn: None,
+            eval_float_fn: None,
+            free_data_fn: None,
+            dup_data_fn: None,
+            save_as_8_bits: false,
+        })
+    }
+
+    /// Inserts a stage at the beginning or at the end of the pipeline.
+    /// Fails if the channels of the stage don't match its neighbours.
+    pub fn insert_stage(&mut self, loc: StageLoc, stage: Stage) -> Result<()> {
+        match loc {
+            StageLoc::AtBegin => self.elements.insert(0, stage),
+            StageLoc::AtEnd => self.elements.push(stage),
+        }
+
+        if let Err(msg) = self.bless() {
+            match loc {
+                StageLoc::AtBegin => self.elements.remove(0),
+                StageLoc::AtEnd => self.elements.pop().unwrap(),
+            };
+            signal_error(self.context_id, Level::Error, ErrorCode::Range, &msg);
+            return Err(msg);
+        }
+
+        Ok(())
+    }
+
+    // This function sets up the channel count
+    fn bless(&mut self) -> Result<()> {
+        // We can set the input/output channels only if we have elements.
+        let (Some(first), Some(last)) = (self.elements.first(), self.elements.last()) else {
+            return Ok(());
+        };
+
+        // Check chain consistency
+        for pair in self.elements.windows(2) {
+            if pair[1].input_channels != pair[0].output_channels {
+                return Err(format!(
+                    "Stage mismatch: {} output channels followed by {} input channels",
+                    pair[0].output_channels, pair[1].input_channels
+                ));
+            }
+        }
+
+        self.input_channels = first.input_channels;
+        self.output_channels = last.output_channels;
+
+        Ok(())
+    }
+
+    pub fn stages(&self) -> &[Stage] {
+        &self.elements
+    }
+
+    pub fn first_stage(&self) -> Option<&Stage> {
+        self.elements.first()
+    }
+
+    pub fn last_stage(&self) -> Option<&Stage> {
+        self.elements.last()
+    }
+
+    /// Returns the stages if the pipeline is made exactly of stages of the given types, in that order.
+    pub fn check_and_retrieve_stages(&self, types: &[Signature]) -> Option<&[Stage]> {
+        // Make sure same number of elements
+        if self.elements.len() != types.len() {
+            return None;
+        }
+
+        // Iterate across asked types
+        if self.elements.iter().zip(types).any(|(mpe, r#type)| mpe.r#type() != *r#type) {
+            return None;
+        }
 
-use crate::list;
-use crate::plugin::{FreeUserDataFn, DupUserDataFn};
+        Some(&self.elements)
+    }
+
+    pub fn input_channels(&self) -> u32 {
+        self.input_channels
+    }
+
+    pub fn output_channels(&self) -> u32 {
+        self.output_channels
+    }
+
+    pub fn context_id(&self) -> &'static Context {
+        self.context_id
+    }
+
+    /// Sets whether the pipeline should be saved as 8 bits LUT in V2 profiles. Returns the previous value.
+    pub fn set_save_as_8_bits(&mut self, on: bool) -> bool {
+        let previous = self.save_as_8_bits;
+        self.save_as_8_bits = on;
+        previous
+    }
+
+    /// Evaluates the pipeline in floating point.
+    pub fn eval_float(&self, r#in: &[f32], out: &mut [f32]) {
+        if let (Some(eval), Some(data)) = (self.eval_float_fn, &self.data) {
+            return eval(r#in, out, data);
+        }
+
+        let mut storage = [[0f32; MAX_STAGE_CHANNELS]; 2];
+        let mut phase = 0;
+
+        let n_in = self.input_channels as usize;
+        storage[phase][..n_in].copy_from_slice(&r#in[..n_in]);
+
+        for mpe in &self.elements {
+            let [a, b] = &mut storage;
+            let (from, to) = if phase == 0 { (a, b) } else { (b, a) };
+
+            mpe.eval(&from[..], &mut to[..]);
+            phase ^= 1;
+        }
+
+        let n_out = self.output_channels as usize;
+        out[..n_out].copy_from_slice(&storage[phase][..n_out]);
+    }
+
+    /// Evaluates the pipeline in 16 bits, through the floating point stages.
+    pub fn eval_16(&self, r#in: &[u16], out: &mut [u16]) {
+        if let (Some(eval), Some(data)) = (self.eval_16_fn, &self.data) {
+            return eval(r#in, out, data);
+        }
+
+        let mut in_float = [0f32; MAX_STAGE_CHANNELS];
+        let mut out_float = [0f32; MAX_STAGE_CHANNELS];
+
+        for (f, v) in in_float.iter_mut().zip(&r#in[..self.input_channels as usize]) {
+            *f = *v as f32 / 65535.0;
+        }
+
+        self.eval_float(&in_float, &mut out_float);
+
+        for (v, f) in out[..self.output_channels as usize].iter_mut().zip(out_float) {
+            *v = quick_saturate_word(f as f64 * 65535.0);
+        }
+    }
+
+    /// Duplicates the pipeline and all its stages. Optimization data is only kept when the
+    /// pipeline knows how to duplicate it.
+    pub fn dup(&self) -> Result<Pipeline> {
+        let elements = self.elements.iter().map(Stage::dup).collect::<Result<Vec<_>>>()?;
+
+        let data = match (&self.data, self.dup_data_fn) {
+            (Some(data), Some(dup)) => Some(dup(self.context_id.clone(), Box::new(data.as_ref()))),
+            _ => None,
+        };
+        let optimized = data.is_some();
+
+        Ok(Pipeline {
+            elements,
+            context_id: self.context_id,
+            input_channels: self.input_channels,
+            output_channels: self.output_channels,
+            data,
+            eval_16_fn: self.eval_16_fn.filter(|_| optimized),
+            eval_float_fn: self.eval_float_fn.filter(|_| optimized),
+            free_data_fn: self.free_data_fn.filter(|_| optimized),
+            dup_data_fn: self.dup_data_fn.filter(|_| optimized),
+            save_as_8_bits: self.save_as_8_bits,
+        })
+    }
+}
+
+impl Drop for Pipeline {
+    fn drop(&mut self) {
+        if let (Some(free), Some(data)) = (self.free_data_fn, self.data.take()) {
+            free(self.context_id.clone(), data);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{sig, DEFAULT_CONTEXT};
+
+    fn no_eval(_in: &[f32], _out: &mut [f32], _mpe: &Stage) {}
+
+    fn failing_dup(_mpe: &Stage) -> Result<Stage> {
+        Err("Stage cannot be duplicated".into())
+    }
+
+    fn no_free(_mpe: Stage) {}
+
+    #[test]
+    fn dup_copies_stages() {
+        let mut lut = Pipeline::new(&DEFAULT_CONTEXT, 3, 3).unwrap();
+        lut.insert_stage(StageLoc::AtEnd, Stage::new_tone_curves(&DEFAULT_CONTEXT, 3, None).unwrap())
+            .unwrap();
+
+        let copy = lut.dup().unwrap();
+        assert_eq!(copy.elements.len(), 1);
+        assert_eq!(copy.input_channels(), 3);
+    }
+
+    #[test]
+    fn dup_reports_stage_errors() {
+        let stage = Stage::alloc_placeholder(
+            &DEFAULT_CONTEXT,
+            sig::mpe_stage::CURVE_SET,
+            3,
+            3,
+            no_eval,
+            failing_dup,
+            no_free,
+            Box::new(()),
+        )
+        .unwrap();
+
+        let mut lut = Pipeline::new(&DEFAULT_CONTEXT, 3, 3).unwrap();
+        lut.insert_stage(StageLoc::AtEnd, stage).unwrap();
+
+        assert!(lut.elements[0].dup().is_err());
+        assert!(lut.dup().is_err());
+    }
+}
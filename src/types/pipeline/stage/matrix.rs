@@ -1,4 +1,5 @@
-pub struct MatrixData<'a> {
-    pub double: &'a [f64],
-    pub offset: Option<&'a [f64]>,
+#[derive(Clone)]
+pub struct MatrixData {
+    pub double: Vec<f64>,
+    pub offset: Option<Vec<f64>>,
 }
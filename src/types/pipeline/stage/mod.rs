@@ -1,9 +1,28 @@
+mod clut;
+mod matrix;
+mod tone_curve;
+
+use std::any::Any;
+
+use log::Level;
+
+pub use clut::CLutData;
+pub use matrix::MatrixData;
+pub use tone_curve::ToneCurvesData;
+
+use crate::{
+    quantize_val, quick_saturate_word, sig, signal_error,
+    state::ErrorCode,
+    types::{lerp_flag, InterpFunction, InterpParams, Signature, ToneCurve},
+    Context, Result, Sampler16, MAX_INPUT_DIMENSIONS, MAX_STAGE_CHANNELS, SAMPLER_INSPECT,
+};
+
 pub type StageEvalFn = fn(In: &[f32], Out: &mut [f32], mpe: &Stage);
-pub type StageDupElemFn = fn(mpe: &Stage) -> Result<Stage, String>;
+pub type StageDupElemFn = fn(mpe: &Stage) -> Result<Stage>;
 pub type StageFreeElemFn = fn(mpe: Stage);
 
 pub struct Stage {
-    pub(crate) context_id: &'static crate::Context,
+    pub(crate) context_id: &'static Context,
 
     pub(crate) r#type: Signature,
     pub(crate) implements: Signature,
@@ -16,18 +35,498 @@ pub struct Stage {
     pub(crate) free_ptr: StageFreeElemFn,
 
     pub(crate) data: Box<dyn Any>,
+}
+
+impl Stage {
+    /// Allocates an empty multi profile element. Used by plug-ins to build their own stages.
+    #[allow(clippy::too_many_arguments)]
+    pub fn alloc_placeholder(
+        context_id: &'static Context,
+        r#type: Signature,
+        input_channels: u32,
+        output_channels: u32,
+        eval_ptr: StageEvalFn,
+        dup_elem_ptr: StageDupElemFn,
+        free_ptr: StageFreeElemFn,
+        data: Box<dyn Any>,
+    ) -> Result<Stage> {
+        if input_channels as usize >= MAX_STAGE_CHANNELS
+            || output_channels as usize >= MAX_STAGE_CHANNELS
+        {
+            let msg = format!(
+                "Invalid number of channels in stage ({}->{})",
+                input_channels, output_channels
+            );
+            signal_error(context_id, Level::Error, ErrorCode::Range, &msg);
+            return Err(msg);
+        }
+
+        Ok(Stage {
+            context_id,
+            r#type,
+            implements: r#type,
+            input_channels,
+            output_channels,
+            eval_ptr,
+            dup_elem_ptr,
+            free_ptr,
+            data,
+        })
+    }
+
+    /// Creates a stage holding one curve per channel. Passing no curves builds identities.
+    pub fn new_tone_curves(
+        context_id: &'static Context,
+        n_channels: u32,
+        curves: Option<&[ToneCurve]>,
+    ) -> Result<Stage> {
+        let the_curves = match curves {
+            Some(curves) => {
+                if curves.len() < n_channels as usize {
+                    let msg = format!(
+                        "Curve set needs {} curves, got {}",
+                        n_channels,
+                        curves.len()
+                    );
+                    signal_error(context_id, Level::Error, ErrorCode::Range, &msg);
+                    return Err(msg);
+                }
+
+                curves[..n_channels as usize].to_vec()
+            }
+            None => (0..n_channels)
+                .map(|_| ToneCurve::build_parametric(context_id, 1, &[1.0]))
+                .collect::<Result<Vec<_>>>()?,
+        };
+
+        let data = ToneCurvesData {
+            n_curves: n_channels,
+            the_curves: the_curves.into_boxed_slice(),
+        };
+
+        Self::alloc_placeholder(
+            context_id,
+            sig::mpe_stage::CURVE_SET,
+            n_channels,
+            n_channels,
+            evaluate_curves,
+            curve_set_dup,
+            generic_free,
+            Box::new(data),
+        )
+    }
+
+    /// Creates a matrix stage of `rows` outputs by `cols` inputs, with an optional offset.
+    pub fn new_matrix(
+        context_id: &'static Context,
+        rows: u32,
+        cols: u32,
+        matrix: &[f64],
+        offset: Option<&[f64]>,
+    ) -> Result<Stage> {
+        let n = rows as usize * cols as usize;
+
+        // Check for overflow
+        if n == 0 || matrix.len() < n || offset.is_some_and(|offset| offset.len() < rows as usize) {
+            let msg = "Invalid matrix size";
+            signal_error(context_id, Level::Error, ErrorCode::Range, msg);
+            return Err(msg.into());
+        }
+
+        let data = MatrixData {
+            double: matrix[..n].to_vec(),
+            offset: offset.map(|offset| offset[..rows as usize].to_vec()),
+        };
+
+        Self::alloc_placeholder(
+            context_id,
+            sig::mpe_stage::MATRIX,
+            cols,
+            rows,
+            evaluate_matrix,
+            matrix_elem_dup,
+            generic_free,
+            Box::new(data),
+        )
+    }
+
+    /// Creates a 16 bit CLUT with a different number of grid points on each input dimension.
+    /// Passing no table leaves all the nodes at zero.
+    pub fn new_clut_16bit_granular(
+        context_id: &'static Context,
+        clut_points: &[u32],
+        input_chan: u32,
+        output_chan: u32,
+        table: Option<&[u16]>,
+    ) -> Result<Stage> {
+        let n = clut_size(context_id, clut_points, input_chan, output_chan)?;
+
+        let mut tab = vec![0u16; n];
+        if let Some(table) = table {
+            copy_table(context_id, &mut tab, table)?;
+        }
+
+        let n_samples: Vec<usize> = clut_points[..input_chan as usize]
+            .iter()
+            .map(|&points| points as usize)
+            .collect();
+        let params = InterpParams::compute_ex(
+            context_id,
+            &n_samples,
+            input_chan as usize,
+            output_chan as usize,
+            &tab,
+            lerp_flag::U16_BITS,
+        )?;
+
+        let data = CLutData {
+            params,
+            n_entries: n as u32,
+        };
+
+        Self::alloc_placeholder(
+            context_id,
+            sig::mpe_stage::CLUT,
+            input_chan,
+            output_chan,
+            evaluate_clut_float_in_16,
+            clut_elem_dup::<u16>,
+            generic_free,
+            Box::new(data),
+        )
+    }
+
+    /// Creates a 16 bit CLUT with the same number of grid points on every input dimension.
+    pub fn new_clut_16bit(
+        context_id: &'static Context,
+        n_grid_points: u32,
+        input_chan: u32,
+        output_chan: u32,
+        table: Option<&[u16]>,
+    ) -> Result<Stage> {
+        let dimensions = [n_grid_points; MAX_INPUT_DIMENSIONS];
+
+        Self::new_clut_16bit_granular(
+            context_id,
+            &dimensions,
+            input_chan,
+            output_chan,
+            table,
+        )
+    }
+
+    /// Creates a floating point CLUT with a different number of grid points on each input dimension.
+    pub fn new_clut_float_granular(
+        context_id: &'static Context,
+        clut_points: &[u32],
+        input_chan: u32,
+        output_chan: u32,
+        table: Option<&[f32]>,
+    ) -> Result<Stage> {
+        let n = clut_size(context_id, clut_points, input_chan, output_chan)?;
+
+        let mut tab = vec![0f32; n];
+        if let Some(table) = table {
+            copy_table(context_id, &mut tab, table)?;
+        }
+
+        let n_samples: Vec<usize> = clut_points[..input_chan as usize]
+            .iter()
+            .map(|&points| points as usize)
+            .collect();
+        let params = InterpParams::compute_ex(
+            context_id,
+            &n_samples,
+            input_chan as usize,
+            output_chan as usize,
+            &tab,
+            lerp_flag::FLOAT,
+        )?;
+
+        let data = CLutData {
+            params,
+            n_entries: n as u32,
+        };
+
+        Self::alloc_placeholder(
+            context_id,
+            sig::mpe_stage::CLUT,
+            input_chan,
+            output_chan,
+            evaluate_clut_float,
+            clut_elem_dup::<f32>,
+            generic_free,
+            Box::new(data),
+        )
+    }
+
+    /// Creates a floating point CLUT with the same number of grid points on every input dimension.
+    pub fn new_clut_float(
+        context_id: &'static Context,
+        n_grid_points: u32,
+        input_chan: u32,
+        output_chan: u32,
+        table: Option<&[f32]>,
+    ) -> Result<Stage> {
+        let dimensions = [n_grid_points; MAX_INPUT_DIMENSIONS];
+
+        Self::new_clut_float_granular(
+            context_id,
+            &dimensions,
+            input_chan,
+            output_chan,
+            table,
+        )
+    }
+
+    pub fn r#type(&self) -> Signature {
+        self.r#type
+    }
+
+    pub fn implements(&self) -> Signature {
+        self.implements
+    }
+
+    pub fn input_channels(&self) -> u32 {
+        self.input_channels
+    }
+
+    pub fn output_channels(&self) -> u32 {
+        self.output_channels
+    }
+
+    /// Returns the data of the stage, if it is of the requested type.
+    pub fn data<T: Any>(&self) -> Option<&T> {
+        self.data.downcast_ref::<T>()
+    }
+
+    /// Evaluates the stage in floating point.
+    pub fn eval(&self, r#in: &[f32], out: &mut [f32]) {
+        (self.eval_ptr)(r#in, out, self)
+    }
+
+    /// Duplicates the stage, along with its data.
+    pub fn dup(&self) -> Result<Stage> {
+        (self.dup_elem_ptr)(self)
+    }
+
+    /// Walks all the nodes of a 16 bit CLUT, calling the sampler on each one. Unless
+    /// `SAMPLER_INSPECT` is set in `flags`, the sampler outputs are stored back in the table.
+    pub fn sample_clut_16bit(&mut self, sampler: Sampler16, cargo: &mut dyn Any, flags: u32) -> Result<()> {
+        let context_id = self.context_id;
+        let n_inputs = self.input_channels as usize;
+        let n_outputs = self.output_channels as usize;
+
+        let Some(clut) = self.data.downcast_mut::<CLutData<u16>>() else {
+            let msg = "Stage is not a 16 bit CLUT";
+            signal_error(context_id, Level::Error, ErrorCode::Internal, msg);
+            return Err(msg.into());
+        };
+
+        if n_inputs == 0 || n_inputs > MAX_INPUT_DIMENSIONS || n_outputs == 0 || n_outputs >= MAX_STAGE_CHANNELS {
+            let msg = format!("Invalid number of channels to sample ({} -> {})", n_inputs, n_outputs);
+            signal_error(context_id, Level::Error, ErrorCode::Range, &msg);
+            return Err(msg);
+        }
+
+        let n_samples = clut.params.n_samples;
+        let n_total_points = clut.params.table.len() / n_outputs;
+
+        let mut r#in = [0u16; MAX_INPUT_DIMENSIONS + 1];
+        let mut out = [0u16; MAX_STAGE_CHANNELS];
+
+        for (i, node) in clut.params.table.chunks_exact_mut(n_outputs).take(n_total_points).enumerate() {
+            let mut rest = i;
+            for t in (0..n_inputs).rev() {
+                let colorant = rest % n_samples[t];
+                rest /= n_samples[t];
+
+                r#in[t] = quantize_val(colorant as f64, n_samples[t]);
+            }
 
-    pub(crate) next: list::Link<Stage>,
+            out[..n_outputs].copy_from_slice(node);
+
+            if !sampler(&r#in, &mut out, cargo) {
+                let msg = "CLUT sampler failed";
+                signal_error(context_id, Level::Error, ErrorCode::Internal, msg);
+                return Err(msg.into());
+            }
+
+            if flags & SAMPLER_INSPECT == 0 {
+                node.copy_from_slice(&out[..n_outputs]);
+            }
+        }
+
+        Ok(())
+    }
 }
 
-mod tone_curve;
-mod matrix;
-mod clut;
+// Number of nodes in a CLUT, checking for overflow
+fn clut_size(
+    context_id: &'static Context,
+    clut_points: &[u32],
+    input_chan: u32,
+    output_chan: u32,
+) -> Result<usize> {
+    if input_chan as usize > MAX_INPUT_DIMENSIONS {
+        let msg = format!(
+            "Too many input channels ({} channels, max={})",
+            input_chan, MAX_INPUT_DIMENSIONS
+        );
+        signal_error(context_id, Level::Error, ErrorCode::Range, &msg);
+        return Err(msg);
+    }
 
-use std::any::Any;
+    if clut_points.len() < input_chan as usize {
+        let msg = "Missing grid points for CLUT";
+        signal_error(context_id, Level::Error, ErrorCode::Range, msg);
+        return Err(msg.into());
+    }
 
-pub use tone_curve::ToneCurvesData;
-pub use matrix::MatrixData;
-pub use clut::{CLutData, Tab};
+    let n = cube_size(&clut_points[..input_chan as usize])
+        .and_then(|size| size.checked_mul(output_chan as usize));
+
+    match n {
+        Some(n) if n > 0 => Ok(n),
+        _ => {
+            let msg = "Invalid CLUT size";
+            signal_error(context_id, Level::Error, ErrorCode::Range, msg);
+            Err(msg.into())
+        }
+    }
+}
+
+// Given an hypercube of b dimensions, with Dims[] number of nodes by dimension, calculate the total amount of nodes
+pub(crate) fn cube_size(dims: &[u32]) -> Option<usize> {
+    let mut rv = 1u32;
+
+    for &dim in dims.iter().rev() {
+        // Error
+        if dim <= 1 {
+            return None;
+        }
+
+        rv = rv.checked_mul(dim)?;
+    }
+
+    // Again, prevent overflow
+    if rv > u32::MAX / 15 {
+        return None;
+    }
+
+    Some(rv as usize)
+}
+
+fn copy_table<T: Copy>(context_id: &'static Context, tab: &mut [T], table: &[T]) -> Result<()> {
+    if table.len() < tab.len() {
+        let msg = format!(
+            "CLUT table too small ({} entries, needs {})",
+            table.len(),
+            tab.len()
+        );
+        signal_error(context_id, Level::Error, ErrorCode::Range, &msg);
+        return Err(msg);
+    }
+
+    tab.copy_from_slice(&table[..tab.len()]);
+    Ok(())
+}
+
+fn generic_free(mpe: Stage) {
+    drop(mpe);
+}
+
+fn duplicate_with_data<T: Any + Clone>(mpe: &Stage) -> Result<Stage> {
+    let Some(data) = mpe.data::<T>() else {
+        return Err("Invalid stage data to duplicate".into());
+    };
+
+    Ok(Stage {
+        context_id: mpe.context_id,
+        r#type: mpe.r#type,
+        implements: mpe.implements,
+        input_channels: mpe.input_channels,
+        output_channels: mpe.output_channels,
+        eval_ptr: mpe.eval_ptr,
+        dup_elem_ptr: mpe.dup_elem_ptr,
+        free_ptr: mpe.free_ptr,
+        data: Box::new(data.clone()),
+    })
+}
 
-use crate::{types::Signature, list};
+fn curve_set_dup(mpe: &Stage) -> Result<Stage> {
+    duplicate_with_data::<ToneCurvesData>(mpe)
+}
+
+fn matrix_elem_dup(mpe: &Stage) -> Result<Stage> {
+    duplicate_with_data::<MatrixData>(mpe)
+}
+
+fn clut_elem_dup<T: Copy + 'static>(mpe: &Stage) -> Result<Stage> {
+    duplicate_with_data::<CLutData<T>>(mpe)
+}
+
+fn evaluate_curves(r#in: &[f32], out: &mut [f32], mpe: &Stage) {
+    let Some(data) = mpe.data::<ToneCurvesData>() else {
+        return;
+    };
+
+    for (i, curve) in data.the_curves.iter().enumerate() {
+        out[i] = curve.eval_f32(r#in[i]);
+    }
+}
+
+fn evaluate_matrix(r#in: &[f32], out: &mut [f32], mpe: &Stage) {
+    let Some(data) = mpe.data::<MatrixData>() else {
+        return;
+    };
+
+    let n_in = mpe.input_channels as usize;
+
+    // Input is already in 0..1.0 notation
+    for i in 0..mpe.output_channels as usize {
+        let row = &data.double[i * n_in..(i + 1) * n_in];
+        let mut tmp: f64 = r#in.iter().zip(row).map(|(x, m)| *x as f64 * m).sum();
+
+        if let Some(offset) = &data.offset {
+            tmp += offset[i];
+        }
+
+        out[i] = tmp as f32;
+    }
+
+    // Output in 0..1.0 domain
+}
+
+// Convert to 16 bits, evaluate, and back to floating point
+fn evaluate_clut_float_in_16(r#in: &[f32], out: &mut [f32], mpe: &Stage) {
+    let Some(data) = mpe.data::<CLutData<u16>>() else {
+        return;
+    };
+
+    let mut in16 = [0u16; MAX_STAGE_CHANNELS];
+    let mut out16 = [0u16; MAX_STAGE_CHANNELS];
+
+    for (i, v) in r#in[..mpe.input_channels as usize].iter().enumerate() {
+        in16[i] = quick_saturate_word(*v as f64 * 65535.0);
+    }
+
+    if let InterpFunction::U16(lerp) = data.params.interpolation {
+        lerp(&in16, &mut out16, &data.params);
+    }
+
+    for (i, v) in out16[..mpe.output_channels as usize].iter().enumerate() {
+        out[i] = *v as f32 / 65535.0;
+    }
+}
+
+// Does the floating point interpolation
+fn evaluate_clut_float(r#in: &[f32], out: &mut [f32], mpe: &Stage) {
+    let Some(data) = mpe.data::<CLutData<f32>>() else {
+        return;
+    };
+
+    if let InterpFunction::F32(lerp) = data.params.interpolation {
+        lerp(r#in, out, &data.params);
+    }
+}
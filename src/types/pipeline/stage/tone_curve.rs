@@ -1,6 +1,7 @@
 use crate::types::ToneCurve;
 
-pub struct ToneCurvesData<'a> {
+#[derive(Clone)]
+pub struct ToneCurvesData {
     pub n_curves: u32,
-    pub the_curves: Box<[&'a ToneCurve<'a>]>,
+    pub the_curves: Box<[ToneCurve]>,
 }
@@ -1,9 +1,25 @@
-use std::any::Any;
+use std::{any::Any, mem::size_of};
 
 use chrono::{DateTime as dt, Utc};
 
-use super::{DateTime, EncodedXYZ, ProfileID, Signature};
-use crate::{Context, io::IoHandler, plugin::{IMutex, TagTypeHandler}};
+use super::{DateTime, EncodedXYZ, ProfileID, Signature, XYZ};
+use crate::{
+    io::IoHandler,
+    plugin::{
+        read_signature, read_u16, read_u32, read_u64, write_signature, write_u16, write_u32,
+        write_u64, IMutex, TagTypeHandler,
+    },
+    cms::D50,
+    Context, Result, DEFAULT_CONTEXT,
+};
+
+mod header;
+mod id;
+mod lut;
+mod open;
+mod save;
+mod tag;
+mod virt;
 
 pub struct Header {
     pub size: u32,
@@ -29,19 +45,20 @@ pub struct Header {
 pub struct Profile<'mtx, 'a, 'b> {
     pub context_id: &'static Context,
     pub io_handler: IoHandler,
-    pub created: dt<Utc>,
+    pub(crate) created: dt<Utc>,
 
-    pub version: u32,
-    pub device_class: Signature,
-    pub color_space: Signature,
-    pub pcs: Signature,
-    pub rendering_intent: u32,
+    pub(crate) version: u32,
+    pub(crate) device_class: Signature,
+    pub(crate) color_space: Signature,
+    pub(crate) pcs: Signature,
+    pub(crate) rendering_intent: u32,
 
-    pub flags: u32,
+    pub(crate) flags: u32,
     pub manufacturer: u32,
     pub model: u32,
-    pub attributes: u64,
+    pub(crate) attributes: u64,
     pub creator: u32,
+    pub(crate) illuminant: XYZ,
 
     pub profile_id: ProfileID,
 
@@ -59,7 +76,186 @@ pub struct TagEntry<'a> {
     pub offset: usize,
     pub save_as_raw: bool,
     pub tag_object: Option<Box<dyn Any>>,
-    pub type_handler: &'a TagTypeHandler
+    pub type_handler: Option<&'a TagTypeHandler>,
+}
+
+impl Header {
+    pub(crate) fn read(io: &mut IoHandler) -> Result<Header> {
+        fn read_err() -> Result<Header> {
+            Err("Read error in Header::read".into())
+        }
+
+        let (Ok(size), Ok(cmm_id), Ok(version), Ok(device_class), Ok(color_space), Ok(pcs)) = (
+            read_u32(io),
+            read_signature(io),
+            read_u32(io),
+            read_signature(io),
+            read_signature(io),
+            read_signature(io),
+        ) else {
+            return read_err();
+        };
+
+        let (Ok(year), Ok(month), Ok(day), Ok(hours), Ok(minutes), Ok(seconds)) = (
+            read_u16(io),
+            read_u16(io),
+            read_u16(io),
+            read_u16(io),
+            read_u16(io),
+            read_u16(io),
+        ) else {
+            return read_err();
+        };
+
+        let (Ok(magic), Ok(platform), Ok(flags), Ok(manufacturer), Ok(model), Ok(attributes)) = (
+            read_signature(io),
+            read_signature(io),
+            read_u32(io),
+            read_signature(io),
+            read_u32(io),
+            read_u64(io),
+        ) else {
+            return read_err();
+        };
+
+        let (Ok(rendering_intent), Ok(x), Ok(y), Ok(z), Ok(creator)) = (
+            read_u32(io),
+            read_u32(io),
+            read_u32(io),
+            read_u32(io),
+            read_signature(io),
+        ) else {
+            return read_err();
+        };
+
+        let mut id8 = [0u8; 16];
+        if (io.read)(io, &mut id8, size_of::<u8>(), 16) != 16 {
+            return read_err();
+        }
+
+        let mut reserved = [0u8; 28];
+        if (io.read)(io, &mut reserved, size_of::<u8>(), 28) != 28 {
+            return read_err();
+        }
+
+        Ok(Header {
+            size,
+            cmm_id,
+            version,
+            device_class,
+            color_space,
+            pcs,
+            date: DateTime {
+                year,
+                month,
+                day,
+                hours,
+                minutes,
+                seconds,
+            },
+            magic,
+            platform,
+            flags,
+            manufacturer,
+            model,
+            attributes,
+            rendering_intent,
+            illuminant: EncodedXYZ {
+                x: x as i32,
+                y: y as i32,
+                z: z as i32,
+            },
+            creator,
+            profile_id: ProfileID { id8 },
+            reserved: reserved.map(|b| b as i8),
+        })
+    }
+
+    pub(crate) fn write(&self, io: &mut IoHandler) -> Result<()> {
+        fn write_err() -> Result<()> {
+            Err("Write error in Header::write".into())
+        }
+
+        let date = &self.date;
+        if write_u32(io, self.size).is_err()
+            || write_signature(io, self.cmm_id).is_err()
+            || write_u32(io, self.version).is_err()
+            || write_signature(io, self.device_class).is_err()
+            || write_signature(io, self.color_space).is_err()
+            || write_signature(io, self.pcs).is_err()
+            || write_u16(io, date.year).is_err()
+            || write_u16(io, date.month).is_err()
+            || write_u16(io, date.day).is_err()
+            || write_u16(io, date.hours).is_err()
+            || write_u16(io, date.minutes).is_err()
+            || write_u16(io, date.seconds).is_err()
+            || write_signature(io, self.magic).is_err()
+            || write_signature(io, self.platform).is_err()
+            || write_u32(io, self.flags).is_err()
+            || write_signature(io, self.manufacturer).is_err()
+            || write_u32(io, self.model).is_err()
+            || write_u64(io, self.attributes).is_err()
+            || write_u32(io, self.rendering_intent).is_err()
+            || write_u32(io, self.illuminant.x as u32).is_err()
+            || write_u32(io, self.illuminant.y as u32).is_err()
+            || write_u32(io, self.illuminant.z as u32).is_err()
+            || write_signature(io, self.creator).is_err()
+        {
+            return write_err();
+        }
+
+        // Profile ID is always big endian
+        let id8 = unsafe { self.profile_id.id8 };
+        if !(io.write)(io, id8.len(), &id8) {
+            return write_err();
+        }
+
+        let reserved = self.reserved.map(|b| b as u8);
+        if !(io.write)(io, reserved.len(), &reserved) {
+            return write_err();
+        }
+
+        Ok(())
+    }
+}
+
+impl<'mtx, 'a, 'b> Profile<'mtx, 'a, 'b> {
+    /// Creates an empty profile with no tags, ready to be filled in.
+    pub fn new() -> Self {
+        let context_id = &*DEFAULT_CONTEXT;
+
+        Profile {
+            context_id,
+            io_handler: IoHandler::null(context_id),
+            created: Utc::now(),
+            version: 0x04300000,
+            device_class: Signature(0),
+            color_space: Signature(0),
+            pcs: Signature(0),
+            rendering_intent: 0,
+            flags: 0,
+            manufacturer: 0,
+            model: 0,
+            attributes: 0,
+            creator: 0,
+            illuminant: D50,
+            profile_id: ProfileID { id8: [0; 16] },
+            tags: Vec::new(),
+            is_write: false,
+            user_mutex: None,
+        }
+    }
+
+    /// Returns the profile version as a decimal number, such as 4.3
+    pub(crate) fn icc_version(&self) -> f64 {
+        base_to_base(self.version >> 16, 16, 10) as f64 / 100.0
+    }
+}
+
+impl<'mtx, 'a, 'b> Default for Profile<'mtx, 'a, 'b> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 pub mod data_access {
@@ -81,3 +277,16 @@ pub mod info_type {
     pub const MODEL: u32 = 2;
     pub const COPYRIGHT: u32 = 3;
 }
+
+/// Converts the digits of a number from one base to another, as used by the BCD version field.
+fn base_to_base(n: u32, base_in: u32, base_out: u32) -> u32 {
+    let mut digits = Vec::new();
+    let mut n = n;
+
+    while n > 0 {
+        digits.push(n % base_in);
+        n /= base_in;
+    }
+
+    digits.iter().rev().fold(0, |out, digit| out * base_out + digit)
+}
@@ -0,0 +1,301 @@
+use chrono::{DateTime as dt, Datelike, SubsecRound, Utc};
+use log::Level;
+
+use crate::{
+    intent,
+    sig::{class, colorspace},
+    signal_error,
+    state::ErrorCode,
+    types::{Signature, XYZ},
+    Result,
+};
+
+use super::{base_to_base, data_access, Profile};
+
+const DEVICE_CLASSES: [Signature; 7] = [
+    class::INPUT,
+    class::DISPLAY,
+    class::OUTPUT,
+    class::LINK,
+    class::ABSTRACT,
+    class::COLOR_SPACE,
+    class::NAMED_COLOR,
+];
+
+const COLOR_SPACES: [Signature; 43] = [
+    colorspace::XYZ,
+    colorspace::LAB,
+    colorspace::LUV,
+    colorspace::YCBCR,
+    colorspace::YXY,
+    colorspace::RGB,
+    colorspace::GRAY,
+    colorspace::HSV,
+    colorspace::HLS,
+    colorspace::CMYK,
+    colorspace::CMY,
+    colorspace::MCH1,
+    colorspace::MCH2,
+    colorspace::MCH3,
+    colorspace::MCH4,
+    colorspace::MCH5,
+    colorspace::MCH6,
+    colorspace::MCH7,
+    colorspace::MCH8,
+    colorspace::MCH9,
+    colorspace::MCHA,
+    colorspace::MCHB,
+    colorspace::MCHC,
+    colorspace::MCHD,
+    colorspace::MCHE,
+    colorspace::MCHF,
+    colorspace::NAMED,
+    colorspace::COLOR1,
+    colorspace::COLOR2,
+    colorspace::COLOR3,
+    colorspace::COLOR4,
+    colorspace::COLOR5,
+    colorspace::COLOR6,
+    colorspace::COLOR7,
+    colorspace::COLOR8,
+    colorspace::COLOR9,
+    colorspace::COLOR10,
+    colorspace::COLOR11,
+    colorspace::COLOR12,
+    colorspace::COLOR13,
+    colorspace::COLOR14,
+    colorspace::COLOR15,
+    colorspace::LUVK,
+];
+
+// Bits 4 to 31 of the device attributes are reserved by the ICC and must be zero
+const RESERVED_ATTRIBUTES: u64 = 0xFFFF_FFF0;
+
+impl<'mtx, 'a, 'b> Profile<'mtx, 'a, 'b> {
+    pub(crate) fn range_error<T>(&self, msg: String) -> Result<T> {
+        signal_error(self.context_id, Level::Error, ErrorCode::Range, &msg);
+        Err(msg)
+    }
+
+    pub(crate) fn colorspace_error<T>(&self, msg: String) -> Result<T> {
+        signal_error(self.context_id, Level::Error, ErrorCode::ColorspaceCheck, &msg);
+        Err(msg)
+    }
+
+    /// Returns the profile version as a decimal number, such as 4.3
+    pub fn version(&self) -> f64 {
+        self.icc_version()
+    }
+
+    /// Sets the profile version, such as 2.1 or 4.4. It is stored in BCD, so only one digit
+    /// is available for the major version and two for the minor one.
+    pub fn set_version(&mut self, version: f64) -> Result<()> {
+        let n = (version * 100.0 + 0.5).floor();
+        if !(100.0..=999.0).contains(&n) {
+            return self.range_error(format!("Invalid profile version '{}'", version));
+        }
+
+        self.version = base_to_base(n as u32, 10, 16) << 16;
+        Ok(())
+    }
+
+    pub fn device_class(&self) -> Signature {
+        self.device_class
+    }
+
+    /// Sets the device class, which must be one of `sig::class::*`.
+    pub fn set_device_class(&mut self, device_class: Signature) -> Result<()> {
+        if !DEVICE_CLASSES.contains(&device_class) {
+            return self.range_error(format!("Invalid device class '{:x}'", device_class.0));
+        }
+
+        self.device_class = device_class;
+        Ok(())
+    }
+
+    pub fn color_space(&self) -> Signature {
+        self.color_space
+    }
+
+    /// Sets the data color space, which must be one of `sig::colorspace::*`.
+    pub fn set_color_space(&mut self, color_space: Signature) -> Result<()> {
+        if !COLOR_SPACES.contains(&color_space) {
+            return self.range_error(format!("Invalid color space '{:x}'", color_space.0));
+        }
+
+        self.color_space = color_space;
+        Ok(())
+    }
+
+    pub fn pcs(&self) -> Signature {
+        self.pcs
+    }
+
+    /// Sets the connection space. Only XYZ and Lab are allowed, except on device links, where
+    /// this is the output color space. Set the device class first.
+    pub fn set_pcs(&mut self, pcs: Signature) -> Result<()> {
+        let valid = if self.device_class == class::LINK {
+            COLOR_SPACES.contains(&pcs)
+        } else {
+            pcs == colorspace::XYZ || pcs == colorspace::LAB
+        };
+
+        if !valid {
+            return self.range_error(format!("Invalid connection space '{:x}'", pcs.0));
+        }
+
+        self.pcs = pcs;
+        Ok(())
+    }
+
+    pub fn rendering_intent(&self) -> u32 {
+        self.rendering_intent
+    }
+
+    /// Sets the rendering intent of the header. Only the ICC intents are allowed.
+    pub fn set_rendering_intent(&mut self, rendering_intent: u32) -> Result<()> {
+        if rendering_intent > intent::ABSOLUTE_COLORIMETRIC {
+            return self.range_error(format!("Invalid rendering intent '{}'", rendering_intent));
+        }
+
+        self.rendering_intent = rendering_intent;
+        Ok(())
+    }
+
+    /// Whether the profile is embedded in a file.
+    pub fn is_embedded(&self) -> bool {
+        self.flags & data_access::EMBEDDED_PROFILE_TRUE != 0
+    }
+
+    pub fn set_embedded(&mut self, embedded: bool) {
+        if embedded {
+            self.flags |= data_access::EMBEDDED_PROFILE_TRUE;
+        } else {
+            self.flags &= !data_access::EMBEDDED_PROFILE_TRUE;
+        }
+    }
+
+    /// Whether the profile can only be used along with the embedded color data.
+    pub fn is_dependent(&self) -> bool {
+        self.flags & data_access::USE_WITH_EMBEDDED_DATA_ONLY != 0
+    }
+
+    pub fn set_dependent(&mut self, dependent: bool) {
+        if dependent {
+            self.flags |= data_access::USE_WITH_EMBEDDED_DATA_ONLY;
+        } else {
+            self.flags &= !data_access::USE_WITH_EMBEDDED_DATA_ONLY;
+        }
+    }
+
+    /// Returns the device attributes, as a combination of `device_attribute::*`.
+    pub fn device_attributes(&self) -> u64 {
+        self.attributes
+    }
+
+    /// Sets the device attributes. The upper 32 bits are free for vendors, but the ICC reserved
+    /// bits must be zero.
+    pub fn set_device_attributes(&mut self, attributes: u64) -> Result<()> {
+        if attributes & RESERVED_ATTRIBUTES != 0 {
+            return self.range_error(format!("Invalid device attributes '{:x}'", attributes));
+        }
+
+        self.attributes = attributes;
+        Ok(())
+    }
+
+    pub fn creation_date(&self) -> dt<Utc> {
+        self.created
+    }
+
+    /// Sets the creation date. Fractions of second are dropped, as the header has no room for
+    /// them. Years out of the 16 bit range are rejected.
+    pub fn set_creation_date(&mut self, date: dt<Utc>) -> Result<()> {
+        if u16::try_from(date.year()).is_err() {
+            return self.range_error(format!("Invalid creation date '{}'", date));
+        }
+
+        self.created = date.trunc_subsecs(0);
+        Ok(())
+    }
+
+    /// Returns the illuminant of the connection space. Always D50 on conforming profiles.
+    pub fn illuminant(&self) -> XYZ {
+        self.illuminant
+    }
+
+    pub fn set_illuminant(&mut self, illuminant: XYZ) -> Result<()> {
+        // Must fit in s15Fixed16, and be a physically meaningful white
+        let valid = [illuminant.x, illuminant.y, illuminant.z]
+            .iter()
+            .all(|v| (0.0..32767.0).contains(v))
+            && illuminant.y > 0.0;
+
+        if !valid {
+            return self.range_error(format!(
+                "Invalid illuminant '{} {} {}'",
+                illuminant.x, illuminant.y, illuminant.z
+            ));
+        }
+
+        self.illuminant = illuminant;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Timelike, Utc};
+
+    use crate::{
+        sig,
+        types::{Profile, Signature, XYZ},
+    };
+
+    #[test]
+    fn creation_date_survives_save_and_open() {
+        let date = Utc.with_ymd_and_hms(2024, 2, 29, 23, 59, 58).unwrap().with_nanosecond(123_456_789).unwrap();
+
+        let mut profile = Profile::new();
+        profile.set_creation_date(date).unwrap();
+        assert_eq!(profile.creation_date().nanosecond(), 0);
+
+        let mem = profile.save_to_mem().unwrap();
+        let reopened = Profile::open_mem(&mem).unwrap();
+        assert_eq!(reopened.creation_date(), profile.creation_date());
+    }
+
+    #[test]
+    fn illuminant_is_validated() {
+        let mut profile = Profile::new();
+        let d65 = XYZ { x: 0.9505, y: 1.0, z: 1.089 };
+
+        assert!(profile.set_illuminant(XYZ { x: 0.9, y: 0.0, z: 0.8 }).is_err());
+        profile.set_illuminant(d65).unwrap();
+
+        let mem = profile.save_to_mem().unwrap();
+        let reopened = Profile::open_mem(&mem).unwrap();
+        assert!((reopened.illuminant().x - d65.x).abs() < 1e-4);
+        assert!((reopened.illuminant().z - d65.z).abs() < 1e-4);
+    }
+
+    #[test]
+    fn setters_keep_header_valid() {
+        let mut profile = Profile::new();
+
+        assert!(profile.set_version(12.0).is_err());
+        assert!(profile.set_device_class(Signature(0xdead)).is_err());
+        assert!(profile.set_pcs(sig::colorspace::CMYK).is_err());
+        assert!(profile.set_rendering_intent(4).is_err());
+
+        profile.set_version(2.1).unwrap();
+        profile.set_device_class(sig::class::OUTPUT).unwrap();
+        profile.set_color_space(sig::colorspace::CMYK).unwrap();
+
+        let mem = profile.save_to_mem().unwrap();
+        let reopened = Profile::open_mem(&mem).unwrap();
+        assert!((reopened.version() - 2.1).abs() < 1e-9);
+        assert!(reopened.device_class() == sig::class::OUTPUT);
+        assert!(reopened.color_space() == sig::colorspace::CMYK);
+    }
+}
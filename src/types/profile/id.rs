@@ -0,0 +1,111 @@
+use log::Level;
+
+use crate::{md5::Md5, signal_error, state::ErrorCode, types::ProfileID, Result};
+
+use super::Profile;
+
+// Byte ranges of the header fields that are set to zero before computing the ID (ICC.1 7.2.18)
+const FLAGS: std::ops::Range<usize> = 44..48;
+const RENDERING_INTENT: std::ops::Range<usize> = 64..68;
+const PROFILE_ID: std::ops::Range<usize> = 84..100;
+
+impl<'mtx, 'a, 'b> Profile<'mtx, 'a, 'b> {
+    /// Computes the MD5 fingerprint of the profile and stores it as the profile ID.
+    pub fn compute_md5(&mut self) -> Result<()> {
+        let mut mem = self.save_to_mem()?;
+
+        self.profile_id = ProfileID {
+            id8: md5_of_profile(&mut mem),
+        };
+
+        Ok(())
+    }
+
+    /// Checks whether the stored profile ID matches the bytes the profile was read from.
+    /// Profiles without an ID (all zeros) never match.
+    pub fn verify_id(&mut self) -> Result<bool> {
+        let id = unsafe { self.profile_id.id8 };
+        if id == [0; 16] {
+            return Ok(false);
+        }
+
+        let io = &mut self.io_handler;
+        if io.reported_size == 0 {
+            signal_error(
+                io.context_id,
+                Level::Error,
+                ErrorCode::NotSuitable,
+                "Profile was not read from a stream",
+            );
+            return Err("Profile was not read from a stream in Profile::verify_id".into());
+        }
+
+        // The header tells how many bytes the profile takes
+        let mut size = [0u8; 4];
+        if !(io.seek)(io, 0) || (io.read)(io, &mut size, 4, 1) != 4 {
+            return Err("Read error in Profile::verify_id".into());
+        }
+        let size = (u32::from_be_bytes(size) as usize).min(io.reported_size);
+        if size < PROFILE_ID.end {
+            return Ok(false);
+        }
+
+        let mut mem = vec![0u8; size];
+        if !(io.seek)(io, 0) || (io.read)(io, &mut mem, size, 1) != size {
+            return Err("Read error in Profile::verify_id".into());
+        }
+
+        Ok(md5_of_profile(&mut mem) == id)
+    }
+}
+
+// Profile flags, rendering intent and ID are not part of the fingerprint
+fn md5_of_profile(mem: &mut [u8]) -> [u8; 16] {
+    mem[FLAGS].fill(0);
+    mem[RENDERING_INTENT].fill(0);
+    mem[PROFILE_ID].fill(0);
+
+    let mut md5 = Md5::new();
+    md5.add(mem);
+    md5.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::Profile;
+
+    #[test]
+    fn computed_id_survives_save_and_open() {
+        let mut profile = Profile::new_srgb().unwrap();
+        profile.compute_md5().unwrap();
+        let id = unsafe { profile.profile_id.id8 };
+        assert_ne!(id, [0; 16]);
+
+        let mem = profile.save_to_mem().unwrap();
+        let mut reopened = Profile::open_mem(&mem).unwrap();
+        assert_eq!(unsafe { reopened.profile_id.id8 }, id);
+        assert!(reopened.verify_id().unwrap());
+    }
+
+    #[test]
+    fn tampered_profile_fails_verification() {
+        let mut profile = Profile::new_srgb().unwrap();
+        profile.compute_md5().unwrap();
+
+        let mut mem = profile.save_to_mem().unwrap();
+        let last = mem.len() - 1;
+        mem[last] ^= 0xff;
+
+        let mut reopened = Profile::open_mem(&mem).unwrap();
+        assert!(!reopened.verify_id().unwrap());
+    }
+
+    #[test]
+    fn profile_without_id_does_not_verify() {
+        let mut profile = Profile::new_srgb().unwrap();
+        let mem = profile.save_to_mem().unwrap();
+
+        let mut reopened = Profile::open_mem(&mem).unwrap();
+        assert!(!reopened.verify_id().unwrap());
+    }
+}
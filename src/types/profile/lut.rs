@@ -0,0 +1,140 @@
+use crate::{
+    cms::D50,
+    sig,
+    types::{Pipeline, Stage, StageLoc, ToneCurve},
+    Result, MAX_ENCODEABLE_XYZ,
+};
+
+use super::Profile;
+
+// Gray input and output work on the 16 bit PCS encoding, where XYZ 1.0 is 0x8000
+const INP_ADJ: f64 = 1.0 / MAX_ENCODEABLE_XYZ;
+const OUTP_ADJ: f64 = MAX_ENCODEABLE_XYZ;
+
+const GRAY_INPUT_MATRIX: [f64; 3] = [INP_ADJ * D50.x, INP_ADJ * D50.y, INP_ADJ * D50.z];
+const ONE_TO_THREE_INPUT_MATRIX: [f64; 3] = [1.0, 1.0, 1.0];
+const PICK_Y_MATRIX: [f64; 3] = [0.0, OUTP_ADJ * D50.y, 0.0];
+const PICK_LSTAR_MATRIX: [f64; 3] = [1.0, 0.0, 0.0];
+
+impl<'mtx, 'a, 'b> Profile<'mtx, 'a, 'b> {
+    /// Builds the device to PCS pipeline of a gray profile: the gray curve scaled across the PCS
+    /// illuminant. No CLUT is involved.
+    pub fn gray_input_pipeline(&mut self) -> Result<Pipeline> {
+        let context_id = self.context_id;
+        let pcs = self.pcs;
+        let gray_trc = self.read_tag::<ToneCurve>(sig::tags::GRAY_TRC)?.clone();
+
+        let mut lut = Pipeline::new(context_id, 1, 3)?;
+
+        if pcs == sig::colorspace::LAB {
+            // In this case we implement the profile as an identity matrix plus 3 tone curves
+            let empty_tab = ToneCurve::build_tabulated_16(context_id, &[0x8080, 0x8080])?;
+            let lab_curves = [gray_trc, empty_tab.clone(), empty_tab];
+
+            lut.insert_stage(
+                StageLoc::AtEnd,
+                Stage::new_matrix(context_id, 3, 1, &ONE_TO_THREE_INPUT_MATRIX, None)?,
+            )?;
+            lut.insert_stage(
+                StageLoc::AtEnd,
+                Stage::new_tone_curves(context_id, 3, Some(&lab_curves))?,
+            )?;
+        } else {
+            lut.insert_stage(
+                StageLoc::AtEnd,
+                Stage::new_tone_curves(context_id, 1, Some(&[gray_trc]))?,
+            )?;
+            lut.insert_stage(
+                StageLoc::AtEnd,
+                Stage::new_matrix(context_id, 3, 1, &GRAY_INPUT_MATRIX, None)?,
+            )?;
+        }
+
+        Ok(lut)
+    }
+
+    /// Builds the PCS to device pipeline of a gray profile: picks the lightness and applies the
+    /// inverse of the gray curve. No CLUT is involved.
+    pub fn gray_output_pipeline(&mut self) -> Result<Pipeline> {
+        let context_id = self.context_id;
+        let pcs = self.pcs;
+        let rev_gray_trc = self.read_tag::<ToneCurve>(sig::tags::GRAY_TRC)?.reverse()?;
+
+        let mut lut = Pipeline::new(context_id, 3, 1)?;
+
+        let pick = if pcs == sig::colorspace::LAB {
+            &PICK_LSTAR_MATRIX
+        } else {
+            &PICK_Y_MATRIX
+        };
+
+        lut.insert_stage(
+            StageLoc::AtEnd,
+            Stage::new_matrix(context_id, 1, 3, pick, None)?,
+        )?;
+        lut.insert_stage(
+            StageLoc::AtEnd,
+            Stage::new_tone_curves(context_id, 1, Some(&[rev_gray_trc]))?,
+        )?;
+
+        Ok(lut)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        sig,
+        types::{Profile, ToneCurve, XYY},
+        DEFAULT_CONTEXT,
+    };
+
+    fn gray_profile() -> Profile<'static, 'static, 'static> {
+        let white_point = XYY { x: 0.3127, y: 0.3290, y_lum: 1.0 };
+        let curve = ToneCurve::build_parametric(&DEFAULT_CONTEXT, 1, &[2.2]).unwrap();
+
+        Profile::new_gray(white_point, curve).unwrap()
+    }
+
+    #[test]
+    fn gray_pipelines_round_trip() {
+        for pcs in [sig::colorspace::XYZ, sig::colorspace::LAB] {
+            let mut profile = gray_profile();
+            profile.set_pcs(pcs).unwrap();
+
+            let input = profile.gray_input_pipeline().unwrap();
+            let output = profile.gray_output_pipeline().unwrap();
+            assert_eq!((input.input_channels(), input.output_channels()), (1, 3));
+            assert_eq!((output.input_channels(), output.output_channels()), (3, 1));
+
+            for gray in [0.0f32, 0.2, 0.5, 0.8, 1.0] {
+                let mut pcs_value = [0.0; 3];
+                let mut back = [0.0];
+
+                input.eval_float(&[gray], &mut pcs_value);
+                output.eval_float(&pcs_value, &mut back);
+
+                assert!((back[0] - gray).abs() < 1e-3, "{} != {}", back[0], gray);
+            }
+        }
+    }
+
+    #[test]
+    fn gray_input_on_lab_is_neutral() {
+        let mut profile = gray_profile();
+        profile.set_pcs(sig::colorspace::LAB).unwrap();
+
+        let input = profile.gray_input_pipeline().unwrap();
+
+        let mut lab = [0u16; 3];
+        input.eval_16(&[0xffff], &mut lab);
+        assert_eq!(lab, [0xffff, 0x8080, 0x8080]);
+    }
+
+    #[test]
+    fn missing_gray_trc_is_an_error() {
+        let mut profile = Profile::new();
+        assert!(profile.gray_input_pipeline().is_err());
+        assert!(profile.gray_output_pipeline().is_err());
+    }
+}
@@ -0,0 +1,273 @@
+use std::{
+    io::{Read, Seek},
+    path::Path,
+};
+
+use log::Level;
+
+use crate::{
+    io::IoHandler,
+    plugin::{
+        decode_date_time, get_tag_descriptor, read_signature, read_u32, s15_fixed16_to_f64,
+        TagDescriptor,
+    },
+    sig, signal_error,
+    state::ErrorCode,
+    types::{tag::directory::Entry, XYZ},
+    Result, DEFAULT_CONTEXT, MAX_TABLE_TAG,
+};
+
+use super::{Header, Profile, TagEntry};
+
+impl<'mtx, 'a, 'b> Profile<'mtx, 'a, 'b> {
+    /// Opens a profile stored in a file.
+    pub fn open_file(file_name: impl AsRef<Path>) -> Result<Self> {
+        let io = IoHandler::from_file(&DEFAULT_CONTEXT, file_name, 'r')?;
+
+        Self::open_io(io)
+    }
+
+    /// Opens a profile from a block of memory. The data is copied, so the block can be dropped afterwards.
+    pub fn open_mem(mem: &[u8]) -> Result<Self> {
+        let io = IoHandler::from_mem(&DEFAULT_CONTEXT, mem);
+
+        Self::open_io(io)
+    }
+
+    /// Opens a profile from any seekable source.
+    pub fn open_stream(stream: impl Read + Seek + 'static) -> Result<Self> {
+        let io = IoHandler::from_reader(&DEFAULT_CONTEXT, stream)?;
+
+        Self::open_io(io)
+    }
+
+    /// Opens a profile from an already set up IO handler. Tags are read on demand later on.
+    pub fn open_io(io: IoHandler) -> Result<Self> {
+        let mut profile = Self::new();
+        profile.context_id = io.context_id;
+        profile.io_handler = io;
+
+        profile.read_header()?;
+
+        Ok(profile)
+    }
+
+    /// Reads the header and the tag directory. The tag contents are left untouched.
+    fn read_header(&mut self) -> Result<()> {
+        let context_id = self.context_id;
+        let io = &mut self.io_handler;
+
+        let header = Header::read(io)?;
+
+        // Validate file as an ICC profile
+        if header.magic != sig::MAGIC_NUMBER {
+            let msg = "not an ICC profile, invalid signature";
+            signal_error(context_id, Level::Error, ErrorCode::BadSignature, msg);
+            return Err(msg.into());
+        }
+
+        // Adjust endianness of the used parameters
+        self.device_class = header.device_class;
+        self.color_space = header.color_space;
+        self.pcs = header.pcs;
+
+        self.rendering_intent = header.rendering_intent;
+        self.flags = header.flags;
+        self.manufacturer = header.manufacturer.0;
+        self.model = header.model;
+        self.creator = header.creator.0;
+
+        self.attributes = header.attributes;
+        self.illuminant = XYZ {
+            x: s15_fixed16_to_f64(header.illuminant.x),
+            y: s15_fixed16_to_f64(header.illuminant.y),
+            z: s15_fixed16_to_f64(header.illuminant.z),
+        };
+        self.version = validated_version(header.version);
+
+        // Get profile ID and creation date
+        self.profile_id = header.profile_id;
+        self.created = decode_date_time(header.date).unwrap_or_default();
+
+        // Get size as reported in header
+        let mut header_size = header.size as usize;
+
+        // Make sure header_size is lower than profile size
+        if header_size >= io.reported_size {
+            header_size = io.reported_size;
+        }
+
+        // Get tag count
+        let tag_count = read_u32(io)? as usize;
+        if tag_count > MAX_TABLE_TAG {
+            let msg = format!("Too many tags ({})", tag_count);
+            signal_error(context_id, Level::Error, ErrorCode::Range, &msg);
+            return Err(msg);
+        }
+
+        // Read tag directory
+        self.tags.clear();
+        for _ in 0..tag_count {
+            let (Ok(sig), Ok(offset), Ok(size)) = (read_signature(io), read_u32(io), read_u32(io))
+            else {
+                return Err("Read error in tag directory".into());
+            };
+            let tag = Entry { sig, offset, size };
+
+            // Perform some sanity check. Offset + size should fall inside file.
+            if tag.size == 0 || tag.offset == 0 {
+                continue;
+            }
+            let Some(end) = tag.offset.checked_add(tag.size) else {
+                continue;
+            };
+            if end as usize > header_size {
+                continue;
+            }
+
+            // Check for duplicates
+            if self.tags.iter().any(|entry| entry.name == tag.sig) {
+                let msg = "Duplicate tag found";
+                signal_error(context_id, Level::Error, ErrorCode::Range, msg);
+                return Err(msg.into());
+            }
+
+            // Search for links. Tags sharing data must also share the types they can be stored as
+            let linked = self
+                .tags
+                .iter()
+                .find(|entry| entry.offset == tag.offset as usize && entry.size == tag.size as usize)
+                .filter(|entry| {
+                    compatible_types(
+                        get_tag_descriptor(context_id, entry.name),
+                        get_tag_descriptor(context_id, tag.sig),
+                    )
+                })
+                .map(|entry| entry.name);
+
+            self.tags.push(TagEntry {
+                name: tag.sig,
+                linked,
+                size: tag.size as usize,
+                offset: tag.offset as usize,
+                save_as_raw: false,
+                tag_object: None,
+                type_handler: None,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Checks whether two tags can be read using the same types.
+fn compatible_types(desc1: Option<&TagDescriptor>, desc2: Option<&TagDescriptor>) -> bool {
+    match (desc1, desc2) {
+        (Some(desc1), Some(desc2)) => {
+            desc1.elem_count == desc2.elem_count
+                && desc1.supported_types() == desc2.supported_types()
+        }
+        _ => false,
+    }
+}
+
+/// Enforces that the profile version is per. spec.
+/// Operates on the big endian bytes from the profile.
+/// Called before converting to platform endianness.
+/// Byte 0 is BCD major version, so max 9.
+/// Byte 1 is 2 BCD digits, one per nibble.
+/// Reserved bytes 2 & 3 must be 0.
+fn validated_version(version: u32) -> u32 {
+    let [mut major, minor, _, _] = version.to_be_bytes();
+
+    if major > 0x09 {
+        major = 0x09;
+    }
+    let temp1 = (minor & 0xf0).min(0x90);
+    let temp2 = (minor & 0x0f).min(0x09);
+
+    u32::from_be_bytes([major, temp1 | temp2, 0, 0])
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::{
+        sig,
+        types::{Profile, XYZ},
+    };
+
+    // A v4.3 display profile with a media white point tag, followed by a tag pointing past the end
+    fn display_profile() -> Vec<u8> {
+        let mut mem = vec![0u8; 128];
+        mem[8..12].copy_from_slice(&0x04300000u32.to_be_bytes());
+        mem[12..16].copy_from_slice(b"mntr");
+        mem[16..20].copy_from_slice(b"RGB ");
+        mem[20..24].copy_from_slice(b"XYZ ");
+        mem[24..36].copy_from_slice(&[0x07, 0xe8, 0, 2, 0, 29, 0, 12, 0, 30, 0, 0]);
+        mem[36..40].copy_from_slice(b"acsp");
+
+        mem.extend_from_slice(&2u32.to_be_bytes());
+        for (tag, offset, size) in [(b"wtpt", 156u32, 20u32), (b"bkpt", 1000, 20)] {
+            mem.extend_from_slice(tag);
+            mem.extend_from_slice(&offset.to_be_bytes());
+            mem.extend_from_slice(&size.to_be_bytes());
+        }
+
+        mem.extend_from_slice(b"XYZ \0\0\0\0");
+        for v in [0x0000f6d6u32, 0x00010000, 0x0000d32d] {
+            mem.extend_from_slice(&v.to_be_bytes());
+        }
+
+        let size = mem.len() as u32;
+        mem[..4].copy_from_slice(&size.to_be_bytes());
+        mem
+    }
+
+    fn check_display_profile(mut profile: Profile) {
+        assert!(profile.device_class() == sig::class::DISPLAY);
+        assert!(profile.color_space() == sig::colorspace::RGB);
+        assert!(profile.pcs() == sig::colorspace::XYZ);
+        assert!((profile.version() - 4.3).abs() < 1e-9);
+        assert_eq!(profile.creation_date().to_string(), "2024-02-29 12:30:00 UTC");
+
+        // Tags that do not fit in the file are dropped from the directory
+        assert!(profile.is_tag(sig::tags::MEDIA_WHITE_POINT));
+        assert!(!profile.is_tag(sig::tags::MEDIA_BLACK_POINT));
+
+        let white = profile.read_tag::<XYZ>(sig::tags::MEDIA_WHITE_POINT).unwrap();
+        assert!((white.x - 0.9642).abs() < 1e-4);
+        assert!((white.z - 0.8249).abs() < 1e-4);
+    }
+
+    #[test]
+    fn open_mem_reads_header_and_directory() {
+        check_display_profile(Profile::open_mem(&display_profile()).unwrap());
+    }
+
+    #[test]
+    fn open_stream_reads_header_and_directory() {
+        check_display_profile(Profile::open_stream(Cursor::new(display_profile())).unwrap());
+    }
+
+    #[test]
+    fn open_file_reads_header_and_directory() {
+        let path = std::env::temp_dir().join(format!("lcms2-open-{}.icc", std::process::id()));
+        std::fs::write(&path, display_profile()).unwrap();
+
+        let profile = Profile::open_file(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        check_display_profile(profile.unwrap());
+    }
+
+    #[test]
+    fn invalid_profiles_are_rejected() {
+        let mut mem = display_profile();
+        mem[36..40].copy_from_slice(b"xxxx");
+        assert!(Profile::open_mem(&mem).is_err());
+
+        assert!(Profile::open_mem(&display_profile()[..100]).is_err());
+    }
+}
@@ -0,0 +1,333 @@
+use std::{mem::size_of, path::Path};
+
+use log::Level;
+
+use crate::{
+    io::IoHandler,
+    plugin::{
+        encode_date_time, f64_to_s15_fixed16, get_tag_descriptor, get_tag_type_handler,
+        write_alignment, write_signature, write_type_base, write_u32,
+    },
+    sig, signal_error,
+    state::ErrorCode,
+    types::{EncodedXYZ, Signature},
+    Result,
+};
+
+use super::{Header, Profile};
+
+impl<'mtx, 'a, 'b> Profile<'mtx, 'a, 'b> {
+    /// Saves the profile to a file.
+    pub fn save_to_file(&mut self, file_name: impl AsRef<Path>) -> Result<()> {
+        let mut io = IoHandler::from_file(self.context_id, file_name, 'w')?;
+
+        self.save_to_io(Some(&mut io))?;
+
+        if !(io.close)(&mut io) {
+            return Err("Error closing file in Profile::save_to_file".into());
+        }
+
+        Ok(())
+    }
+
+    /// Saves the profile to a newly allocated block of memory.
+    pub fn save_to_mem(&mut self) -> Result<Vec<u8>> {
+        let mut io = IoHandler::mem_writer(self.context_id);
+
+        self.save_to_io(Some(&mut io))?;
+
+        match io.into_mem() {
+            Some(mem) => Ok(mem),
+            None => Err("Cannot retrieve memory block in Profile::save_to_mem".into()),
+        }
+    }
+
+    /// Computes the number of bytes the profile would take once saved, without writing anything.
+    pub fn saved_size(&mut self) -> Result<usize> {
+        self.save_to_io(None)
+    }
+
+    /// Low-level save to IO handler. A first pass computes the offsets of every tag, a second one
+    /// writes the actual data. Passing no IO handler performs just the first pass.
+    /// Returns the number of bytes used by the profile.
+    pub fn save_to_io(&mut self, io: Option<&mut IoHandler>) -> Result<usize> {
+        let mut directory = vec![(0usize, 0usize); self.tags.len()];
+
+        // Pass #1 does compute offsets
+        let mut null_io = IoHandler::null(self.context_id);
+        self.write_header(&mut null_io, 0, &directory)?;
+        self.save_tags(&mut null_io, &mut directory)?;
+        self.set_links(&mut directory)?;
+
+        let used_space = null_io.used_space;
+
+        // Pass #2 does save to iohandler
+        if let Some(io) = io {
+            self.set_links(&mut directory)?;
+            self.write_header(io, used_space, &directory)?;
+            self.save_tags(io, &mut directory)?;
+        }
+
+        Ok(used_space)
+    }
+
+    /// Writes the header and the tag directory. `directory` holds the offset and size of each tag.
+    fn write_header(
+        &self,
+        io: &mut IoHandler,
+        used_space: usize,
+        directory: &[(usize, usize)],
+    ) -> Result<()> {
+        let header = Header {
+            size: used_space as u32,
+            cmm_id: sig::LCMS_SIGNATURE,
+            version: self.version,
+            device_class: self.device_class,
+            color_space: self.color_space,
+            pcs: self.pcs,
+            // NOTE: in v4 Timestamp must be in UTC rather than in local time
+            date: encode_date_time(self.created),
+            magic: sig::MAGIC_NUMBER,
+            platform: if cfg!(windows) {
+                sig::platform::MICROSOFT
+            } else {
+                sig::platform::MACINTOSH
+            },
+            flags: self.flags,
+            manufacturer: Signature(self.manufacturer),
+            model: self.model,
+            attributes: self.attributes,
+            rendering_intent: self.rendering_intent,
+            illuminant: EncodedXYZ {
+                x: f64_to_s15_fixed16(self.illuminant.x),
+                y: f64_to_s15_fixed16(self.illuminant.y),
+                z: f64_to_s15_fixed16(self.illuminant.z),
+            },
+            creator: Signature(self.creator),
+            profile_id: self.profile_id,
+            reserved: [0; 28],
+        };
+
+        // Dump the header
+        header.write(io)?;
+
+        // Saves Tag directory
+        write_u32(io, self.tags.len() as u32)?;
+        for (tag, (offset, size)) in self.tags.iter().zip(directory) {
+            if write_signature(io, tag.name).is_err()
+                || write_u32(io, *offset as u32).is_err()
+                || write_u32(io, *size as u32).is_err()
+            {
+                return Err("Write error in tag directory".into());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes the contents of every tag, keeping track of offsets and sizes.
+    fn save_tags(&mut self, io: &mut IoHandler, directory: &mut [(usize, usize)]) -> Result<()> {
+        let context_id = self.context_id;
+        let version = self.icc_version();
+
+        for (i, tag) in self.tags.iter().enumerate() {
+            // Linked tags are not written
+            if tag.linked.is_some() {
+                continue;
+            }
+
+            let begin = io.used_space;
+            directory[i].0 = begin;
+
+            let Some(data) = &tag.tag_object else {
+                // Reach here if we are copying a tag from a disk-based ICC profile which has not
+                // been modified by user. In this case a blind copy of the block data is performed
+                if tag.offset != 0 {
+                    let orig = &mut self.io_handler;
+                    let mut mem = vec![0u8; tag.size];
+
+                    if !(orig.seek)(orig, tag.offset)
+                        || (orig.read)(orig, &mut mem, size_of::<u8>(), tag.size) != tag.size
+                    {
+                        return Err(format!("Cannot copy tag '{:x}' from original profile", tag.name.0));
+                    }
+                    if !(io.write)(io, tag.size, &mem) {
+                        return Err(format!("Cannot write tag '{:x}'", tag.name.0));
+                    }
+
+                    directory[i].1 = io.used_space - begin;
+
+                    // Align to 32 bit boundary.
+                    write_alignment(io)?;
+                    continue;
+                }
+                return Err(format!("Tag '{:x}' has no data to save", tag.name.0));
+            };
+
+            // Should this tag be saved as RAW? If so, tagsizes should be specified in advance (no further cooking is done)
+            if tag.save_as_raw {
+                let Some(mem) = data.downcast_ref::<Vec<u8>>() else {
+                    return Err(format!("Invalid raw data in tag '{:x}'", tag.name.0));
+                };
+                if !(io.write)(io, mem.len(), mem) {
+                    return Err(format!("Cannot write tag '{:x}'", tag.name.0));
+                }
+
+                directory[i].1 = io.used_space - begin;
+
+                // Align to 32 bit boundary.
+                write_alignment(io)?;
+                continue;
+            }
+
+            // Search for support on this tag
+            // A tag left out would still take a slot in the directory, so it is an error
+            let Some(descriptor) = get_tag_descriptor(context_id, tag.name) else {
+                let msg = format!("Unsupported tag '{:x}'", tag.name.0);
+                signal_error(context_id, Level::Error, ErrorCode::UnknownExtension, &msg);
+                return Err(msg);
+            };
+
+            let r#type = descriptor.decide_type(version, data.as_ref());
+            let Some(handler) = get_tag_type_handler(context_id, r#type) else {
+                let msg = format!("(Internal) no handler for tag {:x}", tag.name.0);
+                signal_error(context_id, Level::Error, ErrorCode::Internal, &msg);
+                return Err(msg);
+            };
+
+            write_type_base(io, handler.signature)?;
+
+            let mut local_handler = handler.clone();
+            local_handler.context_id = context_id.clone();
+            local_handler.icc_version = self.version;
+            if let Err(msg) = (handler.write)(
+                &local_handler,
+                io,
+                data.as_ref(),
+                descriptor.elem_count as usize,
+            ) {
+                let msg = format!("Couldn't write type '{:x}': {}", handler.signature.0, msg);
+                signal_error(context_id, Level::Error, ErrorCode::Write, &msg);
+                return Err(msg);
+            }
+
+            directory[i].1 = io.used_space - begin;
+
+            // Align to 32 bit boundary.
+            write_alignment(io)?;
+        }
+
+        Ok(())
+    }
+
+    /// Linked tags share offset and size with the tag they point to. Fails if the tag at the end
+    /// of a link is no longer in the profile.
+    fn set_links(&self, directory: &mut [(usize, usize)]) -> Result<()> {
+        for (i, tag) in self.tags.iter().enumerate() {
+            if tag.linked.is_some() {
+                let Some(j) = self.search_tag(tag.name, true) else {
+                    let msg = format!("Tag '{:x}' is linked to a missing tag", tag.name.0);
+                    signal_error(self.context_id, Level::Error, ErrorCode::NotSuitable, &msg);
+                    return Err(msg);
+                };
+                directory[i] = directory[j];
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        sig,
+        types::{Profile, ToneCurve, MLU, XYZ},
+        DEFAULT_CONTEXT,
+    };
+
+    fn described_profile() -> Profile<'static, 'static, 'static> {
+        // An odd length description, so the next tag needs alignment
+        let mut description = MLU::new(&DEFAULT_CONTEXT, 1);
+        description.set_ascii(*b"en", *b"US", b"Odd").unwrap();
+
+        let mut profile = Profile::new();
+        profile.set_device_class(sig::class::DISPLAY).unwrap();
+        profile.write_tag(sig::tags::PROFILE_DESCRIPTION, &description).unwrap();
+        profile
+            .write_tag(sig::tags::MEDIA_WHITE_POINT, &XYZ { x: 0.9642, y: 1.0, z: 0.8249 })
+            .unwrap();
+        profile
+    }
+
+    #[test]
+    fn header_and_directory_are_consistent() {
+        let mut profile = described_profile();
+        let mem = profile.save_to_mem().unwrap();
+
+        assert_eq!(profile.saved_size().unwrap(), mem.len());
+        assert_eq!(u32::from_be_bytes(mem[..4].try_into().unwrap()) as usize, mem.len());
+        assert_eq!(u32::from_be_bytes(mem[128..132].try_into().unwrap()), 2);
+
+        for entry in mem[132..156].chunks(12) {
+            let offset = u32::from_be_bytes(entry[4..8].try_into().unwrap()) as usize;
+            let size = u32::from_be_bytes(entry[8..12].try_into().unwrap()) as usize;
+
+            assert_eq!(offset % 4, 0);
+            assert!(offset >= 156 && offset + size <= mem.len());
+        }
+    }
+
+    #[test]
+    fn open_and_save_gives_identical_bytes() {
+        let mem = described_profile().save_to_mem().unwrap();
+
+        let mut profile = Profile::open_mem(&mem).unwrap();
+        assert_eq!(profile.save_to_mem().unwrap(), mem);
+
+        // Tags decoded in between are written back the same way
+        profile.read_tag::<XYZ>(sig::tags::MEDIA_WHITE_POINT).unwrap();
+        profile.read_tag::<MLU>(sig::tags::PROFILE_DESCRIPTION).unwrap();
+        assert_eq!(profile.save_to_mem().unwrap(), mem);
+    }
+
+    #[test]
+    fn save_to_file_round_trip() {
+        let path = std::env::temp_dir().join(format!("lcms2-save-{}.icc", std::process::id()));
+
+        let mut profile = described_profile();
+        profile.save_to_file(&path).unwrap();
+        let saved = std::fs::read(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(saved.unwrap(), profile.save_to_mem().unwrap());
+    }
+
+    fn linked_profile() -> Profile<'static, 'static, 'static> {
+        let curve = ToneCurve::build_parametric(&DEFAULT_CONTEXT, 1, &[2.2]).unwrap();
+
+        let mut profile = Profile::new();
+        profile.write_tag(sig::tags::RED_TRC, &curve).unwrap();
+        profile.link_tag(sig::tags::GREEN_TRC, sig::tags::RED_TRC).unwrap();
+        profile
+    }
+
+    #[test]
+    fn linked_tags_share_data() {
+        let mem = linked_profile().save_to_mem().unwrap();
+        let mut profile = Profile::open_mem(&mem).unwrap();
+
+        assert!(profile.tag_linked_to(sig::tags::GREEN_TRC) == Some(sig::tags::RED_TRC));
+        let curve = profile.read_tag::<ToneCurve>(sig::tags::GREEN_TRC).unwrap();
+        assert!((curve.params().unwrap()[0] - 2.2).abs() < 1e-4);
+    }
+
+    #[test]
+    fn dangling_link_fails_save() {
+        let mut profile = linked_profile();
+        assert!(profile.remove_tag(sig::tags::RED_TRC));
+
+        assert!(profile.save_to_mem().is_err());
+        assert!(profile.saved_size().is_err());
+    }
+}
@@ -0,0 +1,415 @@
+use std::{any::Any, mem::size_of};
+
+use log::Level;
+
+use crate::{
+    plugin::{get_tag_descriptor, get_tag_type_handler, read_type_base},
+    quick_saturate_word, sig, signal_error,
+    state::ErrorCode,
+    types::{NamedColor, Signature, ToneCurve},
+    Result, MAX_TABLE_TAG,
+};
+
+use super::{Profile, TagEntry};
+
+impl<'mtx, 'a, 'b> Profile<'mtx, 'a, 'b> {
+    /// Searches for a tag in the directory, following links if requested.
+    pub(crate) fn search_tag(&self, sig: Signature, follow_links: bool) -> Option<usize> {
+        let mut sig = sig;
+
+        // A chain of links can never be longer than the directory itself
+        for _ in 0..=self.tags.len() {
+            let n = self.tags.iter().position(|tag| tag.name == sig)?;
+
+            if !follow_links {
+                return Some(n);
+            }
+
+            match self.tags[n].linked {
+                // Yes, follow link
+                Some(linked) => sig = linked,
+                None => return Some(n),
+            }
+        }
+
+        None
+    }
+
+    /// Searches for an existing tag with this signature, or creates a new entry.
+    /// An existing tag has its contents dropped.
+    fn new_tag(&mut self, sig: Signature) -> Result<usize> {
+        let entry = TagEntry {
+            name: sig,
+            linked: None,
+            size: 0,
+            offset: 0,
+            save_as_raw: false,
+            tag_object: None,
+            type_handler: None,
+        };
+
+        match self.search_tag(sig, false) {
+            // Already exists? delete it
+            Some(i) => {
+                self.tags[i] = entry;
+                Ok(i)
+            }
+            // No, make a new one
+            None => {
+                if self.tags.len() >= MAX_TABLE_TAG {
+                    let msg = format!("Too many tags ({})", MAX_TABLE_TAG);
+                    signal_error(self.context_id, Level::Error, ErrorCode::Range, &msg);
+                    return Err(msg);
+                }
+
+                self.tags.push(entry);
+                Ok(self.tags.len() - 1)
+            }
+        }
+    }
+
+    /// Returns true if the profile contains the tag.
+    pub fn is_tag(&self, sig: Signature) -> bool {
+        self.search_tag(sig, false).is_some()
+    }
+
+    /// Writes a tag. The type used to store it is chosen from the profile version and the data,
+    /// and the data is duplicated, so the caller keeps ownership of it.
+    pub fn write_tag(&mut self, sig: Signature, data: &dyn Any) -> Result<()> {
+        let context_id = self.context_id;
+
+        // Get information about the TAG.
+        let Some(descriptor) = get_tag_descriptor(context_id, sig) else {
+            let msg = format!("Unsupported tag '{:x}'", sig.0);
+            signal_error(context_id, Level::Error, ErrorCode::UnknownExtension, &msg);
+            return Err(msg);
+        };
+
+        // Now we need to know which type to use. It depends on the version.
+        let r#type = descriptor.decide_type(self.icc_version(), data);
+
+        // Does the tag support this type?
+        if !descriptor.is_type_supported(r#type) {
+            let msg = format!("Unsupported type '{:x}' for tag '{:x}'", r#type.0, sig.0);
+            signal_error(context_id, Level::Error, ErrorCode::UnknownExtension, &msg);
+            return Err(msg);
+        }
+
+        // Does we have a handler for this type?
+        let Some(handler) = get_tag_type_handler(context_id, r#type) else {
+            let msg = format!("Unsupported type '{:x}' for tag '{:x}'", r#type.0, sig.0);
+            signal_error(context_id, Level::Error, ErrorCode::UnknownExtension, &msg);
+            return Err(msg);
+        };
+
+        let mut local_handler = handler.clone();
+        local_handler.context_id = context_id.clone();
+        local_handler.icc_version = self.version;
+        let object = (handler.dup)(&local_handler, data, descriptor.elem_count as usize)?;
+
+        let i = self.new_tag(sig)?;
+        let entry = &mut self.tags[i];
+        entry.type_handler = Some(handler);
+        entry.tag_object = Some(object);
+
+        Ok(())
+    }
+
+    /// Reads a tag, decoding it from the profile on first access. Later calls return the cached object.
+    pub fn read_tag_any(&mut self, sig: Signature) -> Result<&dyn Any> {
+        let Some(n) = self.search_tag(sig, true) else {
+            return Err(format!("Tag '{:x}' not found", sig.0));
+        };
+
+        // Raw data can only be read back as raw data
+        if self.tags[n].save_as_raw {
+            let msg = format!("Tag '{:x}' was written as raw data", sig.0);
+            signal_error(self.context_id, Level::Error, ErrorCode::NotSuitable, &msg);
+            return Err(msg);
+        }
+
+        // If the element is already in memory, return the pointer
+        if self.tags[n].tag_object.is_none() {
+            let object = self.read_tag_object(n, sig)?;
+            self.tags[n].tag_object = Some(object);
+        }
+
+        match &self.tags[n].tag_object {
+            Some(object) => Ok(object.as_ref()),
+            None => Err("Tag object missing in Profile::read_tag_any".into()),
+        }
+    }
+
+    /// Reads a tag as a concrete type, such as `read_tag::<XYZ>(sig::tags::MEDIA_WHITE_POINT)`.
+    pub fn read_tag<T: Any>(&mut self, sig: Signature) -> Result<&T> {
+        match self.read_tag_any(sig)?.downcast_ref::<T>() {
+            Some(value) => Ok(value),
+            None => Err(format!(
+                "Tag '{:x}' is not of type {}",
+                sig.0,
+                std::any::type_name::<T>()
+            )),
+        }
+    }
+
+    /// Returns the name of each device colorant, in channel order, as stored in the colorant table.
+    pub fn colorant_names(&mut self) -> Result<Vec<String>> {
+        let list = self.read_tag::<NamedColor>(sig::tags::COLORANT_TABLE)?;
+
+        Ok(list.iter().map(|entry| entry.name.clone()).collect())
+    }
+
+    /// Samples the video card gamma curves into a ramp of `n_entries` values per channel, as
+    /// graphics drivers expect them.
+    pub fn vcgt_ramp(&mut self, n_entries: usize) -> Result<[Vec<u16>; 3]> {
+        if !(2..=65536).contains(&n_entries) {
+            let msg = format!("Invalid number of ramp entries '{}'", n_entries);
+            signal_error(self.context_id, Level::Error, ErrorCode::Range, &msg);
+            return Err(msg);
+        }
+
+        let curves = self.read_tag::<[ToneCurve; 3]>(sig::tags::VCGT)?;
+
+        Ok(curves.each_ref().map(|curve| {
+            (0..n_entries)
+                .map(|i| {
+                    let v = curve.eval_f32(i as f32 / (n_entries - 1) as f32);
+                    quick_saturate_word(v as f64 * 65535.0)
+                })
+                .collect()
+        }))
+    }
+
+    /// Decodes the contents of the n-th tag from the IO handler the profile was opened from.
+    fn read_tag_object(&mut self, n: usize, sig: Signature) -> Result<Box<dyn Any>> {
+        let context_id = self.context_id;
+        let offset = self.tags[n].offset;
+        let tag_size = self.tags[n].size;
+
+        // We need to read it. Get the offset and size to the file
+        let io = &mut self.io_handler;
+        if !(io.seek)(io, offset) {
+            return Err("Seek error in Profile::read_tag".into());
+        }
+
+        // Get information about the TAG.
+        let Some(descriptor) = get_tag_descriptor(context_id, sig) else {
+            let msg = format!("Unknown tag type '{:x}' found", sig.0);
+            signal_error(context_id, Level::Error, ErrorCode::UnknownExtension, &msg);
+            return Err(msg);
+        };
+
+        // Read the type base and check it against the types the tag may use
+        let base_type = read_type_base(io)?;
+        if !descriptor.is_type_supported(base_type) {
+            let msg = format!("Unsupported type '{:x}' for tag '{:x}'", base_type.0, sig.0);
+            signal_error(context_id, Level::Error, ErrorCode::UnknownExtension, &msg);
+            return Err(msg);
+        }
+
+        let Some(handler) = get_tag_type_handler(context_id, base_type) else {
+            let msg = format!("Unsupported type '{:x}' for tag '{:x}'", base_type.0, sig.0);
+            signal_error(context_id, Level::Error, ErrorCode::UnknownExtension, &msg);
+            return Err(msg);
+        };
+
+        // The type base is not part of the data
+        let Some(tag_size) = tag_size.checked_sub(8) else {
+            let msg = format!("Corrupted tag '{:x}'", sig.0);
+            signal_error(context_id, Level::Error, ErrorCode::CorruptionDetected, &msg);
+            return Err(msg);
+        };
+
+        let mut local_handler = handler.clone();
+        local_handler.context_id = context_id.clone();
+        local_handler.icc_version = self.version;
+
+        let mut elem_count = 0;
+        let object = match (handler.read)(&local_handler, io, &mut elem_count, tag_size) {
+            Ok(object) => object,
+            Err(_) => {
+                let msg = format!("Corrupted tag '{:x}'", sig.0);
+                signal_error(context_id, Level::Error, ErrorCode::CorruptionDetected, &msg);
+                return Err(msg);
+            }
+        };
+
+        // This is a weird error that may be a symptom of something more serious, the number of
+        // stored item is actually less than the number of required elements.
+        if elem_count < descriptor.elem_count as usize {
+            let msg = format!(
+                "'{:x}' Inconsistent number of items: expected {}, got {}",
+                sig.0, descriptor.elem_count, elem_count
+            );
+            signal_error(context_id, Level::Error, ErrorCode::CorruptionDetected, &msg);
+            return Err(msg);
+        }
+
+        self.tags[n].type_handler = Some(handler);
+
+        Ok(object)
+    }
+
+    /// Reads the bytes of a tag as stored in the profile, type base included. No decoding is done.
+    /// Tags written with `write_tag` have no raw representation and cannot be read this way.
+    pub fn read_raw_tag(&mut self, sig: Signature) -> Result<Vec<u8>> {
+        let Some(n) = self.search_tag(sig, true) else {
+            return Err(format!("Tag '{:x}' not found", sig.0));
+        };
+        let entry = &self.tags[n];
+
+        // The data has been already written as raw, return a copy
+        if entry.save_as_raw {
+            return match entry.tag_object.as_ref().and_then(|data| data.downcast_ref::<Vec<u8>>()) {
+                Some(data) => Ok(data.clone()),
+                None => Err(format!("Invalid raw data in tag '{:x}'", sig.0)),
+            };
+        }
+
+        // Cooked tags built in memory have no bytes to return
+        if entry.tag_object.is_some() && entry.size == 0 {
+            let msg = format!("Tag '{:x}' was written as cooked data", sig.0);
+            signal_error(self.context_id, Level::Error, ErrorCode::NotSuitable, &msg);
+            return Err(msg);
+        }
+
+        // Read the whole tag from the original profile
+        let (offset, size) = (entry.offset, entry.size);
+        let io = &mut self.io_handler;
+        let mut data = vec![0u8; size];
+
+        if !(io.seek)(io, offset) || (io.read)(io, &mut data, size_of::<u8>(), size) != size {
+            return Err("Read error in Profile::read_raw_tag".into());
+        }
+
+        Ok(data)
+    }
+
+    /// Writes a tag as a block of bytes, type base included. The data is saved as is, which allows
+    /// keeping private tags that have no type handler. Such a tag can only be read back as raw data.
+    pub fn write_raw_tag(&mut self, sig: Signature, data: &[u8]) -> Result<()> {
+        let i = self.new_tag(sig)?;
+        let entry = &mut self.tags[i];
+
+        entry.save_as_raw = true;
+        entry.tag_object = Some(Box::new(data.to_vec()));
+        entry.size = data.len();
+
+        Ok(())
+    }
+
+    /// Makes a tag share the contents of another one. Both end up pointing to the same data when saved.
+    pub fn link_tag(&mut self, sig: Signature, dest: Signature) -> Result<()> {
+        let i = self.new_tag(sig)?;
+        self.tags[i].linked = Some(dest);
+
+        Ok(())
+    }
+
+    /// Returns the signature a tag is linked to, if any.
+    pub fn tag_linked_to(&self, sig: Signature) -> Option<Signature> {
+        let i = self.search_tag(sig, false)?;
+
+        self.tags[i].linked
+    }
+
+    /// Removes a tag from the profile. Returns false if the tag was not there.
+    pub fn remove_tag(&mut self, sig: Signature) -> bool {
+        match self.search_tag(sig, false) {
+            Some(i) => {
+                self.tags.remove(i);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        sig,
+        types::{Profile, Signature, MLU, XYZ},
+        DEFAULT_CONTEXT,
+    };
+
+    fn white_point_profile() -> Vec<u8> {
+        let mut profile = Profile::new();
+        profile
+            .write_tag(sig::tags::MEDIA_WHITE_POINT, &XYZ { x: 0.9642, y: 1.0, z: 0.8249 })
+            .unwrap();
+        profile.save_to_mem().unwrap()
+    }
+
+    #[test]
+    fn tags_are_read_once() {
+        let mut profile = Profile::open_mem(&white_point_profile()).unwrap();
+
+        let first = profile.read_tag::<XYZ>(sig::tags::MEDIA_WHITE_POINT).unwrap() as *const XYZ;
+        let second = profile.read_tag::<XYZ>(sig::tags::MEDIA_WHITE_POINT).unwrap() as *const XYZ;
+        assert_eq!(first, second);
+        assert!((profile.read_tag::<XYZ>(sig::tags::MEDIA_WHITE_POINT).unwrap().y - 1.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn read_tag_checks_the_type() {
+        let mut profile = Profile::open_mem(&white_point_profile()).unwrap();
+
+        assert!(profile.read_tag::<MLU>(sig::tags::MEDIA_WHITE_POINT).is_err());
+        assert!(profile.read_tag::<XYZ>(sig::tags::MEDIA_BLACK_POINT).is_err());
+    }
+
+    #[test]
+    fn unsupported_types_are_rejected() {
+        let mut mem = white_point_profile();
+
+        // A white point can't be stored as text
+        let pos = mem.windows(4).rposition(|w| w == b"XYZ ").unwrap();
+        mem[pos..pos + 4].copy_from_slice(b"text");
+        let mut profile = Profile::open_mem(&mem).unwrap();
+        assert!(profile.read_tag::<XYZ>(sig::tags::MEDIA_WHITE_POINT).is_err());
+
+        let mlu = MLU::new(&DEFAULT_CONTEXT, 0);
+        assert!(profile.write_tag(sig::tags::MEDIA_WHITE_POINT, &mlu).is_err());
+    }
+
+    #[test]
+    fn private_tags_survive_save() {
+        let private = Signature(u32::from_be_bytes(*b"priv"));
+        let data = b"priv\0\0\0\0some vendor data".to_vec();
+
+        let mut profile = Profile::open_mem(&white_point_profile()).unwrap();
+        profile.write_raw_tag(private, &data).unwrap();
+        let mem = profile.save_to_mem().unwrap();
+
+        // Modify another tag, the private one is copied as is
+        let mut profile = Profile::open_mem(&mem).unwrap();
+        profile
+            .write_tag(sig::tags::MEDIA_WHITE_POINT, &XYZ { x: 0.9505, y: 1.0, z: 1.089 })
+            .unwrap();
+        let mem = profile.save_to_mem().unwrap();
+
+        let mut profile = Profile::open_mem(&mem).unwrap();
+        assert_eq!(profile.read_raw_tag(private).unwrap(), data);
+        assert!(profile.read_tag_any(private).is_err());
+    }
+
+    #[test]
+    fn raw_and_cooked_tags_do_not_mix() {
+        let mut profile = Profile::new();
+        profile.write_raw_tag(sig::tags::MEDIA_WHITE_POINT, b"XYZ \0\0\0\0").unwrap();
+        assert!(profile.read_tag::<XYZ>(sig::tags::MEDIA_WHITE_POINT).is_err());
+
+        profile
+            .write_tag(sig::tags::MEDIA_WHITE_POINT, &XYZ { x: 0.9642, y: 1.0, z: 0.8249 })
+            .unwrap();
+        assert!(profile.read_raw_tag(sig::tags::MEDIA_WHITE_POINT).is_err());
+
+        // Tags coming from a file can be read both ways
+        let mut profile = Profile::open_mem(&white_point_profile()).unwrap();
+        let raw = profile.read_raw_tag(sig::tags::MEDIA_WHITE_POINT).unwrap();
+        assert_eq!(raw.len(), 20);
+        assert_eq!(&raw[..4], b"XYZ ");
+        assert!(profile.read_tag::<XYZ>(sig::tags::MEDIA_WHITE_POINT).is_ok());
+    }
+}
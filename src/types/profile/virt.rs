@@ -0,0 +1,922 @@
+use std::any::Any;
+
+use log::Level;
+
+use crate::{
+    cms::D50,
+    intent, quick_saturate_word, sig, signal_error,
+    state::ErrorCode,
+    types::{
+        LCh, Lab, Pipeline, Seq, Signature, Stage, StageLoc, ToneCurve, VideoSignalType, XYYTriple, MLU, XYY,
+        XYZ,
+    },
+    white_point::{adaptation_matrix, build_rgb_to_xyz_transfer_matrix, white_point_from_temp},
+    Context, Result, DEFAULT_CONTEXT, NO_COUNTRY, NO_LANGUAGE,
+};
+
+use super::Profile;
+
+// ProPhoto, as ROMM RGB. Linear below 1/32
+const ROMM_PARAMETERS: [f64; 5] = [1.8, 1.0, 0.0, 1.0 / 16.0, 1.0 / 32.0];
+
+// Adobe RGB (1998) gamma, 2 + 51/256
+const ADOBE_RGB_GAMMA: f64 = 563.0 / 256.0;
+
+const D50_XYY: XYY = XYY {
+    x: 0.3457,
+    y: 0.3585,
+    y_lum: 1.0,
+};
+
+const D65: XYY = XYY {
+    x: 0.3127,
+    y: 0.3290,
+    y_lum: 1.0,
+};
+
+// White of the ACES color spaces, close to D60
+const ACES_WHITE: XYY = XYY {
+    x: 0.32168,
+    y: 0.33767,
+    y_lum: 1.0,
+};
+
+const fn xyy(x: f64, y: f64) -> XYY {
+    XYY { x, y, y_lum: 1.0 }
+}
+
+const ADOBE_RGB_PRIMARIES: [XYY; 3] = [xyy(0.6400, 0.3300), xyy(0.2100, 0.7100), xyy(0.1500, 0.0600)];
+const PROPHOTO_PRIMARIES: [XYY; 3] = [xyy(0.7347, 0.2653), xyy(0.1596, 0.8404), xyy(0.0366, 0.0001)];
+const ACES_AP1_PRIMARIES: [XYY; 3] = [xyy(0.7130, 0.2930), xyy(0.1650, 0.8300), xyy(0.1280, 0.0440)];
+
+impl<'mtx, 'a, 'b> Profile<'mtx, 'a, 'b> {
+    // Fills the description and copyright tags
+    fn set_text_tags(&mut self, description: &str) -> Result<()> {
+        let mut description_mlu = MLU::new(self.context_id, 1);
+        let mut copyright_mlu = MLU::new(self.context_id, 1);
+
+        description_mlu.set_ascii(*b"en", *b"US", description.as_bytes())?;
+        copyright_mlu.set_ascii(*b"en", *b"US", b"No copyright, use freely")?;
+
+        self.write_tag(sig::tags::PROFILE_DESCRIPTION, &description_mlu)?;
+        self.write_tag(sig::tags::COPYRIGHT, &copyright_mlu)
+    }
+
+    // A V4 display profile holding the white point and adaptation, and optionally the colorants and
+    // curves of a matrix-shaper.
+    fn rgb(
+        white_point: XYY,
+        primaries: Option<&XYYTriple>,
+        transfer_function: Option<&[ToneCurve; 3]>,
+    ) -> Result<Self> {
+        let mut profile = Profile::new();
+
+        profile.set_version(4.4)?;
+        profile.set_device_class(sig::class::DISPLAY)?;
+        profile.set_color_space(sig::colorspace::RGB)?;
+        profile.set_pcs(sig::colorspace::XYZ)?;
+        profile.set_rendering_intent(intent::PERCEPTUAL)?;
+
+        // Implement profile using following tags:
+        //
+        //  1 PROFILE_DESCRIPTION
+        //  2 MEDIA_WHITE_POINT
+        //  3 RED_COLORANT
+        //  4 GREEN_COLORANT
+        //  5 BLUE_COLORANT
+        //  6 RED_TRC
+        //  7 GREEN_TRC
+        //  8 BLUE_TRC
+        //  9 Chromatic adaptation Tag
+        // This conforms a standard RGB DisplayProfile as says ICC, and then I add (As per addendum II)
+        // 10 CHROMATICITY
+
+        profile.set_text_tags("RGB built-in")?;
+
+        profile.write_tag(sig::tags::MEDIA_WHITE_POINT, &D50)?;
+
+        let Some(chad) = adaptation_matrix(None, white_point.to_xyz(), D50) else {
+            return profile.range_error(format!("Invalid white point '{} {}'", white_point.x, white_point.y));
+        };
+
+        // This is a V4 tag, but many CMM does read and understand it no matter which version
+        profile.write_tag(sig::tags::CHROMATIC_ADAPTATION, &chad.to_array().to_vec())?;
+
+        if let Some(primaries) = primaries {
+            let max_white = XYY {
+                y_lum: 1.0,
+                ..white_point
+            };
+
+            let Some(colorants) = build_rgb_to_xyz_transfer_matrix(max_white, primaries) else {
+                return profile.range_error("Primaries are not suitable for a RGB space".into());
+            };
+
+            // Colorants are the columns of the matrix
+            let m = colorants;
+            for (tag, xyz) in [
+                (sig::tags::RED_COLORANT, XYZ { x: m.x.x, y: m.y.x, z: m.z.x }),
+                (sig::tags::GREEN_COLORANT, XYZ { x: m.x.y, y: m.y.y, z: m.z.y }),
+                (sig::tags::BLUE_COLORANT, XYZ { x: m.x.z, y: m.y.z, z: m.z.z }),
+            ] {
+                profile.write_tag(tag, &xyz)?;
+            }
+        }
+
+        if let Some([red, green, blue]) = transfer_function {
+            // Tries to minimize space. Thanks to Richard Hughes for this nice idea
+            profile.write_tag(sig::tags::RED_TRC, red)?;
+
+            for (tag, curve) in [(sig::tags::GREEN_TRC, green), (sig::tags::BLUE_TRC, blue)] {
+                if curve.table_16() == red.table_16() && curve.segments() == red.segments() {
+                    profile.link_tag(tag, sig::tags::RED_TRC)?;
+                } else {
+                    profile.write_tag(tag, curve)?;
+                }
+            }
+        }
+
+        if let Some(primaries) = primaries {
+            profile.write_tag(sig::tags::CHROMATICITY, primaries)?;
+        }
+
+        Ok(profile)
+    }
+
+    /// Creates a V4 RGB matrix-shaper profile. The colorants are adapted to D50 by using Bradford.
+    pub fn new_rgb(white_point: XYY, primaries: [XYY; 3], transfer_function: [ToneCurve; 3]) -> Result<Self> {
+        if !(white_point.y > 0.0 && white_point.x >= 0.0 && white_point.x + white_point.y <= 1.0) {
+            let msg = format!("Invalid white point '{} {}'", white_point.x, white_point.y);
+            signal_error(&DEFAULT_CONTEXT, Level::Error, ErrorCode::Range, &msg);
+            return Err(msg);
+        }
+
+        let [red, green, blue] = primaries;
+        let primaries = XYYTriple { red, green, blue };
+
+        Self::rgb(white_point, Some(&primaries), Some(&transfer_function))
+    }
+
+    // A RGB profile whose three channels share the same curve
+    fn rgb_preset(
+        description: &str,
+        white_point: XYY,
+        primaries: [XYY; 3],
+        curve: Result<ToneCurve>,
+    ) -> Result<Self> {
+        let curve = curve?;
+
+        let mut profile = Self::new_rgb(white_point, primaries, [curve.clone(), curve.clone(), curve])?;
+        profile.set_text_tags(description)?;
+
+        Ok(profile)
+    }
+
+    // Same, for the RGB spaces defined as CICP code points
+    fn cicp_preset(description: &str, cicp: VideoSignalType) -> Result<Self> {
+        let Some((white_point, primaries)) = cicp.primaries() else {
+            return Err("Unsupported color primaries".into());
+        };
+        let Some(curve) = cicp.transfer_function(&DEFAULT_CONTEXT) else {
+            return Err("Unsupported transfer characteristics".into());
+        };
+
+        Self::rgb_preset(
+            description,
+            white_point,
+            [primaries.red, primaries.green, primaries.blue],
+            Ok(curve),
+        )
+    }
+
+    /// Creates the sRGB profile.
+    pub fn new_srgb() -> Result<Self> {
+        Self::cicp_preset("sRGB built-in", VideoSignalType::SRGB)
+    }
+
+    /// Creates a Display P3 profile: P3 primaries, D65 white and the sRGB curve.
+    pub fn new_display_p3() -> Result<Self> {
+        Self::cicp_preset("Display P3 built-in", VideoSignalType::DISPLAY_P3)
+    }
+
+    /// Creates a Rec. ITU-R BT.709 profile.
+    pub fn new_rec709() -> Result<Self> {
+        Self::cicp_preset("Rec. 709 built-in", VideoSignalType::BT709)
+    }
+
+    /// Creates a Rec. ITU-R BT.2020 profile, with the SDR transfer function.
+    pub fn new_rec2020() -> Result<Self> {
+        Self::cicp_preset("Rec. 2020 built-in", VideoSignalType::BT2020)
+    }
+
+    /// Creates an Adobe RGB (1998) profile.
+    pub fn new_adobe_rgb() -> Result<Self> {
+        let curve = ToneCurve::build_parametric(&DEFAULT_CONTEXT, 1, &[ADOBE_RGB_GAMMA]);
+
+        Self::rgb_preset("Adobe RGB (1998) built-in", D65, ADOBE_RGB_PRIMARIES, curve)
+    }
+
+    /// Creates a ProPhoto (ROMM RGB) profile.
+    pub fn new_prophoto_rgb() -> Result<Self> {
+        let curve = ToneCurve::build_parametric(&DEFAULT_CONTEXT, 4, &ROMM_PARAMETERS);
+
+        Self::rgb_preset("ProPhoto RGB built-in", D50_XYY, PROPHOTO_PRIMARIES, curve)
+    }
+
+    /// Creates an ACEScg profile: AP1 primaries, ACES white and linear curves.
+    pub fn new_acescg() -> Result<Self> {
+        let curve = ToneCurve::build_parametric(&DEFAULT_CONTEXT, 1, &[1.0]);
+
+        Self::rgb_preset("ACEScg built-in", ACES_WHITE, ACES_AP1_PRIMARIES, curve)
+    }
+
+    /// Creates a V4 gray display profile from its white point and tone curve.
+    pub fn new_gray(white_point: XYY, transfer_function: ToneCurve) -> Result<Self> {
+        let mut profile = Profile::new();
+
+        profile.set_version(4.4)?;
+        profile.set_device_class(sig::class::DISPLAY)?;
+        profile.set_color_space(sig::colorspace::GRAY)?;
+        profile.set_pcs(sig::colorspace::XYZ)?;
+        profile.set_rendering_intent(intent::PERCEPTUAL)?;
+
+        // Implement profile using following tags:
+        //
+        //  1 PROFILE_DESCRIPTION
+        //  2 MEDIA_WHITE_POINT
+        //  3 GRAY_TRC
+
+        // This conforms a standard Gray DisplayProfile
+
+        // Fill-in the tags
+
+        profile.set_text_tags("gray built-in")?;
+
+        if !(white_point.y > 0.0 && white_point.x >= 0.0 && white_point.x + white_point.y <= 1.0) {
+            return profile.range_error(format!("Invalid white point '{} {}'", white_point.x, white_point.y));
+        }
+        profile.write_tag(sig::tags::MEDIA_WHITE_POINT, &white_point.to_xyz())?;
+
+        profile.write_tag(sig::tags::GRAY_TRC, &transfer_function)?;
+
+        Ok(profile)
+    }
+
+    /// Creates a V2 Lab identity profile. The white point defaults to D50.
+    pub fn new_lab_v2(white_point: Option<XYY>) -> Result<Self> {
+        let mut profile = Self::rgb(white_point.unwrap_or(D50.to_xyy()), None, None)?;
+
+        profile.set_version(2.1)?;
+        profile.set_device_class(sig::class::ABSTRACT)?;
+        profile.set_color_space(sig::colorspace::LAB)?;
+        profile.set_pcs(sig::colorspace::LAB)?;
+
+        profile.set_text_tags("Lab identity built-in")?;
+
+        // An identity LUT is all we need
+        let context_id = profile.context_id;
+        let mut lut = Pipeline::new(context_id, 3, 3)?;
+        lut.insert_stage(StageLoc::AtBegin, identity_clut(context_id)?)?;
+
+        profile.write_tag(sig::tags::A_TO_B0, &lut)?;
+
+        Ok(profile)
+    }
+
+    /// Creates a V4 Lab identity profile. The white point defaults to D50.
+    pub fn new_lab_v4(white_point: Option<XYY>) -> Result<Self> {
+        let mut profile = Self::rgb(white_point.unwrap_or(D50.to_xyy()), None, None)?;
+
+        profile.set_version(4.4)?;
+        profile.set_device_class(sig::class::ABSTRACT)?;
+        profile.set_color_space(sig::colorspace::LAB)?;
+        profile.set_pcs(sig::colorspace::LAB)?;
+
+        profile.set_text_tags("Lab identity built-in")?;
+
+        // An empty LUTs is all we need
+        let context_id = profile.context_id;
+        let mut lut = Pipeline::new(context_id, 3, 3)?;
+        lut.insert_stage(StageLoc::AtBegin, Stage::new_tone_curves(context_id, 3, None)?)?;
+
+        profile.write_tag(sig::tags::A_TO_B0, &lut)?;
+
+        Ok(profile)
+    }
+
+    /// Creates a XYZ identity profile.
+    pub fn new_xyz() -> Result<Self> {
+        let mut profile = Self::rgb(D50.to_xyy(), None, None)?;
+
+        profile.set_version(4.4)?;
+        profile.set_device_class(sig::class::ABSTRACT)?;
+        profile.set_color_space(sig::colorspace::XYZ)?;
+        profile.set_pcs(sig::colorspace::XYZ)?;
+
+        profile.set_text_tags("XYZ identity built-in")?;
+
+        // An identity LUT is all we need
+        let context_id = profile.context_id;
+        let mut lut = Pipeline::new(context_id, 3, 3)?;
+        lut.insert_stage(StageLoc::AtBegin, Stage::new_tone_curves(context_id, 3, None)?)?;
+
+        profile.write_tag(sig::tags::A_TO_B0, &lut)?;
+
+        Ok(profile)
+    }
+
+    /// Creates an output-only gray profile that always yields zero. Useful to get the Lab
+    /// values of a transform without keeping any device data.
+    pub fn new_null() -> Result<Self> {
+        let mut profile = Profile::new();
+
+        profile.set_version(4.4)?;
+        profile.set_text_tags("NULL profile built-in")?;
+
+        profile.set_device_class(sig::class::OUTPUT)?;
+        profile.set_color_space(sig::colorspace::GRAY)?;
+        profile.set_pcs(sig::colorspace::LAB)?;
+
+        // Create a valid ICC 4 structure
+        let context_id = profile.context_id;
+        let empty_tab = ToneCurve::build_tabulated_16(context_id, &[0, 0])?;
+
+        let mut lut = Pipeline::new(context_id, 3, 1)?;
+        lut.insert_stage(StageLoc::AtEnd, Stage::new_tone_curves(context_id, 3, None)?)?;
+        lut.insert_stage(StageLoc::AtEnd, Stage::new_clut_16bit(context_id, 2, 3, 1, None)?)?;
+        lut.insert_stage(
+            StageLoc::AtEnd,
+            Stage::new_tone_curves(context_id, 1, Some(&[empty_tab]))?,
+        )?;
+
+        profile.write_tag(sig::tags::B_TO_A0, &lut)?;
+        profile.write_tag(sig::tags::MEDIA_WHITE_POINT, &D50)?;
+
+        Ok(profile)
+    }
+
+    /// Creates a V4 device link that applies one tone curve to each channel of `color_space`.
+    pub fn new_linearization_device_link(color_space: Signature, transfer_functions: &[ToneCurve]) -> Result<Self> {
+        let mut profile = Profile::new();
+
+        let Some(n_channels) = sig::colorspace::channels_of(color_space) else {
+            return profile.colorspace_error(format!("Unknown color space '{:x}'", color_space.0));
+        };
+
+        if transfer_functions.len() != n_channels as usize {
+            return profile.range_error(format!(
+                "Linearization needs {} curves, {} given",
+                n_channels,
+                transfer_functions.len()
+            ));
+        }
+
+        profile.set_version(4.4)?;
+        profile.set_device_class(sig::class::LINK)?;
+        profile.set_color_space(color_space)?;
+        profile.set_pcs(color_space)?;
+        profile.set_rendering_intent(intent::PERCEPTUAL)?;
+
+        // Set up the pipeline
+        let context_id = profile.context_id;
+        let mut lut = Pipeline::new(context_id, n_channels, n_channels)?;
+        lut.insert_stage(
+            StageLoc::AtBegin,
+            Stage::new_tone_curves(context_id, n_channels, Some(transfer_functions))?,
+        )?;
+
+        // Create tags
+        profile.set_text_tags("Linearization built-in")?;
+        profile.write_tag(sig::tags::A_TO_B0, &lut)?;
+        profile.set_seq_desc_tag("Linearization built-in")?;
+
+        Ok(profile)
+    }
+
+    /// Creates a V4 CMYK device link that scales down C, M and Y wherever the sum of all inks goes
+    /// over `limit`, given in percent (0..400). Black is left untouched.
+    pub fn new_ink_limiting_device_link(color_space: Signature, limit: f64) -> Result<Self> {
+        let mut profile = Profile::new();
+
+        if color_space != sig::colorspace::CMYK {
+            return profile.colorspace_error("InkLimiting: Only CMYK currently supported".into());
+        }
+
+        let mut limit = limit;
+        if !(0.0..=400.0).contains(&limit) {
+            signal_error(
+                profile.context_id,
+                Level::Warn,
+                ErrorCode::Range,
+                "InkLimiting: Limit should be between 0..400",
+            );
+            limit = limit.clamp(0.0, 400.0);
+        }
+
+        profile.set_version(4.4)?;
+        profile.set_device_class(sig::class::LINK)?;
+        profile.set_color_space(color_space)?;
+        profile.set_pcs(color_space)?;
+        profile.set_rendering_intent(intent::PERCEPTUAL)?;
+
+        // Creates a pipeline with a 4 dim CLUT, the limit is applied at every node
+        let context_id = profile.context_id;
+        let n_channels = 4;
+
+        let mut clut = Stage::new_clut_16bit(context_id, 17, n_channels, n_channels, None)?;
+        clut.sample_clut_16bit(ink_limiting_sampler, &mut limit, 0)?;
+
+        let mut lut = Pipeline::new(context_id, n_channels, n_channels)?;
+        lut.insert_stage(StageLoc::AtBegin, Stage::new_tone_curves(context_id, n_channels, None)?)?;
+        lut.insert_stage(StageLoc::AtEnd, clut)?;
+        lut.insert_stage(StageLoc::AtEnd, Stage::new_tone_curves(context_id, n_channels, None)?)?;
+
+        // Create tags
+        profile.set_text_tags("ink-limiting built-in")?;
+        profile.write_tag(sig::tags::A_TO_B0, &lut)?;
+        profile.set_seq_desc_tag("ink-limiting built-in")?;
+
+        Ok(profile)
+    }
+
+    /// Creates a V4 abstract Lab profile that adjusts brightness, contrast, hue and saturation, and
+    /// moves the white point from `temp_src` to `temp_dest` (in kelvin, 4000..25000). The adjustments
+    /// are sampled in a CLUT of `n_lut_points` per side.
+    pub fn new_bchsw_abstract(
+        n_lut_points: u32,
+        bright: f64,
+        contrast: f64,
+        hue: f64,
+        saturation: f64,
+        temp_src: u32,
+        temp_dest: u32,
+    ) -> Result<Self> {
+        let mut profile = Profile::new();
+
+        let white_points = if temp_src == temp_dest {
+            None
+        } else {
+            let (Some(src), Some(dest)) = (
+                white_point_from_temp(temp_src as f64),
+                white_point_from_temp(temp_dest as f64),
+            ) else {
+                return profile.range_error(format!("Invalid temperatures '{}' '{}'", temp_src, temp_dest));
+            };
+
+            Some((src.to_xyz(), dest.to_xyz()))
+        };
+
+        let mut bchsw = BchswAdjusts {
+            brightness: bright,
+            contrast,
+            hue,
+            saturation,
+            white_points,
+        };
+
+        profile.set_version(4.4)?;
+        profile.set_device_class(sig::class::ABSTRACT)?;
+        profile.set_color_space(sig::colorspace::LAB)?;
+        profile.set_pcs(sig::colorspace::LAB)?;
+        profile.set_rendering_intent(intent::PERCEPTUAL)?;
+
+        // Creates a Pipeline with 3D grid only. The identity curves are there to make it a valid
+        // A, CLUT, B structure
+        let context_id = profile.context_id;
+        let mut clut = Stage::new_clut_16bit(context_id, n_lut_points, 3, 3, None)?;
+        clut.sample_clut_16bit(bchsw_sampler, &mut bchsw, 0)?;
+
+        let mut lut = Pipeline::new(context_id, 3, 3)?;
+        lut.insert_stage(StageLoc::AtBegin, Stage::new_tone_curves(context_id, 3, None)?)?;
+        lut.insert_stage(StageLoc::AtEnd, clut)?;
+        lut.insert_stage(StageLoc::AtEnd, Stage::new_tone_curves(context_id, 3, None)?)?;
+
+        // Create tags
+        profile.set_text_tags("BCHS built-in")?;
+        profile.write_tag(sig::tags::MEDIA_WHITE_POINT, &D50)?;
+        profile.write_tag(sig::tags::A_TO_B0, &lut)?;
+
+        Ok(profile)
+    }
+
+    // Device links carry a one-profile sequence describing themselves
+    fn set_seq_desc_tag(&mut self, model: &str) -> Result<()> {
+        let mut seq = Seq::new(self.context_id, 1)?;
+
+        seq.seq[0].manufacturer.set_ascii(NO_LANGUAGE, NO_COUNTRY, b"Little CMS")?;
+        seq.seq[0].model.set_ascii(NO_LANGUAGE, NO_COUNTRY, model.as_bytes())?;
+
+        self.write_tag(sig::tags::PROFILE_SEQUENCE_DESC, &seq)?;
+
+        // The profile sequence identifier is V4 only
+        if self.version() >= 4.0 {
+            self.write_tag(sig::tags::PROFILE_SEQUENCE_ID, &seq)?;
+        }
+
+        Ok(())
+    }
+}
+
+struct BchswAdjusts {
+    brightness: f64,
+    contrast: f64,
+    hue: f64,
+    saturation: f64,
+    // Source and destination white points, if they differ
+    white_points: Option<(XYZ, XYZ)>,
+}
+
+// Does the BCHSW adjustments on LCh, then moves the white point in Lab
+fn bchsw_sampler(r#in: &[u16], out: &mut [u16], cargo: &mut dyn Any) -> bool {
+    let Some(bchsw) = cargo.downcast_mut::<BchswAdjusts>() else {
+        return false;
+    };
+
+    let lch_in = Lab::from_encoded(r#in).to_lch();
+
+    // Do some adjusts on LCh
+    let lch_out = LCh {
+        l: lch_in.l * bchsw.contrast + bchsw.brightness,
+        c: lch_in.c + bchsw.saturation,
+        h: lch_in.h + bchsw.hue,
+    };
+
+    let mut lab_out = lch_out.to_lab();
+
+    // Move white point in Lab
+    if let Some((wp_src, wp_dest)) = bchsw.white_points {
+        lab_out = lab_out.to_xyz(wp_src).to_lab(wp_dest);
+    }
+
+    // Back to encoded
+    out[..3].copy_from_slice(&lab_out.to_encoded());
+
+    true
+}
+
+// Reduces C, M and Y by the same ratio, until the total ink coverage fits the limit
+fn ink_limiting_sampler(r#in: &[u16], out: &mut [u16], cargo: &mut dyn Any) -> bool {
+    let Some(&mut limit) = cargo.downcast_mut::<f64>() else {
+        return false;
+    };
+
+    let ink_limit = limit * 655.35;
+
+    let sum_cmy = r#in[0] as f64 + r#in[1] as f64 + r#in[2] as f64;
+    let sum_cmyk = sum_cmy + r#in[3] as f64;
+
+    let ratio = if sum_cmyk > ink_limit {
+        (1.0 - ((sum_cmyk - ink_limit) / sum_cmy)).max(0.0)
+    } else {
+        1.0
+    };
+
+    out[0] = quick_saturate_word(r#in[0] as f64 * ratio); // C
+    out[1] = quick_saturate_word(r#in[1] as f64 * ratio); // M
+    out[2] = quick_saturate_word(r#in[2] as f64 * ratio); // Y
+
+    out[3] = r#in[3]; // K (untouched)
+
+    true
+}
+
+// A 2-point CLUT that maps every node to itself
+fn identity_clut(context_id: &'static Context) -> Result<Stage> {
+    let table = (0..8u16)
+        .flat_map(|i| [(i >> 2) & 1, (i >> 1) & 1, i & 1].map(|bit| bit * 0xffff))
+        .collect::<Vec<_>>();
+
+    Stage::new_clut_16bit(context_id, 2, 3, 3, Some(&table))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        sig,
+        types::{CLutStageData, Pipeline, Profile, Signature, ToneCurve, XYYTriple, XYY, XYZ},
+        DEFAULT_CONTEXT,
+    };
+
+    use super::D65;
+
+    const SRGB_PRIMARIES: [XYY; 3] = [super::xyy(0.64, 0.33), super::xyy(0.30, 0.60), super::xyy(0.15, 0.06)];
+
+    fn gamma(value: f64) -> ToneCurve {
+        ToneCurve::build_parametric(&DEFAULT_CONTEXT, 1, &[value]).unwrap()
+    }
+
+    fn assert_xyz(xyz: &XYZ, expected: [f64; 3]) {
+        for (value, expected) in [xyz.x, xyz.y, xyz.z].into_iter().zip(expected) {
+            assert!((value - expected).abs() < 1e-3, "{} != {}", value, expected);
+        }
+    }
+
+    #[test]
+    fn srgb_colorants() {
+        let mut profile = Profile::new_srgb().unwrap();
+
+        assert!(profile.device_class() == sig::class::DISPLAY);
+        assert!(profile.color_space() == sig::colorspace::RGB);
+        assert!(profile.pcs() == sig::colorspace::XYZ);
+
+        for (tag, expected) in [
+            (sig::tags::RED_COLORANT, [0.4361, 0.2225, 0.0139]),
+            (sig::tags::GREEN_COLORANT, [0.3851, 0.7169, 0.0971]),
+            (sig::tags::BLUE_COLORANT, [0.1431, 0.0606, 0.7141]),
+        ] {
+            assert_xyz(profile.read_tag::<XYZ>(tag).unwrap(), expected);
+        }
+
+        // All three channels share the same curve
+        assert!(profile.tag_linked_to(sig::tags::GREEN_TRC) == Some(sig::tags::RED_TRC));
+        assert!(profile.tag_linked_to(sig::tags::BLUE_TRC) == Some(sig::tags::RED_TRC));
+
+        // Colorants still add up to the D50 white once saved
+        let mem = profile.save_to_mem().unwrap();
+        let mut profile = Profile::open_mem(&mem).unwrap();
+
+        let mut white = [0.0; 3];
+        for tag in [sig::tags::RED_COLORANT, sig::tags::GREEN_COLORANT, sig::tags::BLUE_COLORANT] {
+            let xyz = profile.read_tag::<XYZ>(tag).unwrap();
+            white[0] += xyz.x;
+            white[1] += xyz.y;
+            white[2] += xyz.z;
+        }
+        assert_xyz(&XYZ { x: white[0], y: white[1], z: white[2] }, [0.9642, 1.0, 0.8249]);
+    }
+
+    fn eval_a_to_b0(profile: &mut Profile, r#in: &[u16]) -> Vec<u16> {
+        let lut = profile.read_tag::<Pipeline>(sig::tags::A_TO_B0).unwrap();
+        let mut out = vec![0u16; lut.output_channels() as usize];
+        lut.eval_16(r#in, &mut out);
+        out
+    }
+
+    #[test]
+    fn identity_profiles() {
+        let lab = [0x8000, 0x4000, 0xc000];
+
+        for (profile, version, space) in [
+            (Profile::new_lab_v2(None), 2.1, sig::colorspace::LAB),
+            (Profile::new_lab_v4(None), 4.4, sig::colorspace::LAB),
+            (Profile::new_xyz(), 4.4, sig::colorspace::XYZ),
+        ] {
+            let mem = profile.unwrap().save_to_mem().unwrap();
+            let mut profile = Profile::open_mem(&mem).unwrap();
+
+            assert_eq!(profile.version(), version);
+            assert!(profile.device_class() == sig::class::ABSTRACT);
+            assert!(profile.color_space() == space);
+            assert!(profile.pcs() == space);
+            for (value, expected) in eval_a_to_b0(&mut profile, &lab).into_iter().zip(lab) {
+                assert!(value.abs_diff(expected) <= 1, "{} != {}", value, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn null_profile_yields_zero() {
+        let mem = Profile::new_null().unwrap().save_to_mem().unwrap();
+        let mut profile = Profile::open_mem(&mem).unwrap();
+
+        assert!(profile.device_class() == sig::class::OUTPUT);
+        assert!(profile.color_space() == sig::colorspace::GRAY);
+
+        let lut = profile.read_tag::<Pipeline>(sig::tags::B_TO_A0).unwrap();
+        for lab in [[0, 0x8000, 0x8000], [0xffff, 0x8000, 0x8000], [0x8000, 0, 0xffff]] {
+            let mut out = [0xffff];
+            lut.eval_16(&lab, &mut out);
+            assert_eq!(out, [0]);
+        }
+    }
+
+    #[test]
+    fn rgb_round_trip() {
+        let curves = [gamma(2.2), gamma(1.8), gamma(2.2)];
+        let mut profile = Profile::new_rgb(D65, SRGB_PRIMARIES, curves).unwrap();
+
+        let mem = profile.save_to_mem().unwrap();
+        let mut profile = Profile::open_mem(&mem).unwrap();
+
+        assert!(profile.device_class() == sig::class::DISPLAY);
+        assert!(profile.color_space() == sig::colorspace::RGB);
+
+        // Only the curves equal to the red one are linked
+        assert!(profile.tag_linked_to(sig::tags::GREEN_TRC).is_none());
+        assert!(profile.tag_linked_to(sig::tags::BLUE_TRC) == Some(sig::tags::RED_TRC));
+
+        let chromaticity = profile.read_tag::<XYYTriple>(sig::tags::CHROMATICITY).unwrap();
+        for (read, primary) in [chromaticity.red, chromaticity.green, chromaticity.blue]
+            .into_iter()
+            .zip(SRGB_PRIMARIES)
+        {
+            assert!((read.x - primary.x).abs() < 1e-4 && (read.y - primary.y).abs() < 1e-4);
+        }
+
+        // Same primaries and white as sRGB, so same colorants
+        assert_xyz(
+            profile.read_tag::<XYZ>(sig::tags::RED_COLORANT).unwrap(),
+            [0.4361, 0.2225, 0.0139],
+        );
+    }
+
+    #[test]
+    fn invalid_white_point_is_rejected() {
+        for white_point in [XYY { y: 0.0, ..D65 }, XYY { x: -0.1, ..D65 }, XYY { x: 0.8, ..D65 }] {
+            let curves = [gamma(2.2), gamma(2.2), gamma(2.2)];
+            assert!(Profile::new_rgb(white_point, SRGB_PRIMARIES, curves).is_err());
+        }
+    }
+
+    #[test]
+    fn rgb_presets() {
+        for profile in [
+            Profile::new_display_p3(),
+            Profile::new_rec709(),
+            Profile::new_rec2020(),
+            Profile::new_adobe_rgb(),
+            Profile::new_prophoto_rgb(),
+            Profile::new_acescg(),
+        ] {
+            let mut profile = profile.unwrap();
+
+            // Colorants are adapted to D50, whatever the white of the space
+            let mut white = [0.0; 3];
+            for tag in [sig::tags::RED_COLORANT, sig::tags::GREEN_COLORANT, sig::tags::BLUE_COLORANT] {
+                let xyz = profile.read_tag::<XYZ>(tag).unwrap();
+                white[0] += xyz.x;
+                white[1] += xyz.y;
+                white[2] += xyz.z;
+            }
+            assert_xyz(&XYZ { x: white[0], y: white[1], z: white[2] }, [0.9642, 1.0, 0.8249]);
+
+            assert!(profile.save_to_mem().is_ok());
+        }
+
+        // ProPhoto is defined on D50, so needs (almost) no adaptation
+        let mut profile = Profile::new_prophoto_rgb().unwrap();
+        let chad = profile.read_tag::<Vec<f64>>(sig::tags::CHROMATIC_ADAPTATION).unwrap();
+        for (i, value) in chad.iter().enumerate() {
+            let expected = if i % 4 == 0 { 1.0 } else { 0.0 };
+            assert!((value - expected).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn gray_round_trip() {
+        let mut profile = Profile::new_gray(D65, gamma(2.2)).unwrap();
+        let mem = profile.save_to_mem().unwrap();
+        let mut profile = Profile::open_mem(&mem).unwrap();
+
+        assert!(profile.device_class() == sig::class::DISPLAY);
+        assert!(profile.color_space() == sig::colorspace::GRAY);
+        assert!(profile.pcs() == sig::colorspace::XYZ);
+
+        assert_xyz(
+            profile.read_tag::<XYZ>(sig::tags::MEDIA_WHITE_POINT).unwrap(),
+            [0.9505, 1.0, 1.0891],
+        );
+
+        let curve = profile.read_tag::<ToneCurve>(sig::tags::GRAY_TRC).unwrap();
+        assert_eq!(curve.parametric_type(), 1);
+        assert!((curve.eval_f32(0.5) - 0.5f32.powf(2.2)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn gray_invalid_white_point_is_rejected() {
+        assert!(Profile::new_gray(XYY { y: 0.0, ..D65 }, gamma(2.2)).is_err());
+    }
+
+    #[test]
+    fn ink_limit_is_honored() {
+        let limit = 250.0;
+        let mem = Profile::new_ink_limiting_device_link(sig::colorspace::CMYK, limit)
+            .unwrap()
+            .save_to_mem()
+            .unwrap();
+        let mut profile = Profile::open_mem(&mem).unwrap();
+
+        assert!(profile.device_class() == sig::class::LINK);
+        assert!(profile.is_tag(sig::tags::PROFILE_SEQUENCE_DESC));
+
+        let sum = |values: &[u16]| values.iter().map(|&v| v as f64).sum::<f64>();
+        let steps = (0..=0xffffu32).step_by(0x2aaa).map(|v| v as u16).collect::<Vec<_>>();
+        for &c in &steps {
+            for &m in &steps {
+                for &y in &steps {
+                    for &k in &steps {
+                        let cmyk = [c, m, y, k];
+                        let out = eval_a_to_b0(&mut profile, &cmyk);
+
+                        // Black is left alone, C, M and Y are only reduced
+                        assert!(out[3].abs_diff(k) <= 1);
+                        for (out, r#in) in out[..3].iter().zip(&cmyk) {
+                            assert!(*out <= r#in.saturating_add(1));
+                        }
+
+                        // Black alone may go over the limit
+                        let max_sum = (limit * 655.35).max(k as f64);
+                        assert!(sum(&out) <= max_sum + 4.0, "{:?} -> {:?}", cmyk, out);
+
+                        // Coverages whose whole CLUT cell is within the limit are kept
+                        if sum(&cmyk) + 4.0 * 65535.0 / 16.0 <= max_sum {
+                            for (out, r#in) in out.iter().zip(&cmyk) {
+                                assert!(out.abs_diff(*r#in) <= 1);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn ink_limit_needs_cmyk() {
+        assert!(Profile::new_ink_limiting_device_link(sig::colorspace::RGB, 250.0).is_err());
+
+        // Out of range limits are clamped
+        let mut profile = Profile::new_ink_limiting_device_link(sig::colorspace::CMYK, 500.0).unwrap();
+        let cmyk = [0xffff; 4];
+        assert_eq!(eval_a_to_b0(&mut profile, &cmyk), cmyk);
+    }
+
+    #[test]
+    fn linearization_applies_curves() {
+        let curves = [gamma(1.0), gamma(2.0), gamma(0.5)];
+        let mem = Profile::new_linearization_device_link(sig::colorspace::RGB, &curves)
+            .unwrap()
+            .save_to_mem()
+            .unwrap();
+        let mut profile = Profile::open_mem(&mem).unwrap();
+
+        assert!(profile.device_class() == sig::class::LINK);
+        assert!(profile.color_space() == sig::colorspace::RGB);
+        assert!(profile.pcs() == sig::colorspace::RGB);
+        assert!(profile.is_tag(sig::tags::PROFILE_SEQUENCE_DESC));
+        assert!(profile.is_tag(sig::tags::PROFILE_SEQUENCE_ID));
+
+        let out = eval_a_to_b0(&mut profile, &[0x8000; 3]);
+        for (out, expected) in out.into_iter().zip([0x8000, 0x4000, 0xb505]) {
+            assert!(out.abs_diff(expected) <= 2, "{} != {}", out, expected);
+        }
+    }
+
+    #[test]
+    fn linearization_needs_one_curve_per_channel() {
+        let curves = [gamma(1.0), gamma(2.0)];
+        assert!(Profile::new_linearization_device_link(sig::colorspace::RGB, &curves).is_err());
+        assert!(Profile::new_linearization_device_link(Signature(0), &curves).is_err());
+    }
+
+    fn bchsw_clut(profile: &mut Profile) -> Vec<u16> {
+        let lut = profile.read_tag::<Pipeline>(sig::tags::A_TO_B0).unwrap();
+        let clut = lut.stages()[1].data::<CLutStageData<u16>>().unwrap();
+
+        clut.table().to_vec()
+    }
+
+    #[test]
+    fn bchsw_identity() {
+        let n_points = 9;
+        let mem = Profile::new_bchsw_abstract(n_points, 0.0, 1.0, 0.0, 0.0, 5000, 5000)
+            .unwrap()
+            .save_to_mem()
+            .unwrap();
+        let mut profile = Profile::open_mem(&mem).unwrap();
+
+        assert!(profile.device_class() == sig::class::ABSTRACT);
+        assert!(profile.color_space() == sig::colorspace::LAB);
+        assert!(profile.pcs() == sig::colorspace::LAB);
+
+        // Every node maps to itself, the last input varying fastest
+        let table = bchsw_clut(&mut profile);
+        assert_eq!(table.len(), (n_points * n_points * n_points * 3) as usize);
+
+        let node = |i: u32| (i as f64 * 65535.0 / (n_points - 1) as f64 + 0.5).floor() as u16;
+        for (index, lab) in table.chunks(3).enumerate() {
+            let index = index as u32;
+            let (l, a, b) = (index / (n_points * n_points), index / n_points % n_points, index % n_points);
+            let expected = [l, a, b].map(node);
+
+            for (value, expected) in lab.iter().zip(expected) {
+                assert!(value.abs_diff(expected) <= 1, "{:?} != {:?}", lab, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn bchsw_adjusts() {
+        // Brightness alone raises L*, which is encoded as 0..100 -> 0..0xffff
+        let mut profile = Profile::new_bchsw_abstract(9, 10.0, 1.0, 0.0, 0.0, 5000, 5000).unwrap();
+        let out = eval_a_to_b0(&mut profile, &[0x8000, 0x8080, 0x8080]);
+        assert!(out[0].abs_diff(0x8000 + 6554) <= 2, "{:?}", out);
+        assert!(out[1].abs_diff(0x8080) <= 1 && out[2].abs_diff(0x8080) <= 1, "{:?}", out);
+
+        // Changing the white moves neutrals away from the axis
+        let mut profile = Profile::new_bchsw_abstract(9, 0.0, 1.0, 0.0, 0.0, 5000, 9000).unwrap();
+        let out = eval_a_to_b0(&mut profile, &[0x8000, 0x8080, 0x8080]);
+        assert!(out[2].abs_diff(0x8080) > 0x100, "{:?}", out);
+
+        assert!(Profile::new_bchsw_abstract(9, 0.0, 1.0, 0.0, 0.0, 5000, 100).is_err());
+    }
+}
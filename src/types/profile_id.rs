@@ -1,3 +1,4 @@
+#[derive(Copy, Clone)]
 pub union ProfileID {
     pub id8: [u8;16],
     pub id16: [u16;8],
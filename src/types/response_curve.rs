@@ -0,0 +1,25 @@
+use super::{Signature, XYZ};
+
+/// A device code and the measurement obtained for it.
+#[derive(Clone, Copy)]
+pub struct Response16 {
+    pub device_code: u16,
+    pub measurement: f64,
+}
+
+/// Measurements of a single measurement unit, such as `sig::response_curve::STATUS_T`.
+#[derive(Clone)]
+pub struct ResponseCurve {
+    pub measurement_unit: Signature,
+    /// PCS XYZ of the solid of each channel.
+    pub solid_xyz: Vec<XYZ>,
+    /// Response of each channel, one array per channel.
+    pub responses: Vec<Vec<Response16>>,
+}
+
+/// Densitometric responses of an output device, as stored in the output response tag.
+#[derive(Clone)]
+pub struct ResponseCurveSet {
+    pub n_channels: usize,
+    pub curves: Vec<ResponseCurve>,
+}
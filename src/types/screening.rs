@@ -1,11 +1,13 @@
 use crate::MAX_CHANNELS;
 
+#[derive(Clone, Copy)]
 pub struct ScreeningChannel {
     pub frequency: f64,
     pub screen_angle: f64,
     pub spot_shape: u32,
 }
 
+#[derive(Clone, Copy)]
 pub struct Screening {
     pub flag: u32,
     pub n_channels: u32,
@@ -1,7 +1,10 @@
-use crate::Context;
+use log::Level;
+
+use crate::{signal_error, state::ErrorCode, Context, Result};
 
 use super::{ProfileID, Signature, MLU};
 
+#[derive(Clone)]
 pub struct PSeqDesc {
     pub device_mfg: Signature,
     pub device_model: Signature,
@@ -13,8 +16,50 @@ pub struct PSeqDesc {
     pub description: Box<MLU>,
 }
 
+#[derive(Clone)]
 pub struct Seq {
-    pub n: u32,
     pub context_id: &'static Context,
-    pub seq: PSeqDesc,
+    pub seq: Vec<PSeqDesc>,
+}
+
+impl PSeqDesc {
+    /// An empty description, with no texts.
+    pub fn new(context_id: &Context) -> PSeqDesc {
+        PSeqDesc {
+            device_mfg: Signature(0),
+            device_model: Signature(0),
+            attributes: 0,
+            technology: Signature(0),
+            profile_id: ProfileID { id8: [0; 16] },
+            manufacturer: Box::new(MLU::new(context_id, 0)),
+            model: Box::new(MLU::new(context_id, 0)),
+            description: Box::new(MLU::new(context_id, 0)),
+        }
+    }
+}
+
+impl Seq {
+    /// Allocates a sequence of `n` empty descriptions.
+    pub fn new(context_id: &'static Context, n: usize) -> Result<Seq> {
+        // In a absolutely arbitrary way, I hereby decide to allow a maxim of 255 profiles linked
+        // in a devicelink. It makes not sense anyway and may be used for exploits, so let's close the door!
+        if n == 0 || n > 255 {
+            let msg = format!("Invalid number of profiles in sequence '{}'", n);
+            signal_error(context_id, Level::Error, ErrorCode::Range, &msg);
+            return Err(msg);
+        }
+
+        Ok(Seq {
+            context_id,
+            seq: vec![PSeqDesc::new(context_id); n],
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.seq.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.seq.is_empty()
+    }
 }
@@ -1,13 +1,764 @@
-use crate::plugin::ParametricCurveEvaluator;
+use log::Level;
 
-use super::{CurveSegment, InterpParams};
+use crate::{
+    plugin::ParametricCurveEvaluator, quick_saturate_word, signal_error, state::ErrorCode,
+    Context, Result, MATRIX_DET_TOLERANCE,
+};
 
-pub struct ToneCurve<'a> {
+use super::{lerp_flag, CurveSegment, InterpFunction, InterpParams};
+
+#[derive(Clone)]
+pub struct ToneCurve {
     pub(crate) interp_params: InterpParams<u16>,
-    pub(crate) n_segments: usize,
-    pub(crate) segments: &'a [CurveSegment<'a>],
-    pub(crate) seg_interp: &'a [InterpParams<f32>],
-    pub(crate) evals: &'a [ParametricCurveEvaluator],
-    pub(crate) n_entries: usize,
-    pub(crate) table_16: &'a [u16],
+    pub(crate) segments: Vec<CurveSegment>,
+    pub(crate) seg_interp: Vec<Option<InterpParams<f32>>>,
+    pub(crate) evals: Vec<Option<ParametricCurveEvaluator>>,
+    pub(crate) table_16: Vec<u16>,
+}
+
+pub(crate) const MINUS_INF: f64 = -1e22f32 as f64;
+pub(crate) const PLUS_INF: f64 = 1e22f32 as f64;
+
+// The list of supported parametric curves, with the number of parameters of each one
+const DEFAULT_CURVES: [(i32, usize); 10] = [
+    (1, 1),
+    (2, 3),
+    (3, 4),
+    (4, 5),
+    (5, 7),
+    (6, 4),
+    (7, 5),
+    (8, 5),
+    (108, 1),
+    (109, 1),
+];
+
+impl ToneCurve {
+    /// Builds a curve made of segments. Each segment is either sampled (type 0) or parametric.
+    pub fn build_segmented(context_id: &Context, segments: &[CurveSegment]) -> Result<ToneCurve> {
+        // Optimization for identity curves.
+        let n_grid_points = if segments.len() == 1 && segments[0].r#type == 1 {
+            entries_by_gamma(segments[0].params[0])
+        } else {
+            4096
+        };
+
+        let mut g = Self::allocate(context_id, &vec![0; n_grid_points], segments)?;
+
+        // Once we have the floating point version, we can approximate a 16 bit table of 4096 entries
+        // for performance reasons. This table would normally not be used except on 8/16 bits transforms.
+        let table: Vec<u16> = (0..n_grid_points)
+            .map(|i| {
+                let r = i as f64 / (n_grid_points - 1) as f64;
+
+                // Round and saturate
+                quick_saturate_word(g.eval_segmented(r) * 65535.0)
+            })
+            .collect();
+
+        g.interp_params.table = table.clone().into();
+        g.table_16 = table;
+
+        Ok(g)
+    }
+
+    /// Builds a parametric curve. Negative types stand for the inverse of the function.
+    pub fn build_parametric(context_id: &Context, r#type: i32, params: &[f64]) -> Result<ToneCurve> {
+        let Some((_, n_params)) = get_parametric_curve_by_type(context_id, r#type) else {
+            let msg = format!("Invalid parametric curve type {}", r#type);
+            signal_error(context_id, Level::Error, ErrorCode::UnknownExtension, &msg);
+            return Err(msg);
+        };
+
+        if params.len() < n_params {
+            let msg = format!(
+                "Parametric curve type {} needs {} parameters, got {}",
+                r#type,
+                n_params,
+                params.len()
+            );
+            signal_error(context_id, Level::Error, ErrorCode::Range, &msg);
+            return Err(msg);
+        }
+
+        let mut seg0 = CurveSegment {
+            x0: MINUS_INF,
+            x1: PLUS_INF,
+            r#type,
+            params: [0.0; 10],
+            sampled_points: Vec::new(),
+        };
+        seg0.params[..n_params].copy_from_slice(&params[..n_params]);
+
+        Self::build_segmented(context_id, &[seg0])
+    }
+
+    /// Builds a curve from a table of 16 bit values, evenly spaced in the domain.
+    pub fn build_tabulated_16(context_id: &Context, values: &[u16]) -> Result<ToneCurve> {
+        Self::allocate(context_id, values, &[])
+    }
+
+    /// Builds a curve from a table of floating point values, evenly spaced in 0..1. Values outside
+    /// the domain are clamped to the first and last samples.
+    pub fn build_tabulated_float(context_id: &Context, values: &[f32]) -> Result<ToneCurve> {
+        // Do some housekeeping
+        if values.is_empty() {
+            let msg = "Couldn't create tone curve from an empty table";
+            signal_error(context_id, Level::Error, ErrorCode::Range, msg);
+            return Err(msg.into());
+        }
+
+        let constant = |x0, x1, value: f32| {
+            let mut params = [0.0; 10];
+            params[0] = 1.0;
+            params[3] = value as f64;
+
+            CurveSegment {
+                x0,
+                x1,
+                r#type: 6,
+                params,
+                sampled_points: Vec::new(),
+            }
+        };
+
+        let segments = [
+            // A segmented curve for negative values: constant values[0]
+            constant(MINUS_INF, 0.0, values[0]),
+            // From zero to 1
+            CurveSegment {
+                x0: 0.0,
+                x1: 1.0,
+                r#type: 0,
+                params: [0.0; 10],
+                sampled_points: values.to_vec(),
+            },
+            // Final segment is constant = lastsample
+            constant(1.0, PLUS_INF, values[values.len() - 1]),
+        ];
+
+        Self::build_segmented(context_id, &segments)
+    }
+
+    fn allocate(
+        context_id: &Context,
+        values: &[u16],
+        segments: &[CurveSegment],
+    ) -> Result<ToneCurve> {
+        let n_entries = values.len();
+
+        // We allow huge tables, which are then restricted for smoothing operations
+        if n_entries > 65530 {
+            let msg = "Couldn't create tone curve of more than 65530 entries";
+            signal_error(context_id, Level::Error, ErrorCode::Range, msg);
+            return Err(msg.into());
+        }
+
+        if n_entries == 0 && segments.is_empty() {
+            let msg = "Couldn't create tone curve with zero segments and no table";
+            signal_error(context_id, Level::Error, ErrorCode::Range, msg);
+            return Err(msg.into());
+        }
+
+        // Initialize the segments stuff. The evaluator for each segment is located and a pointer to it
+        // is placed in advance to maximize performance.
+        let mut seg_interp = Vec::with_capacity(segments.len());
+        let mut evals = Vec::with_capacity(segments.len());
+        for segment in segments {
+            if segment.r#type == 0 && segment.sampled_points.is_empty() {
+                let msg = "Couldn't create tone curve with an empty sampled segment";
+                signal_error(context_id, Level::Error, ErrorCode::Range, msg);
+                return Err(msg.into());
+            }
+
+            // Type 0 is a special marker for table-based curves
+            seg_interp.push(if segment.r#type == 0 {
+                Some(InterpParams::compute(
+                    context_id,
+                    segment.sampled_points.len(),
+                    1,
+                    1,
+                    &segment.sampled_points,
+                    lerp_flag::FLOAT,
+                )?)
+            } else {
+                None
+            });
+
+            evals.push(get_parametric_curve_by_type(context_id, segment.r#type).map(|(eval, _)| eval));
+        }
+
+        let interp_params = InterpParams::compute(context_id, n_entries, 1, 1, values, lerp_flag::U16_BITS)?;
+
+        Ok(ToneCurve {
+            interp_params,
+            segments: segments.to_vec(),
+            seg_interp,
+            evals,
+            table_16: values.to_vec(),
+        })
+    }
+
+    // Evaluate a segmented function for a single value. Return -Inf if no valid segment found .
+    // If fn type is 0, perform an interpolation on the table
+    fn eval_segmented(&self, r: f64) -> f64 {
+        for i in (0..self.segments.len()).rev() {
+            let segment = &self.segments[i];
+
+            // Check for domain
+            if r > segment.x0 && r <= segment.x1 {
+                let out = match (&self.seg_interp[i], self.evals[i]) {
+                    // Type == 0 means segment is sampled
+                    (Some(params), _) if segment.r#type == 0 => {
+                        let r1 = ((r - segment.x0) / (segment.x1 - segment.x0)) as f32;
+                        let mut out32 = [0f32];
+
+                        if let InterpFunction::F32(lerp) = params.interpolation {
+                            lerp(&[r1], &mut out32, params);
+                        }
+                        out32[0] as f64
+                    }
+                    (_, Some(eval)) => eval(segment.r#type, segment.params, r),
+                    _ => 0.0,
+                };
+
+                if out.is_infinite() {
+                    return if out > 0.0 { PLUS_INF } else { MINUS_INF };
+                }
+
+                return out;
+            }
+        }
+
+        MINUS_INF
+    }
+
+    /// Evaluates the curve in floating point.
+    pub fn eval_f32(&self, v: f32) -> f32 {
+        // Check for 16 bits table. If so, this is a limited-precision tone curve
+        if self.segments.is_empty() {
+            let r#in = quick_saturate_word(v as f64 * 65535.0);
+            let out = self.eval_16(r#in);
+
+            return (out as f64 / 65535.0) as f32;
+        }
+
+        self.eval_segmented(v as f64) as f32
+    }
+
+    /// Evaluates the curve using the 16 bit table.
+    pub fn eval_16(&self, v: u16) -> u16 {
+        let mut out = [0u16];
+
+        if let InterpFunction::U16(lerp) = self.interp_params.interpolation {
+            lerp(&[v], &mut out, &self.interp_params);
+        }
+
+        out[0]
+    }
+
+    /// Returns the parametric type for single segment curves, or 0 if the curve is not parametric.
+    pub fn parametric_type(&self) -> i32 {
+        if self.segments.len() != 1 {
+            return 0;
+        }
+
+        self.segments[0].r#type
+    }
+
+    /// Returns the parameters of a parametric curve.
+    pub fn params(&self) -> Option<&[f64; 10]> {
+        if self.segments.len() != 1 {
+            return None;
+        }
+
+        Some(&self.segments[0].params)
+    }
+
+    pub fn segments(&self) -> &[CurveSegment] {
+        &self.segments
+    }
+
+    /// Returns the 16 bit approximation of the curve.
+    pub fn table_16(&self) -> &[u16] {
+        &self.table_16
+    }
+
+    pub fn is_multisegment(&self) -> bool {
+        self.segments.len() > 1
+    }
+
+    /// Returns true if the curve goes down, judging by its end points.
+    pub fn is_descending(&self) -> bool {
+        self.table_16[0] > self.table_16[self.table_16.len() - 1]
+    }
+
+    /// Computes the inverse of the curve, sampled in 4096 points unless it can be done analytically.
+    pub fn reverse(&self) -> Result<ToneCurve> {
+        self.reverse_ex(4096)
+    }
+
+    /// Computes the inverse of the curve, sampled in `n_result_samples` points unless it can be done
+    /// analytically.
+    pub fn reverse_ex(&self, n_result_samples: usize) -> Result<ToneCurve> {
+        let context_id = &self.interp_params.context;
+
+        // Try to reverse it analytically whatever possible
+        if self.segments.len() == 1
+            && self.segments[0].r#type > 0
+            && get_parametric_curve_by_type(context_id, self.segments[0].r#type).is_some()
+        {
+            return Self::build_parametric(
+                context_id,
+                -self.segments[0].r#type,
+                &self.segments[0].params,
+            );
+        }
+
+        if n_result_samples < 2 {
+            let msg = "Couldn't reverse a curve into less than 2 samples";
+            signal_error(context_id, Level::Error, ErrorCode::Range, msg);
+            return Err(msg.into());
+        }
+
+        // Nope, reverse the table.
+        let table = &self.table_16;
+        let n_entries = table.len();
+
+        // We want to know if this is an ascending or descending table
+        let ascending = !self.is_descending();
+
+        let (mut a, mut b) = (0.0, 0.0);
+        let mut out = vec![0u16; n_result_samples];
+
+        // Iterate across Y axis
+        for (i, value) in out.iter_mut().enumerate() {
+            let y = i as f64 * 65535.0 / (n_result_samples - 1) as f64;
+
+            // Find interval in which y is within.
+            if let Some(j) = get_interval(y, table) {
+                // Get limits of interval
+                let x1 = table[j] as f64;
+                let x2 = table[j + 1] as f64;
+
+                let y1 = (j as f64 * 65535.0) / (n_entries - 1) as f64;
+                let y2 = ((j + 1) as f64 * 65535.0) / (n_entries - 1) as f64;
+
+                // If collapsed, then use any
+                if x1 == x2 {
+                    *value = quick_saturate_word(if ascending { y2 } else { y1 });
+                    continue;
+                }
+
+                // Interpolate
+                a = (y2 - y1) / (x2 - x1);
+                b = y2 - a * x2;
+            }
+
+            *value = quick_saturate_word(a * y + b);
+        }
+
+        Self::build_tabulated_16(context_id, &out)
+    }
+}
+
+// Locates the interval of the table that holds the given value, searching from the end on ascending
+// tables and from the start on descending ones.
+fn get_interval(r#in: f64, lut_table: &[u16]) -> Option<usize> {
+    // A 1 point table is not allowed
+    if lut_table.len() < 2 {
+        return None;
+    }
+
+    let within = |i: usize| {
+        let (y0, y1) = (lut_table[i] as f64, lut_table[i + 1] as f64);
+
+        if y0 <= y1 {
+            // Increasing
+            r#in >= y0 && r#in <= y1
+        } else {
+            // Decreasing
+            r#in >= y1 && r#in <= y0
+        }
+    };
+
+    let domain = lut_table.len() - 1;
+
+    // Let's see if ascending or descending.
+    if lut_table[0] < lut_table[domain] {
+        // Table is overall ascending
+        (0..domain).rev().find(|&i| within(i))
+    } else {
+        // Table is overall descending
+        (0..domain).find(|&i| within(i))
+    }
+}
+
+// Search for the evaluator of a given type. Plug-in curves take precedence over the default ones.
+fn get_parametric_curve_by_type(
+    context_id: &Context,
+    r#type: i32,
+) -> Option<(ParametricCurveEvaluator, usize)> {
+    let plugin = context_id.curves.iter().find_map(|c| {
+        (0..c.n_functions as usize)
+            .find(|&i| c.function_types[i] as i32 == r#type.abs())
+            .map(|i| (c.evaluator, c.parameter_count[i] as usize))
+    });
+
+    plugin.or_else(|| {
+        DEFAULT_CURVES
+            .iter()
+            .find(|(t, _)| *t == r#type.abs())
+            .map(|&(_, n)| (default_eval_parametric_fn as ParametricCurveEvaluator, n))
+    })
+}
+
+// Identity curves need just two entries
+fn entries_by_gamma(gamma: f64) -> usize {
+    if (gamma - 1.0).abs() < 0.001 {
+        2
+    } else {
+        4096
+    }
+}
+
+fn sigmoid_base(k: f64, t: f64) -> f64 {
+    (1.0 / (1.0 + (-k * t).exp())) - 0.5
+}
+
+fn inverted_sigmoid_base(k: f64, t: f64) -> f64 {
+    -((1.0 / (t + 0.5)) - 1.0).ln() / k
+}
+
+fn sigmoid_factory(k: f64, t: f64) -> f64 {
+    let correction = 0.5 / sigmoid_base(k, 1.0);
+
+    correction * sigmoid_base(k, 2.0 * t - 1.0) + 0.5
+}
+
+fn inverse_sigmoid_factory(k: f64, t: f64) -> f64 {
+    let correction = 0.5 / sigmoid_base(k, 1.0);
+
+    (inverted_sigmoid_base(k, (t - 0.5) / correction) + 1.0) / 2.0
+}
+
+// Parametric Fn using floating point
+fn default_eval_parametric_fn(r#type: i32, params: [f64; 10], r: f64) -> f64 {
+    let p = &params;
+    let tiny = |v: f64| v.abs() < MATRIX_DET_TOLERANCE;
+
+    match r#type {
+        // X = Y ^ Gamma
+        1 => {
+            if r < 0.0 {
+                if tiny(p[0] - 1.0) {
+                    r
+                } else {
+                    0.0
+                }
+            } else {
+                r.powf(p[0])
+            }
+        }
+
+        // Type 1 Reversed: X = Y ^1/gamma
+        -1 => {
+            if r < 0.0 {
+                if tiny(p[0] - 1.0) {
+                    r
+                } else {
+                    0.0
+                }
+            } else if tiny(p[0]) {
+                PLUS_INF
+            } else {
+                r.powf(1.0 / p[0])
+            }
+        }
+
+        // CIE 122-1966
+        // Y = (aX + b)^Gamma  | X >= -b/a
+        // Y = 0               | else
+        2 => {
+            if tiny(p[1]) {
+                0.0
+            } else {
+                let disc = -p[2] / p[1];
+                let e = p[1] * r + p[2];
+
+                if r >= disc && e > 0.0 {
+                    e.powf(p[0])
+                } else {
+                    0.0
+                }
+            }
+        }
+
+        // Type 2 Reversed
+        // X = (Y ^1/g  - b) / a
+        -2 => {
+            if tiny(p[0]) || tiny(p[1]) || r < 0.0 {
+                0.0
+            } else {
+                ((r.powf(1.0 / p[0]) - p[2]) / p[1]).max(0.0)
+            }
+        }
+
+        // IEC 61966-3
+        // Y = (aX + b)^Gamma + c | X <= -b/a
+        // Y = c                  | else
+        3 => {
+            if tiny(p[1]) {
+                0.0
+            } else {
+                let disc = (-p[2] / p[1]).max(0.0);
+
+                if r >= disc {
+                    let e = p[1] * r + p[2];
+
+                    if e > 0.0 {
+                        e.powf(p[0]) + p[3]
+                    } else {
+                        0.0
+                    }
+                } else {
+                    p[3]
+                }
+            }
+        }
+
+        // Type 3 reversed
+        // X=((Y-c)^1/g - b)/a      | (Y>=c)
+        // X=-b/a                   | (Y<c)
+        -3 => {
+            if tiny(p[0]) || tiny(p[1]) {
+                0.0
+            } else if r >= p[3] {
+                let e = r - p[3];
+
+                if e > 0.0 {
+                    (e.powf(1.0 / p[0]) - p[2]) / p[1]
+                } else {
+                    0.0
+                }
+            } else {
+                -p[2] / p[1]
+            }
+        }
+
+        // IEC 61966-2.1 (sRGB)
+        // Y = (aX + b)^Gamma | X >= d
+        // Y = cX             | X < d
+        4 => {
+            if r >= p[4] {
+                let e = p[1] * r + p[2];
+
+                if e > 0.0 {
+                    e.powf(p[0])
+                } else {
+                    0.0
+                }
+            } else {
+                r * p[3]
+            }
+        }
+
+        // Type 4 reversed
+        // X=((Y^1/g-b)/a)    | Y >= (ad+b)^g
+        // X=Y/c              | Y< (ad+b)^g
+        -4 => {
+            let e = p[1] * p[4] + p[2];
+            let disc = if e < 0.0 { 0.0 } else { e.powf(p[0]) };
+
+            if r >= disc {
+                if tiny(p[0]) || tiny(p[1]) {
+                    0.0
+                } else {
+                    (r.powf(1.0 / p[0]) - p[2]) / p[1]
+                }
+            } else if tiny(p[3]) {
+                0.0
+            } else {
+                r / p[3]
+            }
+        }
+
+        // Y = (aX + b)^Gamma + e | X >= d
+        // Y = cX + f             | X < d
+        5 => {
+            if r >= p[4] {
+                let e = p[1] * r + p[2];
+
+                if e > 0.0 {
+                    e.powf(p[0]) + p[5]
+                } else {
+                    p[5]
+                }
+            } else {
+                r * p[3] + p[6]
+            }
+        }
+
+        // Reversed type 5
+        // X=((Y-e)1/g-b)/a   | Y >=(ad+b)^g+e), cd+f
+        // X=(Y-f)/c          | else
+        -5 => {
+            let disc = p[3] * p[4] + p[6];
+
+            if r >= disc {
+                let e = r - p[5];
+
+                if e < 0.0 || tiny(p[0]) || tiny(p[1]) {
+                    0.0
+                } else {
+                    (e.powf(1.0 / p[0]) - p[2]) / p[1]
+                }
+            } else if tiny(p[3]) {
+                0.0
+            } else {
+                (r - p[6]) / p[3]
+            }
+        }
+
+        // Types 6,7,8 comes from segmented curves as described in ICCSpecRevision_02_11_06_Float.pdf
+        // Type 6 is basically identical to type 5 without d
+
+        // Y = (a * X + b) ^ Gamma + c
+        6 => {
+            let e = p[1] * r + p[2];
+
+            // On gamma 1.0, don't clamp
+            if p[0] == 1.0 {
+                e + p[3]
+            } else if e < 0.0 {
+                p[3]
+            } else {
+                e.powf(p[0]) + p[3]
+            }
+        }
+
+        // ((Y - c) ^1/Gamma - b) / a
+        -6 => {
+            let e = r - p[3];
+
+            if tiny(p[0]) || tiny(p[1]) || e < 0.0 {
+                0.0
+            } else {
+                (e.powf(1.0 / p[0]) - p[2]) / p[1]
+            }
+        }
+
+        // Y = a * log (b * X^Gamma + c) + d
+        7 => {
+            let e = p[2] * r.powf(p[0]) + p[3];
+
+            if e <= 0.0 {
+                p[4]
+            } else {
+                p[1] * e.log10() + p[4]
+            }
+        }
+
+        // (Y - d) / a = log(b * X ^Gamma + c)
+        // pow(10, (Y-d) / a) = b * X ^Gamma + c
+        // pow((pow(10, (Y-d) / a) - c) / b, 1/g) = X
+        -7 => {
+            if tiny(p[0]) || tiny(p[1]) || tiny(p[2]) {
+                0.0
+            } else {
+                ((10f64.powf((r - p[4]) / p[1]) - p[3]) / p[2]).powf(1.0 / p[0])
+            }
+        }
+
+        // Y = a * b^(c*X+d) + e
+        8 => p[0] * p[1].powf(p[2] * r + p[3]) + p[4],
+
+        // Y = (log((y-e) / a) / log(b) - d ) / c
+        // a=0, b=1, c=2, d=3, e=4,
+        -8 => {
+            let disc = r - p[4];
+
+            if disc < 0.0 || tiny(p[0]) || tiny(p[2]) {
+                0.0
+            } else {
+                ((disc / p[0]).ln() / p[1].ln() - p[3]) / p[2]
+            }
+        }
+
+        // S-Shaped: (1 - (1-x)^1/g)^1/g
+        108 => {
+            if tiny(p[0]) {
+                0.0
+            } else {
+                (1.0 - (1.0 - r).powf(1.0 / p[0])).powf(1.0 / p[0])
+            }
+        }
+
+        // y = (1 - (1-x)^1/g)^1/g
+        // y^g = (1 - (1-x)^1/g)
+        // 1 - y^g = (1-x)^1/g
+        // (1 - y^g)^g = 1 - x
+        // 1 - (1 - y^g)^g
+        -108 => 1.0 - (1.0 - r.powf(p[0])).powf(p[0]),
+
+        // Sigmoidals
+        109 => sigmoid_factory(p[0], r),
+        -109 => inverse_sigmoid_factory(p[0], r),
+
+        // Unsupported parametric curve. Should never reach here
+        _ => 0.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        types::{CurveSegment, ToneCurve, MINUS_INF, PLUS_INF},
+        DEFAULT_CONTEXT,
+    };
+
+    #[test]
+    fn empty_sampled_segment_is_rejected() {
+        let segment = CurveSegment {
+            x0: MINUS_INF,
+            x1: PLUS_INF,
+            r#type: 0,
+            params: [0.0; 10],
+            sampled_points: Vec::new(),
+        };
+
+        assert!(ToneCurve::build_segmented(&DEFAULT_CONTEXT, &[segment]).is_err());
+    }
+
+    #[test]
+    fn reverse_parametric_and_tabulated() {
+        let gamma = ToneCurve::build_parametric(&DEFAULT_CONTEXT, 1, &[2.2]).unwrap();
+        let reversed = gamma.reverse().unwrap();
+        assert_eq!(reversed.parametric_type(), -1);
+
+        let table = (0..256u32).map(|i| (i * 65535 / 255) as u16).collect::<Vec<_>>();
+        let squared = table
+            .iter()
+            .map(|&v| ((v as f64 / 65535.0).powi(2) * 65535.0).round() as u16)
+            .collect::<Vec<_>>();
+        let squared = ToneCurve::build_tabulated_16(&DEFAULT_CONTEXT, &squared).unwrap();
+        let reversed = squared.reverse().unwrap();
+
+        for v in [0.1f32, 0.25, 0.5, 0.9] {
+            assert!((reversed.eval_f32(squared.eval_f32(v)) - v).abs() < 1e-2);
+            assert!((reversed.eval_f32(v) - v.sqrt()).abs() < 1e-2);
+        }
+    }
+
+    #[test]
+    fn reverse_descending() {
+        let curve = ToneCurve::build_tabulated_16(&DEFAULT_CONTEXT, &[0xffff, 0x8000, 0]).unwrap();
+        assert!(curve.is_descending());
+
+        let reversed = curve.reverse_ex(3).unwrap();
+        assert!(reversed.is_descending());
+        assert_eq!(reversed.table_16(), &[0xffff, 0x8000, 0]);
+
+        assert!(curve.reverse_ex(1).is_err());
+    }
 }
@@ -1,7 +1,8 @@
 use super::{ToneCurve, MLU};
 
-pub struct UcrBg<'a> {
-    pub ucr: ToneCurve<'a>,
-    pub bg: ToneCurve<'a>,
+#[derive(Clone)]
+pub struct UcrBg {
+    pub ucr: ToneCurve,
+    pub bg: ToneCurve,
     pub desc: Box<MLU>,
 }
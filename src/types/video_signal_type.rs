@@ -1,6 +1,171 @@
+use crate::Context;
+
+use super::{ToneCurve, XYYTriple, XYY};
+
+/// Coding-independent code points, as defined in ITU-T H.273.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub struct VideoSignalType {
     pub color_primaries: u8,
     pub transfer_characteristics: u8,
     pub matrix_coefficients: u8,
     pub video_full_range_flag: u8,
 }
+
+// Inverse OETF of BT.709, also used by BT.601 and BT.2020
+const BT709_PARAMS: [f64; 5] = [1.0 / 0.45, 1.0 / 1.099, 0.099 / 1.099, 1.0 / 4.5, 0.081];
+
+// sRGB, as IEC 61966-2-1
+const SRGB_PARAMS: [f64; 5] = [2.4, 1.0 / 1.055, 0.055 / 1.055, 1.0 / 12.92, 0.04045];
+
+// Tabulated transfer functions are sampled with this many points
+const TRANSFER_SAMPLES: usize = 4096;
+
+const D65: XYY = XYY {
+    x: 0.3127,
+    y: 0.3290,
+    y_lum: 1.0,
+};
+
+const fn primaries(rx: f64, ry: f64, gx: f64, gy: f64, bx: f64, by: f64) -> XYYTriple {
+    XYYTriple {
+        red: XYY {
+            x: rx,
+            y: ry,
+            y_lum: 1.0,
+        },
+        green: XYY {
+            x: gx,
+            y: gy,
+            y_lum: 1.0,
+        },
+        blue: XYY {
+            x: bx,
+            y: by,
+            y_lum: 1.0,
+        },
+    }
+}
+
+impl VideoSignalType {
+    /// BT.709 primaries and transfer, full range RGB.
+    pub const BT709: VideoSignalType = VideoSignalType::rgb(1, 1);
+    /// BT.2020 primaries and transfer, full range RGB.
+    pub const BT2020: VideoSignalType = VideoSignalType::rgb(9, 14);
+    /// BT.2100 with the perceptual quantizer (SMPTE ST 2084), as used by HDR10.
+    pub const BT2100_PQ: VideoSignalType = VideoSignalType::rgb(9, 16);
+    /// BT.2100 with hybrid log-gamma (ARIB STD-B67).
+    pub const BT2100_HLG: VideoSignalType = VideoSignalType::rgb(9, 18);
+    /// sRGB, as IEC 61966-2-1: BT.709 primaries with the sRGB transfer.
+    pub const SRGB: VideoSignalType = VideoSignalType::rgb(1, 13);
+    /// P3 primaries with a D65 white and the sRGB transfer.
+    pub const DISPLAY_P3: VideoSignalType = VideoSignalType::rgb(12, 13);
+
+    // ICC profiles carry RGB, so matrix coefficients are always identity
+    const fn rgb(color_primaries: u8, transfer_characteristics: u8) -> VideoSignalType {
+        VideoSignalType {
+            color_primaries,
+            transfer_characteristics,
+            matrix_coefficients: 0,
+            video_full_range_flag: 1,
+        }
+    }
+
+    /// Returns the white point and the RGB primaries, or `None` if the code point is not supported.
+    pub fn primaries(&self) -> Option<(XYY, XYYTriple)> {
+        let triple = match self.color_primaries {
+            // BT.709, sRGB
+            1 => primaries(0.640, 0.330, 0.300, 0.600, 0.150, 0.060),
+            // BT.2020, BT.2100
+            9 => primaries(0.708, 0.292, 0.170, 0.797, 0.131, 0.046),
+            // SMPTE EG 432-1, Display P3
+            12 => primaries(0.680, 0.320, 0.265, 0.690, 0.150, 0.060),
+            _ => return None,
+        };
+
+        Some((D65, triple))
+    }
+
+    /// Builds the curve that maps encoded values to linear light, or `None` if the code point is
+    /// not supported. PQ is normalized so 1.0 stands for 10000 cd/m², HLG yields scene light.
+    pub fn transfer_function(&self, context_id: &Context) -> Option<ToneCurve> {
+        let curve = match self.transfer_characteristics {
+            1 | 6 | 14 | 15 => ToneCurve::build_parametric(context_id, 4, &BT709_PARAMS),
+            4 => ToneCurve::build_parametric(context_id, 1, &[2.2]),
+            5 => ToneCurve::build_parametric(context_id, 1, &[2.8]),
+            8 => ToneCurve::build_parametric(context_id, 1, &[1.0]),
+            13 => ToneCurve::build_parametric(context_id, 4, &SRGB_PARAMS),
+            16 => ToneCurve::build_tabulated_float(context_id, &sample(pq_eotf)),
+            18 => ToneCurve::build_tabulated_float(context_id, &sample(hlg_inverse_oetf)),
+            _ => return None,
+        };
+
+        curve.ok()
+    }
+}
+
+fn sample(f: fn(f64) -> f64) -> Vec<f32> {
+    (0..TRANSFER_SAMPLES)
+        .map(|i| f(i as f64 / (TRANSFER_SAMPLES - 1) as f64) as f32)
+        .collect()
+}
+
+// SMPTE ST 2084
+fn pq_eotf(v: f64) -> f64 {
+    const M1: f64 = 2610.0 / 16384.0;
+    const M2: f64 = 2523.0 / 4096.0 * 128.0;
+    const C1: f64 = 3424.0 / 4096.0;
+    const C2: f64 = 2413.0 / 4096.0 * 32.0;
+    const C3: f64 = 2392.0 / 4096.0 * 32.0;
+
+    let p = v.powf(1.0 / M2);
+    ((p - C1).max(0.0) / (C2 - C3 * p)).powf(1.0 / M1)
+}
+
+// ARIB STD-B67
+fn hlg_inverse_oetf(v: f64) -> f64 {
+    const A: f64 = 0.17883277;
+    const B: f64 = 0.28466892;
+    const C: f64 = 0.55991073;
+
+    if v <= 0.5 {
+        v * v / 3.0
+    } else {
+        (((v - C) / A).exp() + B) / 12.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{types::VideoSignalType, DEFAULT_CONTEXT};
+
+    fn eval(cicp: VideoSignalType, v: f32) -> f32 {
+        cicp.transfer_function(&DEFAULT_CONTEXT).unwrap().eval_f32(v)
+    }
+
+    #[test]
+    fn transfer_functions() {
+        assert!((eval(VideoSignalType::SRGB, 0.5) - 0.2140).abs() < 1e-4);
+        assert!((eval(VideoSignalType::BT709, 0.5) - 0.2596).abs() < 1e-4);
+
+        // 0.5 is about 92 cd/m² in PQ, and the top of the range is 10000 cd/m²
+        assert!((eval(VideoSignalType::BT2100_PQ, 0.5) - 0.00922).abs() < 1e-4);
+        assert!((eval(VideoSignalType::BT2100_PQ, 1.0) - 1.0).abs() < 1e-4);
+
+        // HLG is square law up to 1/12 of the range
+        assert!((eval(VideoSignalType::BT2100_HLG, 0.5) - 1.0 / 12.0).abs() < 1e-4);
+        assert!((eval(VideoSignalType::BT2100_HLG, 1.0) - 1.0).abs() < 1e-3);
+
+        let unknown = VideoSignalType { transfer_characteristics: 2, ..VideoSignalType::SRGB };
+        assert!(unknown.transfer_function(&DEFAULT_CONTEXT).is_none());
+    }
+
+    #[test]
+    fn primaries() {
+        let (white, rgb) = VideoSignalType::BT2020.primaries().unwrap();
+        assert_eq!((white.x, white.y), (0.3127, 0.3290));
+        assert_eq!((rgb.red.x, rgb.green.y, rgb.blue.x), (0.708, 0.797, 0.131));
+
+        let unknown = VideoSignalType { color_primaries: 2, ..VideoSignalType::SRGB };
+        assert!(unknown.primaries().is_none());
+    }
+}
@@ -1,5 +1,6 @@
 use super::XYZ;
 
+#[derive(Clone, Copy)]
 pub struct ViewingConditions {
     pub illuminant: XYZ,
     pub surround: XYZ,
@@ -1,3 +1,5 @@
+use super::XYZ;
+
 #[derive(Copy, Clone)]
 pub struct XYY {
     pub x: f64,
@@ -5,6 +7,16 @@ pub struct XYY {
     pub y_lum: f64,
 }
 
+impl XYY {
+    pub fn to_xyz(&self) -> XYZ {
+        XYZ {
+            x: (self.x / self.y) * self.y_lum,
+            y: self.y_lum,
+            z: ((1.0 - self.x - self.y) / self.y) * self.y_lum,
+        }
+    }
+}
+
 #[derive(Copy, Clone)]
 pub struct XYYTriple {
     pub red: XYY,
@@ -1,5 +1,7 @@
 use crate::s15f16;
 
+use super::{Lab, XYY};
+
 #[derive(Copy, Clone)]
 pub struct XYZ {
     pub x: f64,
@@ -7,6 +9,42 @@ pub struct XYZ {
     pub z: f64,
 }
 
+impl XYZ {
+    pub fn to_xyy(&self) -> XYY {
+        let i_sum = 1.0 / (self.x + self.y + self.z);
+
+        XYY {
+            x: self.x * i_sum,
+            y: self.y * i_sum,
+            y_lum: self.y,
+        }
+    }
+
+    /// Converts to Lab, relative to the given white point.
+    pub fn to_lab(&self, white_point: XYZ) -> Lab {
+        let fx = f(self.x / white_point.x);
+        let fy = f(self.y / white_point.y);
+        let fz = f(self.z / white_point.z);
+
+        Lab {
+            l: 116.0 * fy - 16.0,
+            a: 500.0 * (fx - fy),
+            b: 200.0 * (fy - fz),
+        }
+    }
+}
+
+// The Lab companding function, linear near black
+fn f(t: f64) -> f64 {
+    const LIMIT: f64 = (24.0 / 116.0) * (24.0 / 116.0) * (24.0 / 116.0);
+
+    if t <= LIMIT {
+        return (841.0 / 108.0) * t + (16.0 / 116.0);
+    }
+
+    t.powf(1.0 / 3.0)
+}
+
 #[derive(Copy, Clone)]
 pub struct EncodedXYZ {
     pub x: s15f16,
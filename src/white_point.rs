@@ -0,0 +1,110 @@
+use crate::{
+    cms::D50,
+    types::{Mat3, Vec3, XYYTriple, XYY, XYZ},
+    MATRIX_DET_TOLERANCE,
+};
+
+// Bradford matrix
+const LAM_RIGG: Mat3 = Mat3::from_array([
+    0.8951, 0.2664, -0.1614, //
+    -0.7502, 1.7135, 0.0367, //
+    0.0389, -0.0685, 1.0296,
+]);
+
+// Obtains the chromaticity of a daylight illuminant by its correlated color temperature, valid
+// from 4000K to 25000K
+pub(crate) fn white_point_from_temp(temp_k: f64) -> Option<XYY> {
+    let t = temp_k;
+    let t2 = t * t; // Square
+    let t3 = t2 * t; // Cube
+
+    let x = if (4000.0..=7000.0).contains(&t) {
+        // For correlated color temperature (T) between 4000K and 7000K:
+        -4.6070 * (1E9 / t3) + 2.9678 * (1E6 / t2) + 0.09911 * (1E3 / t) + 0.244063
+    } else if t > 7000.0 && t <= 25000.0 {
+        // or for correlated color temperature (T) between 7000K and 25000K:
+        -2.0064 * (1E9 / t3) + 1.9018 * (1E6 / t2) + 0.24748 * (1E3 / t) + 0.237040
+    } else {
+        return None;
+    };
+
+    // Obtain y(x)
+    let y = -3.000 * (x * x) + 2.870 * x - 0.275;
+
+    Some(XYY { x, y, y_lum: 1.0 })
+}
+
+// Compute chromatic adaptation matrix using Chad as cone matrix
+fn compute_chromatic_adaptation(source_white_point: XYZ, dest_white_point: XYZ, chad: &Mat3) -> Option<Mat3> {
+    let chad_inv = chad.inverse()?;
+
+    let cone_source_rgb = chad.eval(&Vec3::new(source_white_point.x, source_white_point.y, source_white_point.z));
+    let cone_dest_rgb = chad.eval(&Vec3::new(dest_white_point.x, dest_white_point.y, dest_white_point.z));
+
+    if cone_source_rgb.x.abs() < MATRIX_DET_TOLERANCE
+        || cone_source_rgb.y.abs() < MATRIX_DET_TOLERANCE
+        || cone_source_rgb.z.abs() < MATRIX_DET_TOLERANCE
+    {
+        return None;
+    }
+
+    // Build matrix
+    let cone = Mat3::new(
+        Vec3::new(cone_dest_rgb.x / cone_source_rgb.x, 0.0, 0.0),
+        Vec3::new(0.0, cone_dest_rgb.y / cone_source_rgb.y, 0.0),
+        Vec3::new(0.0, 0.0, cone_dest_rgb.z / cone_source_rgb.z),
+    );
+
+    // Normalize
+    Some(chad_inv.per(&cone.per(chad)))
+}
+
+/// Returns the matrix that adapts colors from one illuminant to another. Uses Bradford when no
+/// cone matrix is given.
+pub(crate) fn adaptation_matrix(cone_matrix: Option<&Mat3>, from_ill: XYZ, to_ill: XYZ) -> Option<Mat3> {
+    compute_chromatic_adaptation(from_ill, to_ill, cone_matrix.unwrap_or(&LAM_RIGG))
+}
+
+// Same as anterior, but assuming D50 destination. White point is given in xyY
+fn adapt_matrix_to_d50(r: &Mat3, source_white_pt: XYY) -> Option<Mat3> {
+    let bradford = adaptation_matrix(None, source_white_pt.to_xyz(), D50)?;
+
+    Some(bradford.per(r))
+}
+
+/// Builds the matrix that converts RGB to D50-adapted XYZ, given the primaries and white point
+/// of the RGB space.
+pub(crate) fn build_rgb_to_xyz_transfer_matrix(white_pt: XYY, primaries: &XYYTriple) -> Option<Mat3> {
+    let (xn, yn) = (white_pt.x, white_pt.y);
+    let (xr, yr) = (primaries.red.x, primaries.red.y);
+    let (xg, yg) = (primaries.green.x, primaries.green.y);
+    let (xb, yb) = (primaries.blue.x, primaries.blue.y);
+
+    // Build Primaries matrix
+    let primaries = Mat3::new(
+        Vec3::new(xr, xg, xb),
+        Vec3::new(yr, yg, yb),
+        Vec3::new(1.0 - xr - yr, 1.0 - xg - yg, 1.0 - xb - yb),
+    );
+
+    // Result = Primaries ^ (-1) inverse matrix
+    let result = primaries.inverse()?;
+
+    let white_point = Vec3::new(xn / yn, 1.0, (1.0 - xn - yn) / yn);
+
+    // Across inverse primaries ...
+    let coef = result.eval(&white_point);
+
+    // Give us the Coefs, then I build transformation matrix
+    let r = Mat3::new(
+        Vec3::new(coef.x * xr, coef.y * xg, coef.z * xb),
+        Vec3::new(coef.x * yr, coef.y * yg, coef.z * yb),
+        Vec3::new(
+            coef.x * (1.0 - xr - yr),
+            coef.y * (1.0 - xg - yg),
+            coef.z * (1.0 - xb - yb),
+        ),
+    );
+
+    adapt_matrix_to_d50(&r, white_pt)
+}